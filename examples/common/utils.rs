@@ -1,5 +1,6 @@
 use audio_gate::NoiseGate;
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use gemini::v1beta::audio::{Resampler, downmix_to_mono};
 use std::ops::Deref;
 use std::pin::Pin;
 use tokio::io::{self, AsyncBufReadExt, BufReader};
@@ -143,6 +144,9 @@ impl std::io::Seek for StreamMediaSource {
 /// Creates a stream from the default input device returning audio as [`Stream`] of chunks.
 #[tracing::instrument(skip(output), fields(sample_rate = output.sample_rate, channels = output.channels, bits_per_sample = output.bits_per_sample, batch_size = output.batch_size))]
 pub async fn listen_from_default_input(output: OutputAudioConfig) -> Result<(Stream<Vec<u8>>, Input), Box<dyn std::error::Error>> {
+    if output.channels != 1 {
+        return Err("only mono capture is supported".into());
+    }
     let host = cpal::default_host();
     let device = host.default_input_device().ok_or("no input device")?;
     let device_config = device.default_input_config()?;
@@ -164,28 +168,15 @@ pub async fn listen_from_default_input(output: OutputAudioConfig) -> Result<(Str
         25.0,
         150.0,
     );
+    let mut resampler = Resampler::new(device_config.sample_rate().0, output.sample_rate);
     let mut buffer = Vec::with_capacity(output.batch_size);
     let stream = match device_config.sample_format() {
         cpal::SampleFormat::F32 => device.build_input_stream(
             &config,
             move |data: &[f32], _| {
-                let mut processed = gate.process_frame(data);
-                if device_config.channels() != output.channels {
-                    processed = convert_channels(
-                        &processed,
-                        device_config.channels() as usize,
-                        output.channels as usize,
-                    );
-                }
-                if device_config.sample_rate().0 != output.sample_rate {
-                    processed = resample_linear(
-                        &processed,
-                        device_config.sample_rate().0,
-                        output.sample_rate,
-                        output.channels as usize,
-                    );
-                }
-                for sample in processed {
+                let gated = gate.process_frame(data);
+                let mono = downmix_to_mono(&gated, device_config.channels());
+                for sample in resampler.process(&mono) {
                     buffer.extend(sample_to_bytes(sample, output.bits_per_sample));
                     while buffer.len() >= output.batch_size {
                         let chunk: Vec<u8> = buffer.drain(..output.batch_size).collect();
@@ -225,53 +216,6 @@ pub fn stdin_stream() -> Pin<Box<dyn tokio_stream::Stream<Item = String> + Send>
     Box::pin(tokio_stream::wrappers::UnboundedReceiverStream::new(rx))
 }
 
-fn convert_channels(data: &[f32], in_ch: usize, out_ch: usize) -> Vec<f32> {
-    if in_ch == out_ch {
-        return data.to_vec();
-    }
-    let frames = data.len() / in_ch;
-    let mut out = Vec::with_capacity(frames * out_ch);
-    for frame in 0..frames {
-        for oc in 0..out_ch {
-            if out_ch == 1 {
-                let mut acc = 0f32;
-                for ic in 0..in_ch {
-                    acc += data[frame * in_ch + ic];
-                }
-                out.push(acc / in_ch as f32);
-            } else if in_ch == 1 {
-                out.push(data[frame]);
-            } else {
-                out.push(data[frame * in_ch + oc.min(in_ch - 1)]);
-            }
-        }
-    }
-    out
-}
-
-fn resample_linear(data: &[f32], in_rate: u32, out_rate: u32, channels: usize) -> Vec<f32> {
-    if in_rate == out_rate {
-        return data.to_vec();
-    }
-    let in_frames = data.len() / channels;
-    let ratio = out_rate as f64 / in_rate as f64;
-    let out_frames = ((in_frames as f64) * ratio).ceil() as usize;
-    let mut out = Vec::with_capacity(out_frames * channels);
-    for out_idx in 0..out_frames {
-        let src_pos = out_idx as f64 / ratio;
-        let src_idx = src_pos.floor() as usize;
-        let frac = src_pos - src_idx as f64;
-        for ch in 0..channels {
-            let i0 = std::cmp::min(src_idx, in_frames - 1) * channels + ch;
-            let i1 = std::cmp::min(src_idx + 1, in_frames - 1) * channels + ch;
-            let s0 = data[i0];
-            let s1 = data[i1];
-            out.push(((1.0 - frac) as f32) * s0 + (frac as f32) * s1);
-        }
-    }
-    out
-}
-
 fn sample_to_bytes(sample: f32, bits: u16) -> Vec<u8> {
     let clamped = sample.clamp(-1.0, 1.0);
     match bits {
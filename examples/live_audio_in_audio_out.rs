@@ -3,8 +3,8 @@
 
 use dotenv::dotenv;
 use gemini::v1beta::live::{
-    Client, ClientMessage, GenerationConfig, InlineData, PartData, RealtimeInput, ResponseModality,
-    ServerContent, ServerMessage, Setup,
+    Client, ClientMessage, GenerationConfig, InlineData, RealtimeInput, ResponseModality,
+    ServerMessage, Setup,
 };
 use tokio_stream::StreamExt;
 use tracing::info;
@@ -56,21 +56,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let audio_sender = sender_for_default_audio_output(AudioFormat::Pcm24Khz16BitMono);
 
     while let Some(msg) = stream.next().await {
+        let msg = msg?;
         info!("msg: {:?}", msg);
 
         if let ServerMessage::ServerContent { server_content, .. } = msg {
-            match server_content {
-                ServerContent::ModelTurn(content) => {
-                    for part in content.parts {
-                        match part.data {
-                            PartData::InlineData(inline_data) => {
-                                let _ = audio_sender.send(inline_data.data().to_vec());
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-                _ => {}
+            for inline_data in server_content.inline_data() {
+                let _ = audio_sender.send(inline_data.data().to_vec());
             }
         }
     }
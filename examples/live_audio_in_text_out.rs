@@ -52,6 +52,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     });
 
     while let Some(msg) = stream.next().await {
+        let msg = msg?;
         info!("message: {:?}", msg);
     }
 
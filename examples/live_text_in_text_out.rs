@@ -3,9 +3,10 @@
 
 use dotenv::dotenv;
 use gemini::v1beta::live::{
-    Client, ClientContent, ClientMessage, Content, GenerationConfig, Part, PartData,
-    ResponseModality, Role, ServerMessage, Setup,
+    ClientContent, ClientMessage, Content, Event, GenerationConfig, LiveSession, Part, PartData,
+    ResponseModality, Role, Setup,
 };
+use std::io::Write;
 use tokio_stream::StreamExt;
 use tracing::info;
 
@@ -39,9 +40,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 .with_response_modalities(vec![ResponseModality::Text]),
         );
 
-    let (client, mut stream) = Client::connect(&api_key, setup.clone()).await?;
+    let mut session = LiveSession::connect(&api_key, setup.clone()).await?;
 
-    let _client = client.clone();
+    let _client = session.client().clone();
     tokio::spawn(async move {
         let mut stream_in = utils::stdin_stream();
 
@@ -62,10 +63,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         }
     });
 
-    while let Some(msg) = stream.next().await {
-        info!("message: {:?}", msg);
+    while let Some(event) = session.next().await {
+        match event? {
+            Event::TextDelta(text) => {
+                print!("{text}");
+                std::io::stdout().flush()?;
+            }
+            Event::TurnComplete { .. } => println!(),
+            Event::Closed { reason } => {
+                info!("session closed: {reason:?}");
+                break;
+            }
+            event => info!("event: {:?}", event),
+        }
     }
 
-    client.disconnect(None)?;
+    session.close(None)?;
     Ok(())
 }
@@ -24,6 +24,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     .with_generation_config(GenerationConfig::new().with_max_output_tokens(64));
 
     let response = client.generate_content(request).await?;
-    println!("{:?}", response);
+    println!("{}", response.text().unwrap_or_default());
     Ok(())
 }
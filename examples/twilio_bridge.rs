@@ -0,0 +1,42 @@
+//! Answers phone calls with Gemini through Twilio Media Streams.
+//! Point a TwiML `<Connect><Stream url="wss://.../media"/></Connect>` at this server
+//! (behind a TLS proxy such as ngrok) and talk to the model over the phone.
+
+use dotenv::dotenv;
+use gemini::v1beta::live::{GenerationConfig, ResponseModality, Setup};
+use gemini::v1beta::twilio::Bridge;
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    dotenv().ok();
+
+    if std::env::var("RUST_LOG").is_err() {
+        unsafe {
+            std::env::set_var("RUST_LOG", "info");
+        }
+    }
+    tracing_subscriber::fmt::init();
+
+    let api_key = std::env::var("GEMINI_API_KEY").expect("GEMINI_API_KEY not set");
+    let model = std::env::var("GEMINI_LIVE_MODEL").expect("GEMINI_LIVE_MODEL not set");
+    let address = std::env::var("BRIDGE_ADDRESS").unwrap_or_else(|_| "0.0.0.0:8080".into());
+
+    let setup = Setup::new(format!("models/{model}")).with_generation_config(
+        GenerationConfig::default().with_response_modalities(vec![ResponseModality::Audio]),
+    );
+
+    let listener = TcpListener::bind(&address).await?;
+    info!("waiting for media streams on {address}");
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let bridge = Bridge::new(api_key.clone(), setup.clone());
+        tokio::spawn(async move {
+            info!("call from {peer}");
+            if let Err(e) = bridge.accept(stream).await {
+                warn!("call from {peer} failed: {e}");
+            }
+        });
+    }
+}
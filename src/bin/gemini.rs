@@ -0,0 +1,550 @@
+//! Command-line client for the Gemini API.
+//!
+//! Reads the API key from `GEMINI_API_KEY`, also loaded from a `.env` file.
+//! Every command accepts `--json` to print API responses as JSON for scripting.
+
+use clap::{Args, Parser, Subcommand};
+use gemini::v1beta::{
+    Content, Part, PartData, Role,
+    chat::Chat,
+    live, media,
+    request::{
+        EmbedContentRequest, GenerationConfig, Request, SystemInstructionContent,
+        SystemInstructionPart, TaskType,
+    },
+    response::Response,
+    rest::Client,
+};
+use serde::Serialize;
+use std::io::{IsTerminal, Read, Write};
+use std::path::PathBuf;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio_stream::StreamExt;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+const DEFAULT_MODEL: &str = "gemini-2.5-flash";
+const DEFAULT_EMBEDDING_MODEL: &str = "gemini-embedding-001";
+const DEFAULT_LIVE_MODEL: &str = "gemini-live-2.5-flash-preview";
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "gemini",
+    version,
+    about = "Command-line client for the Gemini API"
+)]
+struct Cli {
+    #[arg(long, env = "GEMINI_API_KEY", hide_env_values = true)]
+    api_key: String,
+    /// Model to use; each command has its own default.
+    #[arg(long, short, env = "GEMINI_MODEL", global = true)]
+    model: Option<String>,
+    /// Override the REST endpoint, e.g. for a proxy.
+    #[arg(long, global = true)]
+    api_base: Option<String>,
+    /// Print responses as JSON.
+    #[arg(long, global = true)]
+    json: bool,
+    /// Override the Live API websocket endpoint.
+    #[arg(long, env = "GEMINI_LIVE_ENDPOINT", global = true)]
+    live_endpoint: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Generate a response to a prompt from the arguments, stdin and files.
+    Generate {
+        #[command(flatten)]
+        prompt: PromptArgs,
+        #[command(flatten)]
+        generation: GenerationArgs,
+        /// Wait for the whole response instead of streaming it.
+        #[arg(long)]
+        no_stream: bool,
+    },
+    /// Interactive conversation; `/clear` forgets the history, `/exit` quits.
+    Chat {
+        #[command(flatten)]
+        generation: GenerationArgs,
+    },
+    /// Count the tokens of a prompt.
+    CountTokens {
+        #[command(flatten)]
+        prompt: PromptArgs,
+    },
+    /// Embed text from the arguments, or stdin when there are none or `-`.
+    Embed {
+        text: Vec<String>,
+        /// e.g. `RETRIEVAL_QUERY` or `SEMANTIC_SIMILARITY`.
+        #[arg(long)]
+        task_type: Option<String>,
+        #[arg(long)]
+        dimensions: Option<u32>,
+    },
+    /// List or inspect models.
+    Models {
+        #[command(subcommand)]
+        command: ModelsCommand,
+    },
+    /// Manage files uploaded through the Files API.
+    Files {
+        #[command(subcommand)]
+        command: FilesCommand,
+    },
+    /// Text session over the Live API websocket.
+    Live {
+        #[arg(long)]
+        system: Option<String>,
+    },
+    /// Serve an OpenAI-compatible API backed by Gemini.
+    #[cfg(feature = "openai")]
+    Serve {
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        addr: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ModelsCommand {
+    List,
+    Get { name: String },
+}
+
+#[derive(Debug, Subcommand)]
+enum FilesCommand {
+    Upload {
+        path: PathBuf,
+        #[arg(long)]
+        display_name: Option<String>,
+        /// Detected from the content or extension when omitted.
+        #[arg(long)]
+        mime_type: Option<String>,
+    },
+    List,
+    Delete {
+        name: String,
+    },
+}
+
+#[derive(Debug, Args)]
+struct PromptArgs {
+    /// Prompt text; `-` reads stdin, as does giving no prompt at all.
+    prompt: Vec<String>,
+    /// Attach a file inline; may be repeated.
+    #[arg(long = "file", short = 'f')]
+    files: Vec<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct GenerationArgs {
+    #[arg(long)]
+    system: Option<String>,
+    #[arg(long)]
+    temperature: Option<f32>,
+    #[arg(long)]
+    max_tokens: Option<i32>,
+}
+
+#[tokio::main]
+async fn main() {
+    dotenv::dotenv().ok();
+    let cli = Cli::parse();
+    if let Err(e) = run(cli).await {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let model = |default: &str| cli.model.clone().unwrap_or_else(|| default.to_string());
+    let client = |model: String| {
+        let client = Client::new(&cli.api_key, model);
+        match &cli.api_base {
+            Some(api_base) => client.with_api_base(api_base),
+            None => client,
+        }
+    };
+
+    match &cli.command {
+        Command::Generate {
+            prompt,
+            generation,
+            no_stream,
+        } => {
+            let request = generation.apply(Request::new(vec![prompt.content()?]));
+            generate(&client(model(DEFAULT_MODEL)), request, !no_stream, cli.json).await
+        }
+        Command::Chat { generation } => {
+            chat(client(model(DEFAULT_MODEL)), generation, cli.json).await
+        }
+        Command::CountTokens { prompt } => {
+            let request = Request::new(vec![prompt.content()?]);
+            let count = client(model(DEFAULT_MODEL)).count_tokens(request).await?;
+            print(cli.json, &count, |count| count.total_tokens.to_string())
+        }
+        Command::Embed {
+            text,
+            task_type,
+            dimensions,
+        } => {
+            let text = text_or_stdin(text)?;
+            let mut request = EmbedContentRequest::new(user(vec![text_part(text)]));
+            if let Some(task_type) = task_type {
+                let task_type: TaskType = serde_json::from_value(task_type.to_uppercase().into())
+                    .map_err(|_| format!("unknown task type {task_type}"))?;
+                request = request.with_task_type(task_type);
+            }
+            if let Some(dimensions) = dimensions {
+                request = request.with_output_dimensionality(*dimensions);
+            }
+            let response = client(model(DEFAULT_EMBEDDING_MODEL))
+                .embed_content(request)
+                .await?;
+            print(cli.json, &response, |response| {
+                serde_json::to_string(&response.embedding.values).unwrap_or_default()
+            })
+        }
+        Command::Models { command } => {
+            let client = client(model(DEFAULT_MODEL));
+            match command {
+                ModelsCommand::List => {
+                    let mut models = Vec::new();
+                    let mut page_token = None;
+                    loop {
+                        let page = client.list_models(page_token.as_deref()).await?;
+                        models.extend(page.models);
+                        page_token = page.next_page_token.filter(|token| !token.is_empty());
+                        if page_token.is_none() {
+                            break;
+                        }
+                    }
+                    print(cli.json, &models, |models| {
+                        models
+                            .iter()
+                            .map(|model| {
+                                let name = model.display_name.as_deref().unwrap_or_default();
+                                format!("{}\t{name}", model.name)
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                }
+                ModelsCommand::Get { name } => {
+                    let model = client.get_model(name).await?;
+                    print(cli.json, &model, |model| {
+                        let mut lines = vec![model.name.clone()];
+                        if let Some(display_name) = &model.display_name {
+                            lines.push(format!("display name: {display_name}"));
+                        }
+                        if let Some(limit) = model.input_token_limit {
+                            lines.push(format!("input token limit: {limit}"));
+                        }
+                        if let Some(limit) = model.output_token_limit {
+                            lines.push(format!("output token limit: {limit}"));
+                        }
+                        lines.join("\n")
+                    })
+                }
+            }
+        }
+        Command::Files { command } => {
+            let client = client(model(DEFAULT_MODEL));
+            match command {
+                FilesCommand::Upload {
+                    path,
+                    display_name,
+                    mime_type,
+                } => {
+                    let bytes = std::fs::read(path)?;
+                    let mime_type = match mime_type {
+                        Some(mime_type) => mime_type.as_str(),
+                        None => media::detect_mime_type(&bytes)
+                            .or_else(|| media::mime_type_from_extension(path))
+                            .ok_or_else(|| format!("unknown MIME type of {}", path.display()))?,
+                    };
+                    let display_name = display_name
+                        .clone()
+                        .or_else(|| Some(path.file_name()?.to_string_lossy().into_owned()));
+                    let file = client
+                        .upload_file(bytes, mime_type, display_name.as_deref())
+                        .await?;
+                    print(cli.json, &file, |file| {
+                        format!("{}\t{}", file.name, file.uri.as_deref().unwrap_or_default())
+                    })
+                }
+                FilesCommand::List => {
+                    let mut files = Vec::new();
+                    let mut page_token = None;
+                    loop {
+                        let page = client.list_files(page_token.as_deref()).await?;
+                        files.extend(page.files);
+                        page_token = page.next_page_token.filter(|token| !token.is_empty());
+                        if page_token.is_none() {
+                            break;
+                        }
+                    }
+                    print(cli.json, &files, |files| {
+                        files
+                            .iter()
+                            .map(|file| {
+                                let mime_type = file.mime_type.as_deref().unwrap_or_default();
+                                let uri = file.uri.as_deref().unwrap_or_default();
+                                format!("{}\t{mime_type}\t{uri}", file.name)
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                }
+                FilesCommand::Delete { name } => Ok(client.delete_file(name).await?),
+            }
+        }
+        Command::Live { system } => {
+            let mut options = live::ConnectOptions::new();
+            if let Some(endpoint) = &cli.live_endpoint {
+                options = options.with_endpoint(endpoint);
+            }
+            live(
+                &cli.api_key,
+                model(DEFAULT_LIVE_MODEL),
+                system,
+                options,
+                cli.json,
+            )
+            .await
+        }
+        #[cfg(feature = "openai")]
+        Command::Serve { addr } => {
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            eprintln!(
+                "serving the OpenAI API on http://{}/v1",
+                listener.local_addr()?
+            );
+            let router = gemini::v1beta::openai::router(client(model(DEFAULT_MODEL)));
+            Ok(axum::serve(listener, router).await?)
+        }
+    }
+}
+
+impl PromptArgs {
+    /// The prompt arguments, stdin and attached files as one user content.
+    ///
+    /// Stdin is read for a `-` argument, or when there are no arguments and
+    /// it is not a terminal.
+    fn content(&self) -> Result<Content> {
+        let mut parts = Vec::new();
+        let text: Vec<&str> = self
+            .prompt
+            .iter()
+            .map(String::as_str)
+            .filter(|arg| *arg != "-")
+            .collect();
+        if !text.is_empty() {
+            parts.push(text_part(text.join(" ")));
+        }
+        let read_stdin = if self.prompt.is_empty() {
+            !std::io::stdin().is_terminal()
+        } else {
+            text.len() < self.prompt.len()
+        };
+        if read_stdin {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input)?;
+            if !input.trim().is_empty() {
+                parts.push(text_part(input));
+            }
+        }
+        for path in &self.files {
+            parts.push(Part::from_path(path)?);
+        }
+        if parts.is_empty() {
+            return Err("no prompt given; pass it as arguments or on stdin".into());
+        }
+        Ok(user(parts))
+    }
+}
+
+impl GenerationArgs {
+    fn apply(&self, request: Request) -> Request {
+        let request = request.with_generation_config(self.generation_config());
+        match self.system_instruction() {
+            Some(instruction) => request.with_system_instruction(instruction),
+            None => request,
+        }
+    }
+
+    fn generation_config(&self) -> GenerationConfig {
+        let mut config = GenerationConfig::new();
+        if let Some(temperature) = self.temperature {
+            config = config.with_temperature(temperature);
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            config = config.with_max_output_tokens(max_tokens);
+        }
+        config
+    }
+
+    fn system_instruction(&self) -> Option<SystemInstructionContent> {
+        let system = self.system.clone()?;
+        Some(SystemInstructionContent::new(vec![
+            SystemInstructionPart::new(system),
+        ]))
+    }
+}
+
+async fn generate(client: &Client, request: Request, stream: bool, json: bool) -> Result<()> {
+    if !stream {
+        let response = client.generate_content(request).await?;
+        return print(json, &response, |response| {
+            response.text().unwrap_or_default()
+        });
+    }
+
+    let mut chunks = client.stream_content(request).await?;
+    let mut stdout = std::io::stdout();
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk?;
+        if json {
+            writeln!(stdout, "{}", serde_json::to_string(&chunk)?)?;
+        } else if let Some(text) = chunk.text() {
+            write!(stdout, "{text}")?;
+            stdout.flush()?;
+        }
+    }
+    if !json {
+        writeln!(stdout)?;
+    }
+    Ok(())
+}
+
+async fn chat(client: Client, generation: &GenerationArgs, json: bool) -> Result<()> {
+    let mut chat = Chat::new(client).with_generation_config(generation.generation_config());
+    if let Some(instruction) = generation.system_instruction() {
+        chat = chat.with_system_instruction(instruction);
+    }
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    loop {
+        prompt_marker()?;
+        let Some(line) = lines.next_line().await? else {
+            break;
+        };
+        match line.trim() {
+            "" => continue,
+            "/exit" => break,
+            "/clear" => {
+                chat.clear();
+                continue;
+            }
+            _ => {}
+        }
+        let response: Response = chat.send(user(vec![text_part(line)])).await?;
+        print(json, &response, |response| {
+            response.text().unwrap_or_default()
+        })?;
+    }
+    Ok(())
+}
+
+async fn live(
+    api_key: &str,
+    model: String,
+    system: &Option<String>,
+    options: live::ConnectOptions,
+    json: bool,
+) -> Result<()> {
+    let model = if model.starts_with("models/") {
+        model
+    } else {
+        format!("models/{model}")
+    };
+    let mut setup = live::Setup::new(model).with_generation_config(
+        live::GenerationConfig::default()
+            .with_response_modalities(vec![live::ResponseModality::Text]),
+    );
+    if let Some(system) = system {
+        setup = setup.with_system_instruction(live::Content::new(
+            None,
+            vec![live::Part::new(live::PartData::Text(system.clone()))],
+        ));
+    }
+    let (client, mut messages) =
+        live::Client::connect_with_options(api_key, setup, options).await?;
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = std::io::stdout();
+    loop {
+        prompt_marker()?;
+        let Some(line) = lines.next_line().await? else {
+            break;
+        };
+        if line.trim() == "/exit" {
+            break;
+        }
+        client.call(live::ClientMessage::ClientContent(
+            live::ClientContent::new(vec![live::Content::new(
+                live::Role::User,
+                vec![live::Part::new(live::PartData::Text(line))],
+            )])
+            .is_turn_completed(true),
+        ))?;
+
+        while let Some(message) = messages.next().await {
+            let live::ServerMessage::ServerContent { server_content, .. } = message? else {
+                continue;
+            };
+            if json {
+                serde_json::to_writer(&mut stdout, &server_content)?;
+                writeln!(stdout)?;
+            } else if let Some(text) = server_content.text() {
+                write!(stdout, "{text}")?;
+                stdout.flush()?;
+            }
+            if server_content.turn_complete {
+                break;
+            }
+        }
+        if !json {
+            writeln!(stdout)?;
+        }
+    }
+
+    client.disconnect(None)?;
+    Ok(())
+}
+
+/// Print `value` as JSON, or as `text` renders it.
+fn print<T: Serialize>(json: bool, value: &T, text: impl FnOnce(&T) -> String) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(value)?);
+    } else {
+        println!("{}", text(value));
+    }
+    Ok(())
+}
+
+fn prompt_marker() -> Result<()> {
+    if std::io::stdin().is_terminal() {
+        print!("> ");
+        std::io::stdout().flush()?;
+    }
+    Ok(())
+}
+
+fn text_or_stdin(text: &[String]) -> Result<String> {
+    if !text.is_empty() && text != ["-"] {
+        return Ok(text.join(" "));
+    }
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+    Ok(input)
+}
+
+fn text_part(text: String) -> Part {
+    Part::new(PartData::Text(text))
+}
+
+fn user(parts: Vec<Part>) -> Content {
+    Content::new(Role::User, parts)
+}
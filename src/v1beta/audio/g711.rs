@@ -0,0 +1,186 @@
+//! G.711 mu-law and A-law, as used on telephone lines.
+//!
+//! Encoding and decoding follow the ITU-T G.711 reference implementation
+//! (the Sun `g711.c` also used by sox), so results are bit-exact with it.
+use super::{INPUT_SAMPLE_RATE, Resampler, f32_to_i16, i16_to_f32};
+
+/// Sample rate of G.711 telephony audio.
+pub const TELEPHONY_SAMPLE_RATE: u32 = 8_000;
+
+/// Largest magnitude mu-law can represent, in 14-bit units.
+const MU_LAW_CLIP: i16 = 8159;
+/// Bias added before mu-law segment lookup.
+const MU_LAW_BIAS: i16 = 0x84;
+/// Segment end points of mu-law, in 14-bit units.
+const MU_LAW_SEGMENTS: [i16; 8] = [0x3F, 0x7F, 0xFF, 0x1FF, 0x3FF, 0x7FF, 0xFFF, 0x1FFF];
+/// Segment end points of A-law, in 13-bit units.
+const A_LAW_SEGMENTS: [i16; 8] = [0x1F, 0x3F, 0x7F, 0xFF, 0x1FF, 0x3FF, 0x7FF, 0xFFF];
+
+const SIGN_BIT: u8 = 0x80;
+const QUANT_MASK: u8 = 0x0F;
+const SEG_SHIFT: u8 = 4;
+
+/// A G.711 companding law.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum G711 {
+    /// mu-law, used in North America and Japan (and by Twilio).
+    MuLaw,
+    /// A-law, used in Europe and most other countries.
+    ALaw,
+}
+
+impl G711 {
+    /// Encode 16-bit samples, one byte per sample.
+    pub fn encode(self, samples: &[i16]) -> Vec<u8> {
+        let encode = match self {
+            Self::MuLaw => linear_to_mu_law,
+            Self::ALaw => linear_to_a_law,
+        };
+        samples.iter().map(|&sample| encode(sample)).collect()
+    }
+
+    /// Decode bytes to 16-bit samples.
+    pub fn decode(self, bytes: &[u8]) -> Vec<i16> {
+        let decode = match self {
+            Self::MuLaw => mu_law_to_linear,
+            Self::ALaw => a_law_to_linear,
+        };
+        bytes.iter().map(|&byte| decode(byte)).collect()
+    }
+
+    /// MIME type of the encoding at 8 kHz, e.g. `audio/x-mulaw`.
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            Self::MuLaw => "audio/x-mulaw",
+            Self::ALaw => "audio/x-alaw",
+        }
+    }
+}
+
+/// Index of the first segment whose end point is at least `value`.
+fn segment(value: i16, segments: &[i16; 8]) -> usize {
+    segments
+        .iter()
+        .position(|&end| value <= end)
+        .unwrap_or(segments.len())
+}
+
+/// Encode one 16-bit sample as mu-law.
+pub fn linear_to_mu_law(sample: i16) -> u8 {
+    let mut value = sample >> 2;
+    let mask = if value < 0 {
+        value = -value;
+        0x7F
+    } else {
+        0xFF
+    };
+    let value = value.min(MU_LAW_CLIP) + (MU_LAW_BIAS >> 2);
+    let segment = segment(value, &MU_LAW_SEGMENTS);
+    if segment >= 8 {
+        return 0x7F ^ mask;
+    }
+    let code = ((segment as u8) << SEG_SHIFT) | ((value >> (segment + 1)) as u8 & QUANT_MASK);
+    code ^ mask
+}
+
+/// Decode one mu-law byte to a 16-bit sample.
+pub fn mu_law_to_linear(byte: u8) -> i16 {
+    let byte = !byte;
+    let magnitude =
+        ((i16::from(byte & QUANT_MASK) << 3) + MU_LAW_BIAS) << ((byte & 0x70) >> SEG_SHIFT);
+    if byte & SIGN_BIT != 0 {
+        MU_LAW_BIAS - magnitude
+    } else {
+        magnitude - MU_LAW_BIAS
+    }
+}
+
+/// Encode one 16-bit sample as A-law.
+pub fn linear_to_a_law(sample: i16) -> u8 {
+    let mut value = sample >> 3;
+    let mask = if value >= 0 {
+        0xD5
+    } else {
+        value = -value - 1;
+        0x55
+    };
+    let segment = segment(value, &A_LAW_SEGMENTS);
+    if segment >= 8 {
+        return 0x7F ^ mask;
+    }
+    let shift = segment.max(1);
+    let code = ((segment as u8) << SEG_SHIFT) | ((value >> shift) as u8 & QUANT_MASK);
+    code ^ mask
+}
+
+/// Decode one A-law byte to a 16-bit sample.
+pub fn a_law_to_linear(byte: u8) -> i16 {
+    let byte = byte ^ 0x55;
+    let mantissa = i16::from(byte & QUANT_MASK) << 4;
+    let magnitude = match (byte & 0x70) >> SEG_SHIFT {
+        0 => mantissa + 8,
+        1 => mantissa + 0x108,
+        segment => (mantissa + 0x108) << (segment - 1),
+    };
+    if byte & SIGN_BIT != 0 {
+        magnitude
+    } else {
+        -magnitude
+    }
+}
+
+/// Turns 8 kHz G.711 from a phone line into 16 kHz PCM for Live input.
+#[derive(Debug, Clone)]
+pub struct TelephonyDecoder {
+    law: G711,
+    resampler: Resampler,
+}
+
+impl TelephonyDecoder {
+    pub fn new(law: G711) -> Self {
+        Self {
+            law,
+            resampler: Resampler::new(TELEPHONY_SAMPLE_RATE, INPUT_SAMPLE_RATE),
+        }
+    }
+
+    /// Decode and upsample the next chunk of G.711 bytes.
+    pub fn process(&mut self, bytes: &[u8]) -> Vec<i16> {
+        let samples = i16_to_f32(&self.law.decode(bytes));
+        f32_to_i16(&self.resampler.process(&samples))
+    }
+
+    /// Return the remaining samples and start over for a new stream.
+    pub fn flush(&mut self) -> Vec<i16> {
+        f32_to_i16(&self.resampler.flush())
+    }
+}
+
+/// Turns Live output PCM (24 kHz by default) into 8 kHz G.711 for a phone line.
+#[derive(Debug, Clone)]
+pub struct TelephonyEncoder {
+    law: G711,
+    resampler: Resampler,
+}
+
+impl TelephonyEncoder {
+    /// Encoder for 16-bit mono PCM at `sample_rate`, usually
+    /// [`super::OUTPUT_SAMPLE_RATE`].
+    pub fn new(law: G711, sample_rate: u32) -> Self {
+        Self {
+            law,
+            resampler: Resampler::new(sample_rate, TELEPHONY_SAMPLE_RATE),
+        }
+    }
+
+    /// Downsample and encode the next chunk of PCM samples.
+    pub fn process(&mut self, samples: &[i16]) -> Vec<u8> {
+        let samples = self.resampler.process(&i16_to_f32(samples));
+        self.law.encode(&f32_to_i16(&samples))
+    }
+
+    /// Return the remaining bytes and start over for a new stream.
+    pub fn flush(&mut self) -> Vec<u8> {
+        self.law.encode(&f32_to_i16(&self.resampler.flush()))
+    }
+}
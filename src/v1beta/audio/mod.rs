@@ -0,0 +1,260 @@
+//! Audio helpers for the Live API: sample conversion, downmixing, resampling,
+//! chunking and G.711 telephony codecs.
+//!
+//! The Live API takes 16-bit mono PCM at 16 kHz and answers at 24 kHz. These
+//! helpers bring microphone or telephony audio into that shape and back.
+mod g711;
+
+pub use g711::{
+    G711, TELEPHONY_SAMPLE_RATE, TelephonyDecoder, TelephonyEncoder, a_law_to_linear,
+    linear_to_a_law, linear_to_mu_law, mu_law_to_linear,
+};
+
+use super::live::{InlineData, RealtimeInput};
+use std::f64::consts::PI;
+use std::time::Duration;
+
+pub use super::media::{bytes_to_i16, i16_to_bytes, pcm_mime_type, pcm_sample_rate};
+
+/// Sample rate the Live API expects for input audio.
+pub const INPUT_SAMPLE_RATE: u32 = 16_000;
+/// Sample rate of the audio the Live API returns.
+pub const OUTPUT_SAMPLE_RATE: u32 = 24_000;
+
+/// Zero crossings of the sinc kernel on each side of the output sample.
+const KERNEL_ZEROS: usize = 24;
+/// Kernel table entries per input sample.
+const KERNEL_STEPS: usize = 256;
+/// Pass band, as a fraction of the lower of the two Nyquist frequencies.
+const PASS_BAND: f64 = 0.95;
+
+/// Scale 16-bit samples to `-1.0..1.0`.
+pub fn i16_to_f32(samples: &[i16]) -> Vec<f32> {
+    samples
+        .iter()
+        .map(|&sample| f32::from(sample) / 32768.0)
+        .collect()
+}
+
+/// Scale samples in `-1.0..1.0` to 16 bits, clipping anything outside.
+pub fn f32_to_i16(samples: &[f32]) -> Vec<i16> {
+    samples
+        .iter()
+        .map(|&sample| (sample * 32768.0).round().clamp(-32768.0, 32767.0) as i16)
+        .collect()
+}
+
+/// Average interleaved frames of `channels` channels into one channel.
+pub fn downmix_to_mono(samples: &[f32], channels: u16) -> Vec<f32> {
+    let channels = usize::from(channels.max(1));
+    if channels == 1 {
+        return samples.to_vec();
+    }
+    samples
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+/// Resample a complete mono signal from `from` Hz to `to` Hz.
+pub fn resample(samples: &[f32], from: u32, to: u32) -> Vec<f32> {
+    let mut resampler = Resampler::new(from, to);
+    let mut output = resampler.process(samples);
+    output.extend(resampler.flush());
+    output
+}
+
+/// Band-limited (windowed sinc) resampler for a mono stream.
+///
+/// Feed audio in chunks of any size with [`Resampler::process`]; the output
+/// lags the input by the kernel's half width, which [`Resampler::flush`]
+/// returns at the end of the stream. Converting `n` samples yields
+/// `ceil(n * to / from)` samples in total.
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    from: u64,
+    to: u64,
+    /// Input samples on each side of an output sample that contribute to it.
+    half_width: usize,
+    /// Kernel sampled `KERNEL_STEPS` times per input sample, from 0 to `half_width`.
+    kernel: Vec<f32>,
+    /// Pending input. `buffer[0]` is input sample `start`, which is negative
+    /// while the leading zero padding is still held.
+    buffer: Vec<f32>,
+    start: i64,
+    consumed: u64,
+    produced: u64,
+}
+
+impl Resampler {
+    pub fn new(from: u32, to: u32) -> Self {
+        assert!(from > 0 && to > 0, "sample rates must be positive");
+        let cutoff = PASS_BAND * (f64::from(to) / f64::from(from)).min(1.0);
+        let half_width = (KERNEL_ZEROS as f64 / cutoff).ceil() as usize;
+        let kernel = (0..=half_width * KERNEL_STEPS)
+            .map(|step| {
+                let x = step as f64 / KERNEL_STEPS as f64;
+                (cutoff * sinc(cutoff * x) * blackman_harris(x / half_width as f64)) as f32
+            })
+            .collect();
+        let mut resampler = Self {
+            from: u64::from(from),
+            to: u64::from(to),
+            half_width,
+            kernel,
+            buffer: Vec::new(),
+            start: 0,
+            consumed: 0,
+            produced: 0,
+        };
+        resampler.reset();
+        resampler
+    }
+
+    /// Resample the next chunk of input.
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        if self.from == self.to {
+            return input.to_vec();
+        }
+        self.buffer.extend_from_slice(input);
+        self.consumed += input.len() as u64;
+        self.drain(u64::MAX)
+    }
+
+    /// Return the remaining output and start over for a new stream.
+    pub fn flush(&mut self) -> Vec<f32> {
+        if self.from == self.to {
+            return Vec::new();
+        }
+        let total = (self.consumed * self.to).div_ceil(self.from);
+        self.buffer
+            .extend(std::iter::repeat_n(0.0, self.half_width + 1));
+        let output = self.drain(total);
+        self.reset();
+        output
+    }
+
+    fn reset(&mut self) {
+        self.buffer = vec![0.0; self.half_width];
+        self.start = -(self.half_width as i64);
+        self.consumed = 0;
+        self.produced = 0;
+    }
+
+    /// Produce output samples while enough input is buffered, up to `limit` in total.
+    fn drain(&mut self, limit: u64) -> Vec<f32> {
+        let half_width = self.half_width as i64;
+        let mut output = Vec::new();
+        while self.produced < limit {
+            // Position of the output sample in input samples: center + fraction.
+            let position = self.produced * self.from;
+            let center = (position / self.to) as i64;
+            let fraction = (position % self.to) as f64 / self.to as f64;
+            let last = center + half_width;
+            if last - self.start >= self.buffer.len() as i64 {
+                break;
+            }
+            let mut sum = 0.0f32;
+            for index in (center - half_width + 1)..=last {
+                let distance = ((index - center) as f64 - fraction).abs();
+                sum += self.buffer[(index - self.start) as usize] * self.kernel_at(distance);
+            }
+            output.push(sum);
+            self.produced += 1;
+        }
+
+        // Drop input that no later output sample needs.
+        let next_center = (self.produced * self.from / self.to) as i64;
+        let keep_from = (next_center - half_width + 1 - self.start).max(0) as usize;
+        let keep_from = keep_from.min(self.buffer.len());
+        self.buffer.drain(..keep_from);
+        self.start += keep_from as i64;
+        output
+    }
+
+    /// Kernel value at `distance` input samples, interpolated from the table.
+    fn kernel_at(&self, distance: f64) -> f32 {
+        let position = distance * KERNEL_STEPS as f64;
+        let index = position as usize;
+        if index + 1 >= self.kernel.len() {
+            return 0.0;
+        }
+        let fraction = (position - index as f64) as f32;
+        self.kernel[index] + (self.kernel[index + 1] - self.kernel[index]) * fraction
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+/// Blackman-Harris window at `x` in `-1.0..=1.0`, peaking at 0.
+fn blackman_harris(x: f64) -> f64 {
+    if x.abs() >= 1.0 {
+        return 0.0;
+    }
+    let phase = PI * (x + 1.0);
+    0.35875 - 0.48829 * phase.cos() + 0.14128 * (2.0 * phase).cos() - 0.01168 * (3.0 * phase).cos()
+}
+
+/// Splits a stream of 16-bit mono samples into chunks of equal duration.
+#[derive(Debug, Clone)]
+pub struct Chunker {
+    sample_rate: u32,
+    chunk_len: usize,
+    buffer: Vec<i16>,
+}
+
+impl Chunker {
+    /// Chunks of `duration` at `sample_rate`, at least one sample long.
+    pub fn new(sample_rate: u32, duration: Duration) -> Self {
+        let chunk_len = (duration.as_secs_f64() * f64::from(sample_rate)).round() as usize;
+        Self {
+            sample_rate,
+            chunk_len: chunk_len.max(1),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Samples in each chunk.
+    pub fn chunk_len(&self) -> usize {
+        self.chunk_len
+    }
+
+    /// Add samples and return every chunk that is now complete.
+    pub fn push(&mut self, samples: &[i16]) -> Vec<Vec<i16>> {
+        self.buffer.extend_from_slice(samples);
+        let complete = self.buffer.len() / self.chunk_len * self.chunk_len;
+        self.buffer
+            .drain(..complete)
+            .collect::<Vec<_>>()
+            .chunks(self.chunk_len)
+            .map(<[i16]>::to_vec)
+            .collect()
+    }
+
+    /// Return the incomplete last chunk, if any.
+    pub fn flush(&mut self) -> Option<Vec<i16>> {
+        (!self.buffer.is_empty()).then(|| std::mem::take(&mut self.buffer))
+    }
+
+    /// Like [`Chunker::push`], with each chunk ready to send as realtime input.
+    pub fn push_input(&mut self, samples: &[i16]) -> Vec<RealtimeInput> {
+        self.push(samples)
+            .iter()
+            .map(|chunk| audio_input(chunk, self.sample_rate))
+            .collect()
+    }
+}
+
+/// Realtime input carrying 16-bit mono PCM at `sample_rate`.
+pub fn audio_input(samples: &[i16], sample_rate: u32) -> RealtimeInput {
+    RealtimeInput::Audio(InlineData::new(
+        pcm_mime_type(sample_rate),
+        i16_to_bytes(samples),
+    ))
+}
@@ -0,0 +1,268 @@
+//! Synchronous wrapper around [`rest::Client`](super::rest::Client).
+//!
+//! Like `reqwest::blocking`, the client drives its requests on a runtime in a
+//! background thread, which stops once the last clone of the client and its
+//! iterators are dropped. Calls block the current thread until they complete,
+//! so prefer the async client inside async code; dropping the client there is
+//! fine.
+use super::{
+    request, response,
+    rest::{self, Error},
+    usage,
+};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::mpsc;
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
+use tokio_stream::{Stream, StreamExt};
+
+type Job = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Handle to the runtime thread, which runs every job it is sent.
+#[derive(Debug, Clone)]
+struct Runtime {
+    jobs: UnboundedSender<Job>,
+}
+
+impl Runtime {
+    /// # Panics
+    ///
+    /// If the runtime or its thread cannot be created.
+    fn start() -> Self {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to build the blocking client runtime");
+        let (jobs, mut received) = unbounded_channel::<Job>();
+        std::thread::Builder::new()
+            .name("gemini-blocking-runtime".into())
+            .spawn(move || {
+                runtime.block_on(async {
+                    while let Some(job) = received.recv().await {
+                        tokio::spawn(job);
+                    }
+                })
+            })
+            .expect("failed to start the blocking client runtime thread");
+        Self { jobs }
+    }
+
+    /// Run `future` on the runtime thread and wait for its output.
+    fn block_on<T: Send + 'static>(&self, future: impl Future<Output = T> + Send + 'static) -> T {
+        let (tx, rx) = mpsc::sync_channel(1);
+        let job = Box::pin(async move {
+            let _ = tx.send(future.await);
+        });
+        self.jobs
+            .send(job)
+            .expect("the blocking client runtime stopped");
+        rx.recv().expect("the blocking client runtime stopped")
+    }
+}
+
+/// Blocking client; clones share the runtime thread.
+#[derive(Debug, Clone)]
+pub struct Client {
+    inner: rest::Client,
+    runtime: Runtime,
+}
+
+impl Client {
+    /// # Panics
+    ///
+    /// If the runtime or its thread cannot be created.
+    pub fn new(api_key: impl Into<String>, model: impl Into<String>) -> Self {
+        rest::Client::new(api_key, model).into()
+    }
+
+    pub fn with_api_base(self, api_base: impl Into<String>) -> Self {
+        Self {
+            inner: self.inner.with_api_base(api_base),
+            ..self
+        }
+    }
+
+    /// See [`rest::Client::with_strict`].
+    pub fn with_strict(self, strict: bool) -> Self {
+        Self {
+            inner: self.inner.with_strict(strict),
+            ..self
+        }
+    }
+
+    pub fn with_client(self, client: reqwest::Client) -> Self {
+        Self {
+            inner: self.inner.with_client(client),
+            ..self
+        }
+    }
+
+    pub fn generate_content(&self, request: request::Request) -> Result<response::Response, Error> {
+        let inner = self.inner.clone();
+        self.runtime
+            .block_on(async move { inner.generate_content(request).await })
+    }
+
+    /// Stream the response, blocking on each chunk as the iterator advances.
+    pub fn stream_content(
+        &self,
+        request: request::Request,
+    ) -> Result<Iter<response::Response>, Error> {
+        let inner = self.inner.clone();
+        let stream = self
+            .runtime
+            .block_on(async move { inner.stream_content(request).await })?;
+        Ok(Iter::new(self.runtime.clone(), stream))
+    }
+
+    /// Like [`Client::stream_content`], with the thought parts of every chunk
+    /// separated from the answer.
+    pub fn stream_content_split(
+        &self,
+        request: request::Request,
+    ) -> Result<Iter<response::Split>, Error> {
+        let inner = self.inner.clone();
+        let stream = self
+            .runtime
+            .block_on(async move { inner.stream_content_split(request).await })?;
+        Ok(Iter::new(self.runtime.clone(), stream))
+    }
+
+    pub fn count_tokens(
+        &self,
+        request: request::Request,
+    ) -> Result<response::CountTokensResponse, Error> {
+        let inner = self.inner.clone();
+        self.runtime
+            .block_on(async move { inner.count_tokens(request).await })
+    }
+
+    pub fn embed_content(
+        &self,
+        request: request::EmbedContentRequest,
+    ) -> Result<response::EmbedContentResponse, Error> {
+        let inner = self.inner.clone();
+        self.runtime
+            .block_on(async move { inner.embed_content(request).await })
+    }
+
+    /// One page of the available models.
+    pub fn list_models(
+        &self,
+        page_token: Option<&str>,
+    ) -> Result<response::ListModelsResponse, Error> {
+        let inner = self.inner.clone();
+        let page_token = page_token.map(str::to_string);
+        self.runtime
+            .block_on(async move { inner.list_models(page_token.as_deref()).await })
+    }
+
+    pub fn get_model(&self, name: &str) -> Result<response::Model, Error> {
+        let inner = self.inner.clone();
+        let name = name.to_string();
+        self.runtime
+            .block_on(async move { inner.get_model(&name).await })
+    }
+
+    /// See [`rest::Client::upload_file`].
+    pub fn upload_file(
+        &self,
+        bytes: Vec<u8>,
+        mime_type: &str,
+        display_name: Option<&str>,
+    ) -> Result<response::File, Error> {
+        let inner = self.inner.clone();
+        let mime_type = mime_type.to_string();
+        let display_name = display_name.map(str::to_string);
+        self.runtime.block_on(async move {
+            inner
+                .upload_file(bytes, &mime_type, display_name.as_deref())
+                .await
+        })
+    }
+
+    /// One page of the uploaded files.
+    pub fn list_files(
+        &self,
+        page_token: Option<&str>,
+    ) -> Result<response::ListFilesResponse, Error> {
+        let inner = self.inner.clone();
+        let page_token = page_token.map(str::to_string);
+        self.runtime
+            .block_on(async move { inner.list_files(page_token.as_deref()).await })
+    }
+
+    pub fn get_file(&self, name: &str) -> Result<response::File, Error> {
+        let inner = self.inner.clone();
+        let name = name.to_string();
+        self.runtime
+            .block_on(async move { inner.get_file(&name).await })
+    }
+
+    pub fn delete_file(&self, name: &str) -> Result<(), Error> {
+        let inner = self.inner.clone();
+        let name = name.to_string();
+        self.runtime
+            .block_on(async move { inner.delete_file(&name).await })
+    }
+
+    /// Usage of all responses received so far.
+    pub fn usage(&self) -> usage::UsageSnapshot {
+        self.inner.usage()
+    }
+}
+
+impl From<rest::Client> for Client {
+    /// # Panics
+    ///
+    /// If the runtime or its thread cannot be created.
+    fn from(inner: rest::Client) -> Self {
+        Self {
+            inner,
+            runtime: Runtime::start(),
+        }
+    }
+}
+
+type BoxStream<T> = Pin<Box<dyn Stream<Item = Result<T, Error>> + Send>>;
+
+/// Blocking iterator over a streamed response.
+pub struct Iter<T> {
+    runtime: Runtime,
+    /// Moved to the runtime thread while the next item is awaited.
+    stream: Option<BoxStream<T>>,
+}
+
+impl<T> Iter<T> {
+    fn new(
+        runtime: Runtime,
+        stream: impl Stream<Item = Result<T, Error>> + Send + 'static,
+    ) -> Self {
+        Self {
+            runtime,
+            stream: Some(Box::pin(stream)),
+        }
+    }
+}
+
+impl<T: Send + 'static> Iterator for Iter<T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut stream = self.stream.take()?;
+        let (item, stream) = self.runtime.block_on(async move {
+            let item = stream.next().await;
+            (item, stream)
+        });
+        if item.is_some() {
+            self.stream = Some(stream);
+        }
+        item
+    }
+}
+
+impl<T> std::fmt::Debug for Iter<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Iter").finish_non_exhaustive()
+    }
+}
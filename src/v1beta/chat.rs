@@ -0,0 +1,279 @@
+//! Multi-turn conversations over the REST API with a bounded history.
+use super::{
+    Content, Part, PartData, Role, request, response,
+    rest::{Client, Error},
+    usage::{UsageSnapshot, UsageTracker},
+};
+use derive_new::new;
+use derive_setters::Setters;
+
+/// Prompt used to condense older turns when [`HistoryPolicy::Summarize`] applies.
+const SUMMARY_PROMPT: &str = "Summarize the conversation so far. Keep every fact, decision and open question needed to continue it, and nothing else.";
+
+/// Controls how much of the conversation is sent with each message.
+///
+/// Policies work on whole turns: a user message together with everything that
+/// answers it, including function calls and their responses, so a call is never
+/// sent without its response.
+#[derive(Debug, Clone, Default)]
+pub enum HistoryPolicy {
+    /// Send the full history.
+    #[default]
+    KeepAll,
+    /// Send only the last `n` turns.
+    LastTurns(usize),
+    /// Drop the oldest turns until the history fits in the given number of tokens.
+    TokenBudget(u32),
+    /// Once the history exceeds `max_tokens`, replace everything but the last
+    /// `keep_last` turns with a summary written by the model.
+    Summarize { max_tokens: u32, keep_last: usize },
+}
+
+#[derive(Debug, Clone)]
+struct Turn {
+    contents: Vec<Content>,
+    /// Token count from `countTokens`, or derived from the usage metadata of the
+    /// last response. The newest turn absorbs the system instruction and tools,
+    /// so the counts of all turns add up to the last reported prompt size.
+    tokens: Option<u32>,
+}
+
+#[derive(Debug, Clone, new, Setters)]
+#[setters(prefix = "with_", into, strip_option)]
+/// A conversation that keeps its own history and sends it with every message.
+pub struct Chat {
+    #[setters(skip)]
+    client: Client,
+    #[new(default)]
+    policy: HistoryPolicy,
+    #[new(default)]
+    tools: Vec<request::Tools>,
+    #[new(default)]
+    safety_settings: Vec<request::SafetySettings>,
+    #[new(default)]
+    generation_config: Option<request::GenerationConfig>,
+    #[new(default)]
+    system_instruction: Option<request::SystemInstructionContent>,
+    /// Ledger of this conversation alone, summaries included. The client keeps
+    /// its own as well.
+    #[new(default)]
+    usage_tracker: UsageTracker,
+    #[new(default)]
+    #[setters(skip)]
+    turns: Vec<Turn>,
+}
+
+impl Chat {
+    /// Replace the history, e.g. with a conversation restored from storage.
+    pub fn with_history(mut self, history: Vec<Content>) -> Self {
+        self.turns.clear();
+        for content in history {
+            self.push(content);
+        }
+        self
+    }
+
+    /// The contents that will be sent along with the next message.
+    pub fn history(&self) -> impl Iterator<Item = &Content> {
+        self.turns.iter().flat_map(|turn| turn.contents.iter())
+    }
+
+    /// Usage of this conversation so far.
+    pub fn usage(&self) -> UsageSnapshot {
+        self.usage_tracker.snapshot()
+    }
+
+    /// Forget the whole conversation.
+    pub fn clear(&mut self) {
+        self.turns.clear();
+    }
+
+    /// Send `content` and record the model's reply in the history.
+    ///
+    /// Function responses continue the current turn, any other user content
+    /// starts a new one. On error the history is left as it was, minus anything
+    /// the [`HistoryPolicy`] already dropped.
+    pub async fn send(&mut self, content: Content) -> Result<response::Response, Error> {
+        self.push(content);
+
+        let result = self.exchange().await;
+        if result.is_err() {
+            self.pop();
+        }
+        result
+    }
+
+    async fn exchange(&mut self) -> Result<response::Response, Error> {
+        self.apply_policy().await?;
+
+        let contents = self.history().cloned().collect();
+        let response = self.generate(self.request(contents)).await?;
+
+        if let Some(content) = response
+            .candidates
+            .first()
+            .and_then(|candidate| candidate.content.clone())
+            .filter(|content| !content.parts.is_empty())
+        {
+            self.push(content);
+        }
+        self.record_usage(response.usage_metadata.as_ref());
+
+        Ok(response)
+    }
+
+    async fn generate(&self, request: request::Request) -> Result<response::Response, Error> {
+        let response = self.client.generate_content(request).await?;
+        if let Some(usage) = &response.usage_metadata {
+            self.usage_tracker.record(self.client.model(), usage);
+        }
+        Ok(response)
+    }
+
+    fn request(&self, contents: Vec<Content>) -> request::Request {
+        let mut request = request::Request::new(contents)
+            .with_tools(self.tools.clone())
+            .with_safety_settings(self.safety_settings.clone());
+        if let Some(config) = &self.generation_config {
+            request = request.with_generation_config(config.clone());
+        }
+        if let Some(instruction) = &self.system_instruction {
+            request = request.with_system_instruction(instruction.clone());
+        }
+        request
+    }
+
+    fn push(&mut self, content: Content) {
+        let continues_turn = matches!(content.role, Role::Model)
+            || content
+                .parts
+                .iter()
+                .any(|part| matches!(part.data, PartData::FunctionResponse(_)));
+
+        match self.turns.last_mut() {
+            Some(turn) if continues_turn => {
+                turn.contents.push(content);
+                turn.tokens = None;
+            }
+            _ => self.turns.push(Turn {
+                contents: vec![content],
+                tokens: None,
+            }),
+        }
+    }
+
+    /// Undo the last [`Chat::push`]. Policies never drop the newest turn, so the
+    /// pushed content is still at its end.
+    fn pop(&mut self) {
+        if let Some(turn) = self.turns.last_mut() {
+            turn.contents.pop();
+            turn.tokens = None;
+            if turn.contents.is_empty() {
+                self.turns.pop();
+            }
+        }
+    }
+
+    fn record_usage(&mut self, usage: Option<&response::UsageMetadata>) {
+        let Some((last, previous)) = self.turns.split_last_mut() else {
+            return;
+        };
+        let total = usage.and_then(|usage| {
+            Some(usage.prompt_token_count? + usage.candidates_token_count.unwrap_or_default())
+        });
+        let previous = previous.iter().map(|turn| turn.tokens).sum::<Option<u32>>();
+
+        last.tokens = total
+            .zip(previous)
+            .map(|(total, previous)| total.saturating_sub(previous));
+    }
+
+    fn total_tokens(&self) -> u32 {
+        self.turns
+            .iter()
+            .map(|turn| turn.tokens.unwrap_or_default())
+            .sum()
+    }
+
+    async fn apply_policy(&mut self) -> Result<(), Error> {
+        match self.policy {
+            HistoryPolicy::KeepAll => {}
+            HistoryPolicy::LastTurns(n) => {
+                let excess = self.turns.len().saturating_sub(n.max(1));
+                self.turns.drain(..excess);
+            }
+            HistoryPolicy::TokenBudget(max_tokens) => {
+                self.count_tokens().await?;
+                while self.turns.len() > 1 && self.total_tokens() > max_tokens {
+                    self.turns.remove(0);
+                }
+            }
+            HistoryPolicy::Summarize {
+                max_tokens,
+                keep_last,
+            } => {
+                self.count_tokens().await?;
+                let keep_last = keep_last.max(1);
+                if self.total_tokens() > max_tokens && self.turns.len() > keep_last {
+                    self.summarize(self.turns.len() - keep_last).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fill in the token count of every turn that has none yet.
+    async fn count_tokens(&mut self) -> Result<(), Error> {
+        for i in 0..self.turns.len() {
+            if self.turns[i].tokens.is_none() {
+                let request = request::Request::new(self.turns[i].contents.clone());
+                let count = self.client.count_tokens(request).await?;
+                self.turns[i].tokens = Some(count.total_tokens);
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the oldest `n` turns with a single turn holding the summary
+    /// request and the model's summary, so roles keep alternating.
+    async fn summarize(&mut self, n: usize) -> Result<(), Error> {
+        let prompt = Content::new(
+            Role::User,
+            vec![Part::new(PartData::Text(SUMMARY_PROMPT.into()))],
+        );
+        let mut contents: Vec<Content> = self.turns[..n]
+            .iter()
+            .flat_map(|turn| turn.contents.iter().cloned())
+            .collect();
+        contents.push(prompt.clone());
+
+        // The history may hold function calls, which need their declarations,
+        // but the summary itself must be text.
+        let mut request = request::Request::new(contents);
+        if !self.tools.is_empty() {
+            request =
+                request
+                    .with_tools(self.tools.clone())
+                    .with_tool_config(request::ToolConfig::new(
+                        request::FunctionCallingConfig::new(request::FunctionCallingMode::None),
+                    ));
+        }
+        if let Some(instruction) = &self.system_instruction {
+            request = request.with_system_instruction(instruction.clone());
+        }
+        let response = self.generate(request).await?;
+        let summary = response
+            .text()
+            .ok_or_else(|| Error::ApiError("summary response contained no text".into()))?;
+
+        let turn = Turn {
+            contents: vec![
+                prompt,
+                Content::new(Role::Model, vec![Part::new(PartData::Text(summary))]),
+            ],
+            tokens: None,
+        };
+        self.turns.splice(..n, [turn]);
+        Ok(())
+    }
+}
@@ -0,0 +1,832 @@
+//! [Dotprompt](https://google.github.io/dotprompt/) `.prompt` files.
+//!
+//! A prompt file is a YAML frontmatter block followed by a Handlebars template:
+//!
+//! ```text
+//! ---
+//! model: googleai/gemini-2.5-flash
+//! config:
+//!   temperature: 0.4
+//! input:
+//!   schema:
+//!     city: string, the city to describe
+//!     style?: string
+//!   default:
+//!     style: brief
+//! output:
+//!   schema:
+//!     summary: string
+//!     sights(array): string
+//! ---
+//! {{role "system"}}
+//! You are a travel guide. Keep it {{style}}.
+//! {{role "user"}}
+//! Describe {{city}}. {{media url=photo}}
+//! ```
+//!
+//! Schemas are written in Picoschema or as JSON Schema. The input is checked
+//! against the input schema before rendering and the output schema becomes the
+//! request's `response_schema`. Optional `?` fields become `nullable`, and
+//! `any` is only allowed in the input schema, as the API needs a type for
+//! every output and tool parameter. Besides the usual Handlebars helpers the
+//! template can use `{{role "system" | "user" | "model"}}`, `{{media url=...
+//! contentType=...}}`, `{{history}}` and `{{json value}}`.
+use super::{
+    Content, FileData, InlineData, Part, PartData, Role, live, media,
+    request::{
+        FunctionDeclaration, GenerationConfig, Request, SystemInstructionContent,
+        SystemInstructionPart, Tools,
+    },
+};
+use handlebars::{
+    Context, Handlebars, Helper, HelperResult, Output, RenderContext, RenderErrorReason,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use std::path::Path;
+use thiserror::Error;
+
+const TEMPLATE: &str = "prompt";
+const MARKER_START: &str = "<<<dotprompt:";
+const MARKER_END: &str = ">>>";
+const JSON_TYPES: [&str; 7] = [
+    "string", "number", "integer", "boolean", "null", "object", "array",
+];
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("invalid frontmatter: {0}")]
+    Frontmatter(#[from] serde_yaml::Error),
+    #[error("frontmatter is not closed by a `---` line")]
+    UnterminatedFrontmatter,
+    #[error(transparent)]
+    Template(Box<handlebars::TemplateError>),
+    #[error(transparent)]
+    Render(Box<handlebars::RenderError>),
+    #[error("invalid schema: {0}")]
+    Schema(String),
+    #[error("invalid input at `{path}`: {message}")]
+    InvalidInput { path: String, message: String },
+    #[error("invalid generation config: {0}")]
+    Config(#[source] serde_json::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("unknown tool `{0}`, register it with `Prompt::with_tool`")]
+    UnknownTool(String),
+    #[error("unknown role `{0}`")]
+    UnknownRole(String),
+    #[error("unsupported output format `{0}`")]
+    OutputFormat(String),
+    #[error("could not determine the content type of {0}")]
+    MediaType(String),
+    #[error("the prompt declares no model")]
+    MissingModel,
+    #[error("a live setup only takes system instructions, found a {0} message")]
+    SetupRole(&'static str),
+}
+
+impl From<handlebars::TemplateError> for Error {
+    fn from(error: handlebars::TemplateError) -> Self {
+        Self::Template(Box::new(error))
+    }
+}
+
+impl From<handlebars::RenderError> for Error {
+    fn from(error: handlebars::RenderError) -> Self {
+        Self::Render(Box::new(error))
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Frontmatter {
+    name: Option<String>,
+    model: Option<String>,
+    config: Option<Value>,
+    #[serde(default)]
+    input: InputSpec,
+    #[serde(default)]
+    output: OutputSpec,
+    #[serde(default)]
+    tools: Vec<String>,
+    #[serde(default)]
+    tool_defs: Vec<ToolDef>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct InputSpec {
+    schema: Option<Value>,
+    default: Option<Map<String, Value>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct OutputSpec {
+    format: Option<String>,
+    schema: Option<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolDef {
+    name: String,
+    #[serde(default)]
+    description: String,
+    input_schema: Option<Value>,
+}
+
+/// A parsed `.prompt` file, ready to be rendered with input variables.
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    frontmatter: Frontmatter,
+    input_schema: Option<Value>,
+    output_schema: Option<Value>,
+    registry: Handlebars<'static>,
+    tools: HashMap<String, FunctionDeclaration>,
+}
+
+impl Prompt {
+    /// Parse the source of a `.prompt` file.
+    pub fn parse(source: &str) -> Result<Self, Error> {
+        let (frontmatter, body) = split_frontmatter(source)?;
+        let frontmatter: Frontmatter = match frontmatter {
+            Some(yaml) if !yaml.trim().is_empty() => serde_yaml::from_str(yaml)?,
+            _ => Frontmatter::default(),
+        };
+        let input_schema = frontmatter
+            .input
+            .schema
+            .as_ref()
+            .map(|definition| schema(definition, Target::Input))
+            .transpose()?;
+        let output_schema = frontmatter
+            .output
+            .schema
+            .as_ref()
+            .map(|definition| schema(definition, Target::Api))
+            .transpose()?;
+
+        let mut registry = Handlebars::new();
+        registry.register_escape_fn(handlebars::no_escape);
+        registry.register_helper("role", Box::new(role_helper));
+        registry.register_helper("media", Box::new(media_helper));
+        registry.register_helper("history", Box::new(history_helper));
+        registry.register_helper("json", Box::new(json_helper));
+        registry.register_template_string(TEMPLATE, body)?;
+
+        let mut tools = HashMap::new();
+        for tool in &frontmatter.tool_defs {
+            let mut declaration = FunctionDeclaration::new(&tool.name, &tool.description);
+            if let Some(parameters) = &tool.input_schema {
+                declaration = declaration.with_parameters(schema(parameters, Target::Api)?);
+            }
+            tools.insert(tool.name.clone(), declaration);
+        }
+
+        Ok(Self {
+            frontmatter,
+            input_schema,
+            output_schema,
+            registry,
+            tools,
+        })
+    }
+
+    /// Read and parse a `.prompt` file. Partials named `_<name>.prompt` in the
+    /// same directory are registered as `{{> name}}`.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let mut prompt = Self::parse(&std::fs::read_to_string(path)?)?;
+        let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+        for entry in std::fs::read_dir(dir.unwrap_or(Path::new(".")))? {
+            let path = entry?.path();
+            let partial = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix('_')?.strip_suffix(".prompt"));
+            if let Some(name) = partial {
+                let source = std::fs::read_to_string(&path)?;
+                prompt = prompt.with_partial(name, &source)?;
+            }
+        }
+        Ok(prompt)
+    }
+
+    /// Register a partial template usable as `{{> name}}`.
+    pub fn with_partial(mut self, name: &str, template: &str) -> Result<Self, Error> {
+        let (_, body) = split_frontmatter(template)?;
+        self.registry.register_partial(name, body)?;
+        Ok(self)
+    }
+
+    /// Provide the declaration of a tool the frontmatter refers to by name.
+    pub fn with_tool(mut self, declaration: FunctionDeclaration) -> Self {
+        self.tools
+            .insert(declaration.name().to_string(), declaration);
+        self
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.frontmatter.name.as_deref()
+    }
+
+    /// The model without a `googleai/` or `vertexai/` provider prefix.
+    pub fn model(&self) -> Option<&str> {
+        let model = self.frontmatter.model.as_deref()?;
+        Some(
+            model
+                .strip_prefix("googleai/")
+                .or_else(|| model.strip_prefix("vertexai/"))
+                .unwrap_or(model),
+        )
+    }
+
+    /// The input schema as JSON Schema.
+    pub fn input_schema(&self) -> Option<&Value> {
+        self.input_schema.as_ref()
+    }
+
+    /// The output schema as JSON Schema.
+    pub fn output_schema(&self) -> Option<&Value> {
+        self.output_schema.as_ref()
+    }
+
+    /// Render the prompt into a request.
+    pub fn render(&self, input: &impl Serialize) -> Result<Request, Error> {
+        self.render_with_history(input, &[])
+    }
+
+    /// Render the prompt into a request, placing `history` at the `{{history}}`
+    /// marker or, without one, ahead of the rendered turns.
+    pub fn render_with_history(
+        &self,
+        input: &impl Serialize,
+        history: &[Content],
+    ) -> Result<Request, Error> {
+        let messages = self.messages(input)?;
+        let mut system = Vec::new();
+        let mut contents = Vec::new();
+        let mut history_placed = false;
+        for message in messages {
+            match message {
+                Message::System(parts) => system.extend(parts),
+                Message::Untagged(parts) => contents.push(Content::new(Role::User, parts)),
+                Message::Turn(role, parts) => contents.push(Content::new(role, parts)),
+                Message::History => {
+                    contents.extend_from_slice(history);
+                    history_placed = true;
+                }
+            }
+        }
+        if !history_placed {
+            contents.splice(0..0, history.iter().cloned());
+        }
+
+        let mut request = Request::new(contents);
+        if let Some(system) = system_instruction(&system) {
+            request = request.with_system_instruction(system);
+        }
+        if let Some(config) = self.generation_config()? {
+            request = request.with_generation_config(config);
+        }
+        let tools = self.tools()?;
+        if !tools.is_empty() {
+            request = request.with_tools(vec![Tools::new(tools)]);
+        }
+        Ok(request)
+    }
+
+    /// Render the prompt into a live session setup. The template may only
+    /// contain system messages; text outside any `{{role}}` block becomes the
+    /// system instruction too.
+    pub fn render_setup(&self, input: &impl Serialize) -> Result<live::Setup, Error> {
+        let model = self.model().ok_or(Error::MissingModel)?;
+        let model = if model.starts_with("models/") {
+            model.to_string()
+        } else {
+            format!("models/{model}")
+        };
+
+        let mut parts = Vec::new();
+        for message in self.messages(input)? {
+            match message {
+                Message::System(system) | Message::Untagged(system) => parts.extend(system),
+                Message::Turn(Role::User, _) => return Err(Error::SetupRole("user")),
+                Message::Turn(Role::Model, _) => return Err(Error::SetupRole("model")),
+                Message::History => return Err(Error::SetupRole("history")),
+            }
+        }
+
+        let mut setup = live::Setup::new(model);
+        if let Some(text) = system_instruction(&parts).map(|system| system.text()) {
+            setup = setup.with_system_instruction(live::Content::new(
+                None,
+                vec![live::Part::new(live::PartData::Text(text))],
+            ));
+        }
+        if let Some(config) = &self.frontmatter.config {
+            setup = setup.with_generation_config(
+                serde_json::from_value::<live::GenerationConfig>(config.clone())
+                    .map_err(Error::Config)?,
+            );
+        }
+        let tools = self.tools()?;
+        if !tools.is_empty() {
+            let declarations = serde_json::from_value(serde_json::to_value(tools)?)?;
+            setup = setup.with_tools(vec![live::Tool::FunctionDeclarations(declarations)]);
+        }
+        Ok(setup)
+    }
+
+    fn messages(&self, input: &impl Serialize) -> Result<Vec<Message>, Error> {
+        let input = self.input(input)?;
+        let rendered = self.registry.render(TEMPLATE, &input)?;
+        messages(&rendered)
+    }
+
+    fn input(&self, input: &impl Serialize) -> Result<Value, Error> {
+        let mut input = match serde_json::to_value(input)? {
+            Value::Null => Value::Object(Map::new()),
+            input => input,
+        };
+        if let (Some(defaults), Value::Object(fields)) =
+            (&self.frontmatter.input.default, &mut input)
+        {
+            for (name, value) in defaults {
+                fields.entry(name.as_str()).or_insert_with(|| value.clone());
+            }
+        }
+        if let Some(schema) = &self.input_schema {
+            validate(schema, &input, "input")?;
+        }
+        Ok(input)
+    }
+
+    fn generation_config(&self) -> Result<Option<GenerationConfig>, Error> {
+        let mut config = match &self.frontmatter.config {
+            Some(config) => Some(
+                serde_json::from_value::<GenerationConfig>(config.clone())
+                    .map_err(Error::Config)?,
+            ),
+            None => None,
+        };
+
+        let format = match (&self.frontmatter.output.format, &self.output_schema) {
+            (Some(format), _) => format.as_str(),
+            (None, Some(_)) => "json",
+            (None, None) => return Ok(config),
+        };
+        let mime_type = match format {
+            "json" => "application/json",
+            "enum" => "text/x.enum",
+            "text" => return Ok(config),
+            other => return Err(Error::OutputFormat(other.to_string())),
+        };
+        let mut output = config
+            .take()
+            .unwrap_or_default()
+            .with_response_mime_type(mime_type);
+        if let Some(schema) = &self.output_schema {
+            output = output.with_response_schema(schema.clone());
+        }
+        Ok(Some(output))
+    }
+
+    fn tools(&self) -> Result<Vec<FunctionDeclaration>, Error> {
+        let mut names: Vec<&str> = self.frontmatter.tools.iter().map(String::as_str).collect();
+        for tool in &self.frontmatter.tool_defs {
+            if !names.contains(&tool.name.as_str()) {
+                names.push(&tool.name);
+            }
+        }
+        names
+            .into_iter()
+            .map(|name| {
+                self.tools
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| Error::UnknownTool(name.to_string()))
+            })
+            .collect()
+    }
+}
+
+impl std::str::FromStr for Prompt {
+    type Err = Error;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Self::parse(source)
+    }
+}
+
+enum Message {
+    /// Text before the first `{{role}}` marker, a user turn in requests.
+    Untagged(Vec<Part>),
+    System(Vec<Part>),
+    Turn(Role, Vec<Part>),
+    History,
+}
+
+fn split_frontmatter(source: &str) -> Result<(Option<&str>, &str), Error> {
+    let Some(rest) = source
+        .strip_prefix("---\n")
+        .or_else(|| source.strip_prefix("---\r\n"))
+    else {
+        return Ok((None, source));
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Ok((Some(&rest[..offset]), &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    Err(Error::UnterminatedFrontmatter)
+}
+
+fn system_instruction(parts: &[Part]) -> Option<SystemInstructionContent> {
+    let parts: Vec<_> = parts
+        .iter()
+        .filter_map(|part| match &part.data {
+            PartData::Text(text) => Some(SystemInstructionPart::new(text.clone())),
+            _ => None,
+        })
+        .collect();
+    (!parts.is_empty()).then(|| SystemInstructionContent::new(parts))
+}
+
+/// Split the rendered template at the role, media and history markers.
+fn messages(rendered: &str) -> Result<Vec<Message>, Error> {
+    let mut messages = Vec::new();
+    let mut role = "";
+    let mut parts = Vec::new();
+    let mut rest = rendered;
+    loop {
+        let (text, marker) = match rest.find(MARKER_START) {
+            Some(start) => {
+                let after = &rest[start + MARKER_START.len()..];
+                match after.find(MARKER_END) {
+                    Some(end) => {
+                        let marker = &after[..end];
+                        let text = &rest[..start];
+                        rest = &after[end + MARKER_END.len()..];
+                        (text, Some(marker))
+                    }
+                    None => (std::mem::take(&mut rest), None),
+                }
+            }
+            None => (std::mem::take(&mut rest), None),
+        };
+        push_text(&mut parts, text);
+
+        let Some(marker) = marker else {
+            push_message(&mut messages, role, parts)?;
+            return Ok(messages);
+        };
+        if let Some(next) = marker.strip_prefix("role:") {
+            push_message(&mut messages, role, std::mem::take(&mut parts))?;
+            role = match next {
+                "system" | "user" | "model" => next,
+                "assistant" => "model",
+                other => return Err(Error::UnknownRole(other.to_string())),
+            };
+        } else if let Some(media) = marker.strip_prefix("media:url ") {
+            parts.push(media_part(media)?);
+        } else if marker == "history" {
+            push_message(&mut messages, role, std::mem::take(&mut parts))?;
+            messages.push(Message::History);
+            // Text after the history belongs to a new user turn, as in Dotprompt.
+            role = "user";
+        }
+    }
+}
+
+fn push_text(parts: &mut Vec<Part>, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    if let Some(Part {
+        data: PartData::Text(last),
+        ..
+    }) = parts.last_mut()
+    {
+        last.push_str(text);
+    } else {
+        parts.push(Part::new(PartData::Text(text.to_string())));
+    }
+}
+
+fn push_message(
+    messages: &mut Vec<Message>,
+    role: &str,
+    mut parts: Vec<Part>,
+) -> Result<(), Error> {
+    // Whitespace around markers is template layout, not prompt text.
+    for part in &mut parts {
+        if let PartData::Text(text) = &mut part.data {
+            *text = text.trim().to_string();
+        }
+    }
+    parts.retain(|part| !matches!(&part.data, PartData::Text(text) if text.is_empty()));
+    if parts.is_empty() {
+        return Ok(());
+    }
+    messages.push(match role {
+        "" => Message::Untagged(parts),
+        "system" => Message::System(parts),
+        "model" => Message::Turn(Role::Model, parts),
+        "user" => Message::Turn(Role::User, parts),
+        other => return Err(Error::UnknownRole(other.to_string())),
+    });
+    Ok(())
+}
+
+fn media_part(media: &str) -> Result<Part, Error> {
+    let (url, content_type) = match media.split_once(' ') {
+        Some((url, content_type)) => (url, Some(content_type)),
+        None => (media, None),
+    };
+    if let Some(data_url) = url.strip_prefix("data:")
+        && let Some((mime_type, data)) = data_url.split_once(";base64,")
+    {
+        let mime_type = content_type.unwrap_or(mime_type);
+        return Ok(Part::new(PartData::InlineData(InlineData::new(
+            mime_type, data,
+        ))));
+    }
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let mime_type = content_type
+        .or_else(|| media::mime_type_from_extension(path))
+        .ok_or_else(|| Error::MediaType(url.to_string()))?;
+    Ok(Part::new(PartData::FileData(FileData::new(mime_type, url))))
+}
+
+fn role_helper(
+    helper: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let role = helper
+        .param(0)
+        .and_then(|param| param.value().as_str())
+        .ok_or(RenderErrorReason::ParamNotFoundForIndex("role", 0))?;
+    out.write(&format!("{MARKER_START}role:{role}{MARKER_END}"))?;
+    Ok(())
+}
+
+fn media_helper(
+    helper: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let url = helper
+        .hash_get("url")
+        .and_then(|url| url.value().as_str())
+        .ok_or(RenderErrorReason::ParamNotFoundForName(
+            "media",
+            "url".into(),
+        ))?;
+    out.write(&format!("{MARKER_START}media:url {url}"))?;
+    if let Some(content_type) = helper
+        .hash_get("contentType")
+        .and_then(|content_type| content_type.value().as_str())
+    {
+        out.write(&format!(" {content_type}"))?;
+    }
+    out.write(MARKER_END)?;
+    Ok(())
+}
+
+fn history_helper(
+    _: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    out.write(&format!("{MARKER_START}history{MARKER_END}"))?;
+    Ok(())
+}
+
+fn json_helper(
+    helper: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let value = helper
+        .param(0)
+        .map(|param| param.value())
+        .ok_or(RenderErrorReason::ParamNotFoundForIndex("json", 0))?;
+    let json = match helper.hash_get("indent") {
+        Some(_) => serde_json::to_string_pretty(value),
+        None => serde_json::to_string(value),
+    }
+    .map_err(RenderErrorReason::SerdeError)?;
+    out.write(&json)?;
+    Ok(())
+}
+
+/// What a converted schema is used for.
+#[derive(Clone, Copy, PartialEq)]
+enum Target {
+    /// Checking the input locally, where any JSON Schema works.
+    Input,
+    /// A response schema or tool parameters, which the API only accepts with
+    /// a concrete type.
+    Api,
+}
+
+/// Convert a Picoschema or JSON Schema definition into JSON Schema.
+fn schema(definition: &Value, target: Target) -> Result<Value, Error> {
+    match definition {
+        Value::String(spec) => scalar(spec, target),
+        Value::Object(map) if is_json_schema(map) => Ok(definition.clone()),
+        Value::Object(map) => object(map, target),
+        other => Err(Error::Schema(format!("unsupported definition {other}"))),
+    }
+}
+
+fn is_json_schema(map: &Map<String, Value>) -> bool {
+    matches!(map.get("type"), Some(Value::String(kind)) if JSON_TYPES.contains(&kind.as_str()))
+        || map.contains_key("properties")
+}
+
+fn scalar(spec: &str, target: Target) -> Result<Value, Error> {
+    let (kind, description) = match spec.split_once(',') {
+        Some((kind, description)) => (kind.trim(), Some(description.trim())),
+        None => (spec.trim(), None),
+    };
+    let mut schema = match kind {
+        "string" | "number" | "integer" | "boolean" => json!({ "type": kind }),
+        "any" | "null" if target == Target::Api => {
+            return Err(Error::Schema(format!(
+                "`{kind}` is only supported in input schemas, the API needs a concrete type"
+            )));
+        }
+        "null" => json!({ "type": kind }),
+        "any" => json!({}),
+        other => return Err(Error::Schema(format!("unknown type `{other}`"))),
+    };
+    if let Some(description) = description {
+        schema["description"] = description.into();
+    }
+    Ok(schema)
+}
+
+fn object(fields: &Map<String, Value>, target: Target) -> Result<Value, Error> {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for (key, definition) in fields {
+        let (name, modifier) = match key.split_once('(') {
+            Some((name, modifier)) => (
+                name,
+                Some(
+                    modifier
+                        .strip_suffix(')')
+                        .ok_or_else(|| Error::Schema(format!("unclosed `(` in `{key}`")))?,
+                ),
+            ),
+            None => (key.as_str(), None),
+        };
+        let (name, optional) = match name.trim().strip_suffix('?') {
+            Some(name) => (name, true),
+            None => (name.trim(), false),
+        };
+        let (modifier, description) = match modifier.map(|modifier| modifier.split_once(',')) {
+            Some(Some((modifier, description))) => {
+                (Some(modifier.trim()), Some(description.trim()))
+            }
+            Some(None) => (modifier.map(str::trim), None),
+            None => (None, None),
+        };
+
+        let mut property = match modifier {
+            None => schema(definition, target)?,
+            Some("array") => json!({ "type": "array", "items": schema(definition, target)? }),
+            Some("object") => match definition {
+                Value::Object(fields) => object(fields, target)?,
+                _ => return Err(Error::Schema(format!("`{name}` must list its fields"))),
+            },
+            Some("enum") => enumeration(name, definition)?,
+            Some(other) => return Err(Error::Schema(format!("unknown modifier `{other}`"))),
+        };
+        if let Some(description) = description {
+            property["description"] = description.into();
+        }
+        if optional {
+            property["nullable"] = true.into();
+        } else {
+            required.push(Value::String(name.to_string()));
+        }
+        properties.insert(name.to_string(), property);
+    }
+
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        schema["required"] = Value::Array(required);
+    }
+    Ok(schema)
+}
+
+/// A string enum; a `null` value makes it nullable.
+fn enumeration(name: &str, definition: &Value) -> Result<Value, Error> {
+    let Value::Array(values) = definition else {
+        return Err(Error::Schema(format!("`{name}` must list its values")));
+    };
+    let mut schema = json!({ "type": "string" });
+    let mut strings = Vec::new();
+    for value in values {
+        match value {
+            Value::String(_) => strings.push(value.clone()),
+            Value::Null => schema["nullable"] = true.into(),
+            other => {
+                return Err(Error::Schema(format!(
+                    "`{name}` values must be strings, found {other}"
+                )));
+            }
+        }
+    }
+    schema["enum"] = Value::Array(strings);
+    Ok(schema)
+}
+
+/// Check `value` against the `type`, `nullable`, `enum`, `properties`,
+/// `required` and `items` keywords of a JSON Schema.
+fn validate(schema: &Value, value: &Value, path: &str) -> Result<(), Error> {
+    let invalid = |message: String| Error::InvalidInput {
+        path: path.to_string(),
+        message,
+    };
+    let Some(schema) = schema.as_object() else {
+        return Ok(());
+    };
+    if value.is_null() && schema.get("nullable") == Some(&Value::Bool(true)) {
+        return Ok(());
+    }
+
+    let kinds: Vec<&str> = match schema.get("type") {
+        Some(Value::String(kind)) => vec![kind],
+        Some(Value::Array(kinds)) => kinds.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if !kinds.is_empty() && !kinds.iter().any(|kind| is_type(kind, value)) {
+        return Err(invalid(format!("expected {}", kinds.join(" or "))));
+    }
+    if let Some(Value::Array(values)) = schema.get("enum")
+        && !values.contains(value)
+    {
+        return Err(invalid(format!(
+            "expected one of {}",
+            Value::Array(values.clone())
+        )));
+    }
+
+    if let Value::Object(fields) = value {
+        let required: Vec<&str> = match schema.get("required") {
+            Some(Value::Array(required)) => required.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        for name in &required {
+            if fields.get(*name).is_none_or(Value::is_null) {
+                return Err(invalid(format!("missing required field `{name}`")));
+            }
+        }
+        if let Some(Value::Object(properties)) = schema.get("properties") {
+            for (name, property) in properties {
+                match fields.get(name) {
+                    Some(Value::Null) | None => {}
+                    Some(field) => validate(property, field, &format!("{path}.{name}"))?,
+                }
+            }
+        }
+    }
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (index, item) in items.iter().enumerate() {
+            validate(item_schema, item, &format!("{path}[{index}]"))?;
+        }
+    }
+    Ok(())
+}
+
+fn is_type(kind: &str, value: &Value) -> bool {
+    match kind {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        _ => true,
+    }
+}
@@ -0,0 +1,671 @@
+use super::{
+    ClientMessage, Content, FunctionResponse, InlineData, Part, PartData, RealtimeInput, Role,
+    ServerMessage, SessionResumptionConfig, Setup, ToolResponse,
+};
+use crate::v1beta::{
+    media::{i16_to_bytes, pcm_mime_type},
+    telemetry::{self, Operation},
+    usage::{UsageSnapshot, UsageTracker},
+};
+use async_trait::async_trait;
+use derive_new::new;
+use derive_setters::Setters;
+use ezsockets::client::ClientCloseMode;
+use ezsockets::{
+    Bytes, Client as EzClient, ClientConfig, ClientExt, CloseFrame, Error as EzError, Utf8Bytes,
+    WSError,
+};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::mpsc::{Sender, WeakSender, channel};
+use tokio::sync::oneshot;
+use tokio::time::Instant;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, error, info, warn};
+
+/// Default websocket endpoint for Gemini Live API.
+const DEFAULT_WS_ENDPOINT: &str = "wss://generativelanguage.googleapis.com/ws/google.ai.generativelanguage.v1beta.GenerativeService.BidiGenerateContent";
+/// Default channel capacity for message streams.
+const DEFAULT_CHANNEL_CAPACITY: usize = 1024;
+/// Default time to wait for `setupComplete` after connecting.
+const DEFAULT_SETUP_TIMEOUT: Duration = Duration::from_secs(30);
+/// Time allowed for replacing a connection when a `GoAway` has no `timeLeft`.
+const DEFAULT_GO_AWAY_GRACE: Duration = Duration::from_secs(10);
+/// Pause between attempts to replace a connection after a `GoAway` or to
+/// resume the session after a drop.
+const RECONNECT_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+/// Default number of attempts to resume the session after a drop.
+const DEFAULT_RECONNECT_ATTEMPTS: u32 = 20;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Ez(#[from] EzError),
+    #[error(transparent)]
+    Url(#[from] url::ParseError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Join(#[from] tokio::task::JoinError),
+    /// DNS resolution, TCP or TLS failed before the websocket handshake.
+    #[error("could not reach the live endpoint: {0}")]
+    Connect(#[source] Box<WSError>),
+    /// The server refused the websocket upgrade.
+    #[error("websocket handshake failed: {0}")]
+    Handshake(#[source] Box<WSError>),
+    /// The API key was missing, invalid or not allowed to use the model.
+    #[error("authentication rejected: {0}")]
+    Unauthorized(String),
+    /// The server closed the connection in response to the `Setup` message,
+    /// e.g. for an unknown model or an invalid configuration.
+    #[error("setup rejected with close code {code}: {reason}")]
+    SetupRejected { code: u16, reason: String },
+    #[error("no setupComplete received within {0:?}")]
+    SetupTimeout(Duration),
+    #[error("connection closed before setup completed")]
+    Closed,
+    /// The server closed an established connection that could not be resumed.
+    #[error("connection closed by the server with code {code}: {reason}")]
+    Disconnected { code: u16, reason: String },
+}
+
+impl Error {
+    fn from_connect_fail(error: WSError) -> Self {
+        match &error {
+            WSError::Http(response) if matches!(response.status().as_u16(), 401 | 403) => {
+                let body = response
+                    .body()
+                    .as_deref()
+                    .map(String::from_utf8_lossy)
+                    .unwrap_or_default();
+                Error::Unauthorized(format!("{} {}", response.status(), body.trim()))
+            }
+            WSError::Io(_) | WSError::Tls(_) | WSError::Url(_) => Error::Connect(Box::new(error)),
+            _ => Error::Handshake(Box::new(error)),
+        }
+    }
+
+    fn from_close(frame: Option<CloseFrame>) -> Self {
+        let Some(frame) = frame else {
+            return Error::Closed;
+        };
+        let code = u16::from(frame.code);
+        let reason = frame.reason.to_string();
+        // The API reports bad keys as a policy violation with an explanatory reason.
+        let lowercase = reason.to_lowercase();
+        if code == 1008
+            && (lowercase.contains("api key")
+                || lowercase.contains("permission")
+                || lowercase.contains("unauthenticated"))
+        {
+            Error::Unauthorized(reason)
+        } else {
+            Error::SetupRejected { code, reason }
+        }
+    }
+}
+
+/// Options for [`Client::connect_with_options`].
+#[derive(Debug, Clone, new, Setters)]
+#[setters(prefix = "with_", into)]
+pub struct ConnectOptions {
+    #[new(value = "DEFAULT_WS_ENDPOINT.to_string()")]
+    endpoint: String,
+    /// How long to wait for the server's `setupComplete` after the websocket is
+    /// open. Defaults to 30 seconds.
+    #[new(value = "DEFAULT_SETUP_TIMEOUT")]
+    setup_timeout: Duration,
+    /// Open a replacement connection when the server sends a `GoAway`,
+    /// resuming the session with the latest resumption handle. Without a
+    /// handle the stream ends with [`Error::Disconnected`] once the server
+    /// closes the connection. Defaults to true.
+    #[new(value = "true")]
+    reconnect_on_go_away: bool,
+    /// How often to try resuming the session when an established connection
+    /// drops, half a second apart, before the stream ends with
+    /// [`Error::Disconnected`]. Defaults to 20.
+    #[new(value = "DEFAULT_RECONNECT_ATTEMPTS")]
+    reconnect_attempts: u32,
+    /// Record prompts and completions on the telemetry spans. Defaults to the
+    /// value of [`telemetry::CAPTURE_CONTENT_ENV`].
+    #[new(value = "telemetry::capture_content_from_env()")]
+    capture_content: bool,
+}
+
+impl Default for ConnectOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// State shared by every [`Client`] handle of a session.
+struct Session {
+    api_key: String,
+    setup: Setup,
+    options: ConnectOptions,
+    /// Held weakly so the message stream ends once every connection is gone.
+    sender: WeakSender<Result<ServerMessage, Error>>,
+    usage_tracker: UsageTracker,
+    /// Latest resumable handle, sent in the setup of every new connection.
+    resumption: Arc<Mutex<Option<SessionResumptionConfig>>>,
+    /// The connection calls go to, replaced after a `GoAway` and cleared on
+    /// disconnect.
+    connection: Mutex<Option<EzClient<WsClient>>>,
+}
+
+impl Session {
+    /// Open a new connection and wait for the server to acknowledge the setup.
+    /// A `replacement` continues the session, so its `setupComplete` is not
+    /// forwarded.
+    async fn open(self: &Arc<Self>, replacement: bool) -> Result<EzClient<WsClient>, Error> {
+        let sender = self.sender.upgrade().ok_or(Error::Closed)?;
+        let config = ClientConfig::new(self.options.endpoint.as_str())
+            .query_parameter("key", &self.api_key)
+            .max_initial_connect_attempts(1)
+            .reconnect_interval(RECONNECT_RETRY_INTERVAL);
+        let (tx_connected, rx_connected) = oneshot::channel();
+        let operation = Operation::start("chat", &self.setup.model, self.options.capture_content);
+        let session = self.clone();
+        let (handle, _fut) = ezsockets::connect(
+            move |h| WsClient::new(&session, sender, h, tx_connected, operation, replacement),
+            config,
+        )
+        .await;
+
+        let result = match tokio::time::timeout(self.options.setup_timeout, rx_connected).await {
+            Ok(Ok(result)) => result,
+            // The client actor stopped without reporting, e.g. it failed inside a hook.
+            Ok(Err(_)) => Err(Error::Closed),
+            Err(_) => Err(Error::SetupTimeout(self.options.setup_timeout)),
+        };
+        match result {
+            Ok(()) => Ok(handle),
+            Err(e) => {
+                error!(endpoint = %self.options.endpoint, error = %e, "live connection failed");
+                let _ = handle.close(None);
+                Err(e)
+            }
+        }
+    }
+
+    /// Move the session to a resumed connection before the server closes the
+    /// current one, retrying until `time_left` runs out.
+    async fn reconnect(self: Arc<Self>, time_left: Option<Duration>) {
+        let Some(sender) = self.sender.upgrade() else {
+            return;
+        };
+        let deadline = Instant::now() + time_left.unwrap_or(DEFAULT_GO_AWAY_GRACE);
+        let connection = loop {
+            match self.open(true).await {
+                Ok(connection) => break connection,
+                Err(e) if Instant::now() + RECONNECT_RETRY_INTERVAL < deadline => {
+                    warn!(error = %e, "live reconnect failed, retrying");
+                    tokio::time::sleep(RECONNECT_RETRY_INTERVAL).await;
+                }
+                Err(e) => {
+                    let _ = sender.send(Err(e)).await;
+                    return;
+                }
+            }
+        };
+
+        let previous = {
+            let mut current = self.connection.lock().unwrap();
+            if current.is_none() {
+                // Disconnected while the new connection was being opened.
+                let _ = connection.close(None);
+                return;
+            }
+            current.replace(connection)
+        };
+        if let Some(previous) = previous {
+            let _ = previous.close(None);
+        }
+        info!("live session moved to a new connection");
+        let _ = sender
+            .send(Ok(ServerMessage::Reconnected { resumed: true }))
+            .await;
+    }
+}
+
+struct WsClient {
+    setup: Setup,
+    sender: Sender<Result<ServerMessage, Error>>,
+    inner: ezsockets::Client<Self>,
+    /// Resolved with the outcome of the first connection, once the setup is
+    /// acknowledged or the attempt fails.
+    connected_sender: Option<oneshot::Sender<Result<(), Error>>>,
+    /// Telemetry span of the connection, finished when the client is dropped.
+    operation: Operation,
+    usage_tracker: UsageTracker,
+    resumption: Arc<Mutex<Option<SessionResumptionConfig>>>,
+    session: Weak<Session>,
+    /// Whether this connection continues the session of a previous one.
+    replacement: bool,
+    /// Set once a replacement connection is being opened after a `GoAway`.
+    replaced: bool,
+    /// Close code and reason of a dropped connection while it is being
+    /// resumed.
+    lost: Option<(u16, String)>,
+    /// Failed attempts to resume since the connection dropped.
+    reconnect_attempts: u32,
+    max_reconnect_attempts: u32,
+    /// Contents sent and received on this connection, kept only while content
+    /// is captured and recorded on the span when the connection ends.
+    input: Vec<Content>,
+    output: Vec<Content>,
+    /// Whether the last content in `output` is a model turn still streaming.
+    turn_open: bool,
+}
+
+#[async_trait]
+impl ClientExt for WsClient {
+    type Call = ClientMessage;
+
+    async fn on_text(&mut self, text: Utf8Bytes) -> Result<(), EzError> {
+        debug!("received message: {}", text);
+        self.handle_message(text.as_bytes()).await
+    }
+
+    async fn on_binary(&mut self, bytes: Bytes) -> Result<(), EzError> {
+        debug!("received binary message: {:?}", bytes);
+        self.handle_message(bytes.as_ref()).await
+    }
+
+    async fn on_call(&mut self, call: Self::Call) -> Result<(), EzError> {
+        if self.operation.captures_content() {
+            match &call {
+                ClientMessage::ClientContent(content) => {
+                    self.input.extend(content.turns.iter().cloned())
+                }
+                ClientMessage::RealtimeInput(RealtimeInput::Text(text)) => self.input.push(
+                    Content::new(Role::User, vec![Part::new(PartData::Text(text.clone()))]),
+                ),
+                _ => {}
+            }
+        }
+        let msg = serde_json::to_string(&call)?;
+        match call {
+            ClientMessage::RealtimeInput(_) => {}
+            _ => debug!("sending message: {:?}", msg),
+        };
+
+        self.inner
+            .text(msg)
+            .map_err(|e| Error::from(EzError::from(e)))?;
+        Ok(())
+    }
+
+    async fn on_connect(&mut self) -> Result<(), EzError> {
+        let mut setup = self.setup.clone();
+        if let Some(session_resumption) = self.resumption.lock().unwrap().clone() {
+            setup.session_resumption = Some(session_resumption);
+        }
+
+        let _ = self.inner.call(ClientMessage::Setup(setup));
+        Ok(())
+    }
+
+    async fn on_connect_fail(&mut self, error: WSError) -> Result<ClientCloseMode, EzError> {
+        // Fail the initial connect right away; retry later reconnects a while.
+        match self.connected_sender.take() {
+            Some(tx) => {
+                let _ = tx.send(Err(Error::from_connect_fail(error)));
+                Ok(ClientCloseMode::Close)
+            }
+            None => {
+                warn!(%error, "live reconnect failed");
+                Ok(self.reconnect_failed().await)
+            }
+        }
+    }
+
+    async fn on_close(&mut self, frame: Option<CloseFrame>) -> Result<ClientCloseMode, EzError> {
+        if let Some(tx) = self.connected_sender.take() {
+            let _ = tx.send(Err(Error::from_close(frame)));
+            return Ok(ClientCloseMode::Close);
+        }
+        let (code, reason) = frame
+            .map(|frame| (u16::from(frame.code), frame.reason.to_string()))
+            .unwrap_or((1005, String::new()));
+        Ok(self.connection_lost(code, reason).await)
+    }
+
+    async fn on_disconnect(&mut self) -> Result<ClientCloseMode, EzError> {
+        match self.connected_sender.take() {
+            Some(tx) => {
+                let _ = tx.send(Err(Error::Closed));
+                Ok(ClientCloseMode::Close)
+            }
+            None => Ok(self.connection_lost(1006, String::new()).await),
+        }
+    }
+}
+
+impl WsClient {
+    fn new(
+        session: &Arc<Session>,
+        sender: Sender<Result<ServerMessage, Error>>,
+        inner: ezsockets::Client<Self>,
+        connected_sender: oneshot::Sender<Result<(), Error>>,
+        operation: Operation,
+        replacement: bool,
+    ) -> Self {
+        Self {
+            setup: session.setup.clone(),
+            sender,
+            inner,
+            connected_sender: Some(connected_sender),
+            operation,
+            usage_tracker: session.usage_tracker.clone(),
+            resumption: session.resumption.clone(),
+            session: Arc::downgrade(session),
+            replacement,
+            replaced: false,
+            lost: None,
+            reconnect_attempts: 0,
+            max_reconnect_attempts: session.options.reconnect_attempts,
+            input: Vec::new(),
+            output: Vec::new(),
+            turn_open: false,
+        }
+    }
+
+    /// A connection being replaced is left to close; others reconnect.
+    fn close_mode(&self) -> ClientCloseMode {
+        if self.replaced {
+            ClientCloseMode::Close
+        } else {
+            ClientCloseMode::Reconnect
+        }
+    }
+
+    /// Decide what to do after an established connection dropped.
+    async fn connection_lost(&mut self, code: u16, reason: String) -> ClientCloseMode {
+        if self.replaced {
+            return self.close_mode();
+        }
+        if self.resumption.lock().unwrap().is_some() {
+            // Dropped again before the resumed session was set up.
+            let retry = self.lost.is_some();
+            warn!(code, %reason, "live connection lost, resuming the session");
+            self.lost = Some((code, reason));
+            if retry {
+                return self.reconnect_failed().await;
+            }
+            return self.close_mode();
+        }
+        // Without a resumption handle a new connection would silently start a
+        // fresh session, so end the stream instead.
+        let _ = self
+            .sender
+            .send(Err(Error::Disconnected { code, reason }))
+            .await;
+        ClientCloseMode::Close
+    }
+
+    async fn handle_message(&mut self, bytes: &[u8]) -> Result<(), EzError> {
+        let messages = match ServerMessage::from_frame(bytes) {
+            Ok(messages) => messages,
+            Err(e) => {
+                error!("failed to deserialize message: {}", e);
+                if self.sender.send(Err(Error::from(e))).await.is_err() {
+                    return Err("failed to send message".into());
+                }
+                return Ok(());
+            }
+        };
+        for msg in messages {
+            self.handle_server_message(msg).await?;
+        }
+        Ok(())
+    }
+
+    /// Count a failed attempt to resume after a drop and end the stream once
+    /// they are used up.
+    async fn reconnect_failed(&mut self) -> ClientCloseMode {
+        self.reconnect_attempts += 1;
+        if self.reconnect_attempts < self.max_reconnect_attempts {
+            return self.close_mode();
+        }
+        let (code, reason) = self.lost.take().unwrap_or((1006, String::new()));
+        error!(
+            attempts = self.reconnect_attempts,
+            "could not resume the live session"
+        );
+        let _ = self
+            .sender
+            .send(Err(Error::Disconnected { code, reason }))
+            .await;
+        ClientCloseMode::Close
+    }
+
+    async fn handle_server_message(&mut self, msg: ServerMessage) -> Result<(), EzError> {
+        if let ServerMessage::ServerContent {
+            usage_metadata: Some(usage),
+            ..
+        }
+        | ServerMessage::UsageMetadata(usage) = &msg
+        {
+            let tokens = |count: Option<i32>| count.and_then(|c| u32::try_from(c).ok());
+            self.operation.record_usage(
+                tokens(usage.prompt_token_count),
+                tokens(usage.response_token_count),
+            );
+            self.usage_tracker.record(&self.setup.model, usage);
+        }
+
+        if self.operation.captures_content()
+            && let ServerMessage::ServerContent { server_content, .. } = &msg
+        {
+            if let Some(turn) = &server_content.model_turn {
+                match self.output.last_mut() {
+                    Some(last) if self.turn_open => last.parts.extend(turn.parts.iter().cloned()),
+                    _ => self.output.push(turn.clone()),
+                }
+                self.turn_open = true;
+            }
+            if server_content.turn_complete || server_content.interrupted {
+                self.turn_open = false;
+            }
+        }
+
+        if let ServerMessage::SessionResumptionUpdate(update) = &msg
+            && update.resumable == Some(true)
+            && let Some(handle) = update.new_handle.clone()
+        {
+            self.resumption
+                .lock()
+                .unwrap()
+                .replace(SessionResumptionConfig { handle });
+        }
+
+        if let ServerMessage::SetupComplete = &msg
+            && let Some(tx) = self.connected_sender.take()
+        {
+            let _ = tx.send(Ok(()));
+        }
+        let resumed = matches!(msg, ServerMessage::SetupComplete) && self.lost.take().is_some();
+        if resumed {
+            info!("live session resumed after the connection dropped");
+            self.reconnect_attempts = 0;
+        }
+
+        if let ServerMessage::GoAway(go_away) = &msg
+            && !self.replaced
+            && let Some(session) = self.session.upgrade()
+            && session.options.reconnect_on_go_away
+        {
+            // Like a drop, a GoAway without a handle ends the session once the
+            // server closes the connection rather than starting a fresh one.
+            if self.resumption.lock().unwrap().is_some() {
+                info!(time_left = ?go_away.time_left, "live server is going away, reconnecting");
+                self.replaced = true;
+                tokio::spawn(session.reconnect(go_away.time_left));
+            } else {
+                warn!("live server is going away and the session cannot be resumed");
+            }
+        }
+
+        if let ServerMessage::Unknown(value) = &msg {
+            debug!(%value, "received unknown message");
+        }
+
+        // A resumed connection continues the session instead of starting one.
+        let msg = match msg {
+            ServerMessage::SetupComplete if resumed => ServerMessage::Reconnected { resumed },
+            ServerMessage::SetupComplete if self.replacement => return Ok(()),
+            msg => msg,
+        };
+        if self.sender.send(Ok(msg)).await.is_err() {
+            return Err("failed to send message".into());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for WsClient {
+    fn drop(&mut self) {
+        self.operation
+            .record_live_contents(&self.input, &self.output);
+        self.operation.finish(None);
+    }
+}
+
+#[derive(Clone)]
+/// Client for interacting with the Gemini Live websocket API.
+///
+/// When the server announces a `GoAway`, the client opens a new connection
+/// with the latest session resumption handle and moves over to it; clones of
+/// the client and the message stream stay valid across the switch. A dropped
+/// connection is resumed the same way. Without a handle the session cannot be
+/// resumed, and the stream ends with [`Error::Disconnected`] instead.
+pub struct Client {
+    session: Arc<Session>,
+}
+
+impl std::fmt::Debug for Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Client")
+            .field("model", &self.session.setup.model)
+            .field("endpoint", &self.session.options.endpoint)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Client {
+    /// Establish a websocket connection using the provided API key and setup.
+    ///
+    /// Resolves once the server has acknowledged the setup. Returns the
+    /// [`Client`] and a stream of incoming [`ServerMessage`]s, with messages that
+    /// could not be decoded as `Err` items.
+    pub async fn connect(
+        api_key: impl Into<String>,
+        setup: Setup,
+    ) -> Result<(Self, ReceiverStream<Result<ServerMessage, Error>>), Error> {
+        Self::connect_with_options(api_key, setup, ConnectOptions::new()).await
+    }
+
+    /// Like [`Client::connect`], using a custom endpoint.
+    pub async fn connect_with_endpoint(
+        api_key: impl Into<String>,
+        setup: Setup,
+        endpoint: &str,
+    ) -> Result<(Self, ReceiverStream<Result<ServerMessage, Error>>), Error> {
+        let options = ConnectOptions::new().with_endpoint(endpoint);
+        Self::connect_with_options(api_key, setup, options).await
+    }
+
+    /// Like [`Client::connect`], with a custom endpoint and setup timeout.
+    #[tracing::instrument(
+        skip(api_key, setup, options),
+        fields(endpoint = %options.endpoint, model = %setup.model)
+    )]
+    pub async fn connect_with_options(
+        api_key: impl Into<String>,
+        setup: Setup,
+        options: ConnectOptions,
+    ) -> Result<(Self, ReceiverStream<Result<ServerMessage, Error>>), Error> {
+        let (tx, rx) = channel(DEFAULT_CHANNEL_CAPACITY);
+        let session = Arc::new(Session {
+            api_key: api_key.into(),
+            resumption: Arc::new(Mutex::new(setup.session_resumption.clone())),
+            setup,
+            options,
+            sender: tx.downgrade(),
+            usage_tracker: UsageTracker::new(),
+            connection: Mutex::new(None),
+        });
+        let connection = session.open(false).await?;
+        drop(tx);
+        info!(endpoint = %session.options.endpoint, "live session established");
+        session.connection.lock().unwrap().replace(connection);
+
+        Ok((Self { session }, ReceiverStream::new(rx)))
+    }
+
+    fn connection(&self) -> Result<EzClient<WsClient>, Error> {
+        self.session
+            .connection
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or(Error::Closed)
+    }
+
+    /// Send a message to the server.
+    pub fn call(&self, message: ClientMessage) -> Result<(), Error> {
+        Ok(self.connection()?.call(message).map_err(EzError::from)?)
+    }
+
+    /// Send text as realtime input.
+    pub fn send_text(&self, text: impl Into<String>) -> Result<(), Error> {
+        self.call(ClientMessage::RealtimeInput(RealtimeInput::Text(
+            text.into(),
+        )))
+    }
+
+    /// Send a chunk of 16-bit mono PCM audio recorded at `sample_rate`.
+    pub fn send_audio(&self, samples: &[i16], sample_rate: u32) -> Result<(), Error> {
+        self.call(ClientMessage::RealtimeInput(RealtimeInput::Audio(
+            InlineData::new(pcm_mime_type(sample_rate), i16_to_bytes(samples)),
+        )))
+    }
+
+    /// Send one encoded video frame, e.g. a JPEG image.
+    pub fn send_video_frame(
+        &self,
+        mime_type: impl Into<String>,
+        bytes: impl Into<Vec<u8>>,
+    ) -> Result<(), Error> {
+        self.call(ClientMessage::RealtimeInput(RealtimeInput::Video(
+            InlineData::new(mime_type, bytes.into()),
+        )))
+    }
+
+    /// Answer the function calls of a tool call.
+    pub fn send_tool_responses(&self, responses: Vec<FunctionResponse>) -> Result<(), Error> {
+        self.call(ClientMessage::ToolResponse(ToolResponse::new(responses)))
+    }
+
+    /// Tell the server the audio stream was paused, flushing any buffered audio.
+    pub fn end_audio_stream(&self) -> Result<(), Error> {
+        self.call(ClientMessage::RealtimeInput(RealtimeInput::AudioStreamEnd(
+            true,
+        )))
+    }
+
+    /// Usage of this session so far.
+    pub fn usage(&self) -> UsageSnapshot {
+        self.session.usage_tracker.snapshot()
+    }
+
+    /// Close the websocket connection.
+    pub fn disconnect(self, reason: Option<CloseFrame>) -> Result<(), Error> {
+        let connection = self.session.connection.lock().unwrap().take();
+        let connection = connection.ok_or(Error::Closed)?;
+        connection.close(reason).map_err(EzError::from)?;
+        Ok(())
+    }
+}
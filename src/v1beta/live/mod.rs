@@ -1,33 +1,21 @@
-use async_trait::async_trait;
 use base64::{Engine as _, engine::general_purpose};
 use derive_new::new;
 use derive_setters::Setters;
-use ezsockets::{
-    Bytes, Client as EzClient, ClientConfig, ClientExt, CloseFrame, Error as EzError, Utf8Bytes,
-};
 use serde::{Deserialize, Serialize};
-use thiserror::Error;
-use tokio::sync::mpsc::{Sender, channel};
-use tokio::sync::oneshot;
-use tokio_stream::wrappers::ReceiverStream;
-use tracing::{debug, error, info};
-
-/// Default websocket endpoint for Gemini Live API.
-const DEFAULT_WS_ENDPOINT: &str = "wss://generativelanguage.googleapis.com/ws/google.ai.generativelanguage.v1beta.GenerativeService.BidiGenerateContent";
-/// Default channel capacity for message streams.
-const DEFAULT_CHANNEL_CAPACITY: usize = 1024;
-
-#[derive(Debug, Error)]
-pub enum Error {
-    #[error(transparent)]
-    Ez(#[from] EzError),
-    #[error(transparent)]
-    Url(#[from] url::ParseError),
-    #[error(transparent)]
-    Json(#[from] serde_json::Error),
-    #[error(transparent)]
-    Join(#[from] tokio::task::JoinError),
-}
+
+#[cfg(feature = "live")]
+mod client;
+#[cfg(feature = "live")]
+mod session;
+#[cfg(feature = "live")]
+mod tools;
+
+#[cfg(feature = "live")]
+pub use client::{Client, ConnectOptions, Error};
+#[cfg(feature = "live")]
+pub use session::{Event, LiveSession};
+#[cfg(feature = "live")]
+pub use tools::ToolRegistry;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
@@ -443,6 +431,11 @@ impl InlineData {
     pub fn mime_type(&self) -> &str {
         &self.mime_type
     }
+
+    /// Decode an `audio/pcm` payload, such as the model's spoken reply, into samples.
+    pub fn to_pcm(&self) -> Result<super::media::PcmAudio, super::media::Error> {
+        super::media::PcmAudio::from_inline(&self.mime_type, &self.data)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, new)]
@@ -487,6 +480,24 @@ pub struct EndOffset {
     nanos: i32,
 }
 
+impl From<std::time::Duration> for StartOffset {
+    fn from(duration: std::time::Duration) -> Self {
+        Self {
+            seconds: duration.as_secs() as i64,
+            nanos: duration.subsec_nanos() as i32,
+        }
+    }
+}
+
+impl From<std::time::Duration> for EndOffset {
+    fn from(duration: std::time::Duration) -> Self {
+        Self {
+            seconds: duration.as_secs() as i64,
+            nanos: duration.subsec_nanos() as i32,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, new)]
 pub struct ExecutableCode {
     #[new(into)]
@@ -542,6 +553,16 @@ pub struct Part {
     #[serde(default)]
     #[new(default)]
     pub thought: bool,
+
+    /// Opaque signature of the model's reasoning, to be sent back unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[new(default)]
+    pub thought_signature: Option<String>,
+
+    /// Clipping interval for the video in a [`PartData::FileData`] part.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[new(default)]
+    pub video_metadata: Option<VideoMetadata>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, new)]
@@ -552,6 +573,59 @@ pub struct Content {
     pub parts: Vec<Part>,
 }
 
+impl Content {
+    /// Text of all non-thought parts joined together, or `None` if there is none.
+    pub fn text(&self) -> Option<String> {
+        join_text(self.parts.iter().filter(|part| !part.thought))
+    }
+
+    /// Text of all thought parts joined together, or `None` if there is none.
+    pub fn thoughts(&self) -> Option<String> {
+        join_text(self.parts.iter().filter(|part| part.thought))
+    }
+
+    pub fn function_calls(&self) -> Vec<&FunctionCall> {
+        self.parts
+            .iter()
+            .filter_map(|part| match &part.data {
+                PartData::FunctionCall(call) => Some(call),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn inline_data(&self) -> Vec<&InlineData> {
+        self.parts
+            .iter()
+            .filter_map(|part| match &part.data {
+                PartData::InlineData(data) => Some(data),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn executable_code(&self) -> Vec<&ExecutableCode> {
+        self.parts
+            .iter()
+            .filter_map(|part| match &part.data {
+                PartData::ExecutableCode(code) => Some(code),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+fn join_text<'a>(parts: impl Iterator<Item = &'a Part>) -> Option<String> {
+    parts
+        .filter_map(|part| match &part.data {
+            PartData::Text(text) => Some(text.as_str()),
+            _ => None,
+        })
+        .fold(None, |joined: Option<String>, text| {
+            Some(joined.unwrap_or_default() + text)
+        })
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Role {
@@ -638,6 +712,21 @@ where
     serializer.serialize_str(&general_purpose::STANDARD.encode(bytes))
 }
 
+/// Deserialize a protobuf JSON duration such as `"9.5s"`.
+fn deserialize_duration<'de, D>(deserializer: D) -> Result<Option<std::time::Duration>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let Some(s) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    s.strip_suffix('s')
+        .and_then(|seconds| seconds.parse::<f64>().ok())
+        .and_then(|seconds| std::time::Duration::try_from_secs_f64(seconds).ok())
+        .map(Some)
+        .ok_or_else(|| serde::de::Error::custom(format!("invalid duration `{s}`")))
+}
+
 /// Deserialize a base64 encoded string into raw bytes.
 fn deserialize_base64<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
 where
@@ -657,6 +746,7 @@ pub struct ToolResponse {
 
 #[derive(Debug, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
+#[allow(clippy::large_enum_variant)]
 pub enum ClientMessage {
     Setup(Setup),
     ClientContent(ClientContent),
@@ -667,34 +757,84 @@ pub enum ClientMessage {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SetupComplete {}
 
-#[derive(Debug, Clone, Deserialize)]
+/// Incremental update of the model's turn. The server sets several fields at
+/// once, e.g. the last `model_turn` chunk together with `turn_complete`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub enum ServerContent {
-    ModelTurn(Content),
+pub struct ServerContent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_turn: Option<Content>,
+    /// The model finished its turn and waits for the client.
+    #[serde(default)]
+    pub turn_complete: bool,
+    /// The model finished generating, possibly before the turn is complete.
+    #[serde(default)]
+    pub generation_complete: bool,
+    /// The client interrupted generation, e.g. by starting to speak.
+    #[serde(default)]
+    pub interrupted: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grounding_metadata: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_transcription: Option<Transcription>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_transcription: Option<Transcription>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url_context_metadata: Option<serde_json::Value>,
+    /// Why the turn completed, sent along with `turn_complete`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub turn_complete_reason: Option<TurnCompleteReason>,
+    /// The model expects more input before it continues.
+    #[serde(default)]
+    pub waiting_for_input: bool,
+}
 
-    #[serde(deserialize_with = "deserialize_ignore")]
-    GenerationComplete,
-    #[serde(deserialize_with = "deserialize_ignore")]
-    TurnComplete,
-    #[serde(deserialize_with = "deserialize_ignore")]
-    Interrupted,
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TurnCompleteReason {
+    TurnCompleteReasonUnspecified,
+    MalformedFunctionCall,
+    ResponseRejected,
+    NeedMoreInput,
+    /// A reason added to the API after this crate was released.
+    #[serde(other)]
+    Unknown,
+}
+
+impl ServerContent {
+    pub fn model_turn(&self) -> Option<&Content> {
+        self.model_turn.as_ref()
+    }
 
-    GroundingMetadata(serde_json::Value),
-    OutputTranscription(Transcription),
-    InputTranscription(Transcription),
-}
+    /// Text of the model turn, without thought parts.
+    pub fn text(&self) -> Option<String> {
+        self.model_turn()?.text()
+    }
 
-// Custom deserializer that ignores the boolean value and returns unit type
-fn deserialize_ignore<'de, D>(deserializer: D) -> Result<(), D::Error>
-where
-    D: serde::Deserializer<'de>,
-{
-    // Deserialize the boolean but ignore its value
-    let _ignored: serde_json::Value = serde_json::Value::deserialize(deserializer)?;
-    Ok(())
+    pub fn thoughts(&self) -> Option<String> {
+        self.model_turn()?.thoughts()
+    }
+
+    pub fn function_calls(&self) -> Vec<&FunctionCall> {
+        self.model_turn()
+            .map(Content::function_calls)
+            .unwrap_or_default()
+    }
+
+    pub fn inline_data(&self) -> Vec<&InlineData> {
+        self.model_turn()
+            .map(Content::inline_data)
+            .unwrap_or_default()
+    }
+
+    pub fn executable_code(&self) -> Vec<&ExecutableCode> {
+        self.model_turn()
+            .map(Content::executable_code)
+            .unwrap_or_default()
+    }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Transcription {
     pub text: String,
@@ -717,7 +857,9 @@ pub struct ToolCallCancellation {
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GoAway {
-    pub time_left: Option<String>,
+    /// Time until the server closes the connection.
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub time_left: Option<std::time::Duration>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -747,6 +889,7 @@ pub struct UsageMetadata {
 }
 
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum ServerMessage {
     SetupComplete,
     ServerContent {
@@ -757,175 +900,80 @@ pub enum ServerMessage {
     ToolCallCancellation(ToolCallCancellation),
     GoAway(GoAway),
     SessionResumptionUpdate(SessionResumptionUpdate),
+    /// Token usage sent on its own, without content.
+    UsageMetadata(UsageMetadata),
+    /// A message this crate does not know yet, kept as sent.
+    Unknown(serde_json::Value),
+    /// Not sent by the server: the client moved the session to a new
+    /// connection after a [`GoAway`] or a drop. Sent in place of the new
+    /// connection's `setupComplete`; `resumed` is true, as the client only
+    /// reconnects with a session resumption handle.
+    Reconnected {
+        resumed: bool,
+    },
 }
 
-impl<'de> Deserialize<'de> for ServerMessage {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        #[derive(Deserialize)]
-        #[serde(rename_all = "camelCase")]
-        struct Helper {
-            setup_complete: Option<SetupComplete>,
-            server_content: Option<ServerContent>,
-            usage_metadata: Option<UsageMetadata>,
-            tool_call: Option<ToolCall>,
-            tool_call_cancellation: Option<ToolCallCancellation>,
-            go_away: Option<GoAway>,
-            session_resumption_update: Option<SessionResumptionUpdate>,
-        }
-
-        let helper = Helper::deserialize(deserializer)?;
-        if helper.setup_complete.is_some() {
-            Ok(ServerMessage::SetupComplete)
-        } else if let Some(content) = helper.server_content {
-            Ok(ServerMessage::ServerContent {
-                server_content: content,
-                usage_metadata: helper.usage_metadata,
-            })
-        } else if let Some(call) = helper.tool_call {
-            Ok(ServerMessage::ToolCall(call))
-        } else if let Some(cancel) = helper.tool_call_cancellation {
-            Ok(ServerMessage::ToolCallCancellation(cancel))
-        } else if let Some(go) = helper.go_away {
-            Ok(ServerMessage::GoAway(go))
-        } else if let Some(update) = helper.session_resumption_update {
-            Ok(ServerMessage::SessionResumptionUpdate(update))
-        } else {
-            Err(serde::de::Error::custom("unknown server message"))
-        }
-    }
-}
-
-#[derive(Debug, new)]
-struct WsClient {
-    setup: Setup,
-    sender: Sender<ServerMessage>,
-    inner: ezsockets::Client<Self>,
-    #[new(into)]
-    connected_sender: Option<oneshot::Sender<()>>,
-    #[new(default)]
-    session_resumption: Option<SessionResumptionConfig>,
-}
-
-#[async_trait]
-impl ClientExt for WsClient {
-    type Call = ClientMessage;
-
-    async fn on_text(&mut self, text: Utf8Bytes) -> Result<(), EzError> {
-        debug!("received message: {}", text);
-
-        Ok(())
-    }
-
-    async fn on_binary(&mut self, bytes: Bytes) -> Result<(), EzError> {
-        debug!("received binary message: {:?}", bytes);
-
-        match serde_json::from_slice::<ServerMessage>(bytes.as_ref()) {
-            Ok(msg) => {
-                if let ServerMessage::SessionResumptionUpdate(update) = &msg {
-                    if update.resumable == Some(true) {
-                        if let Some(handle) = update.new_handle.clone() {
-                            self.session_resumption.replace(SessionResumptionConfig {
-                                handle: handle.clone(),
-                            });
-                        }
-                    }
-                }
-
-                if self.sender.send(msg).await.is_err() {
-                    return Err("failed to send message".into());
-                }
-            }
-            Err(e) => {
-                error!("failed to deserialize message: {}", e);
-            }
-        }
-
-        Ok(())
-    }
-
-    async fn on_call(&mut self, call: Self::Call) -> Result<(), EzError> {
-        let msg = serde_json::to_string(&call)?;
-        match call {
-            ClientMessage::RealtimeInput(_) => {}
-            _ => debug!("sending message: {:?}", msg),
-        };
-
-        self.inner
-            .text(msg)
-            .map_err(|e| Error::from(EzError::from(e)))?;
-        Ok(())
-    }
-
-    async fn on_connect(&mut self) -> Result<(), EzError> {
-        let mut setup = self.setup.clone();
-        if let Some(session_resumption) = self.session_resumption.clone() {
-            setup.session_resumption = Some(session_resumption);
-        }
-
-        let _ = self.inner.call(ClientMessage::Setup(setup));
-
-        if let Some(tx) = self.connected_sender.take() {
-            let _ = tx.send(());
-        }
-        Ok(())
+impl ServerMessage {
+    /// Parse one websocket frame. Usage sent along with anything but server
+    /// content follows the main message as a [`ServerMessage::UsageMetadata`].
+    pub fn from_frame(bytes: &[u8]) -> Result<Vec<Self>, serde_json::Error> {
+        let (message, usage) = split_frame(serde_json::from_slice(bytes)?)?;
+        Ok(std::iter::once(message)
+            .chain(usage.map(ServerMessage::UsageMetadata))
+            .collect())
     }
 }
 
-#[derive(Debug, Clone)]
-/// Client for interacting with the Gemini Live websocket API.
-pub struct Client {
-    client: EzClient<WsClient>,
-}
-
-impl Client {
-    /// Establish a websocket connection using the provided API key and setup.
-    ///
-    /// Returns the [`Client`] and a stream of incoming [`ServerMessage`]s.
-    pub async fn connect(
-        api_key: impl Into<String>,
-        setup: Setup,
-    ) -> Result<(Self, ReceiverStream<ServerMessage>), Error> {
-        Self::connect_with_endpoint(api_key, setup, DEFAULT_WS_ENDPOINT).await
+/// The main message of a frame, and its usage if the message cannot hold it.
+fn split_frame(
+    value: serde_json::Value,
+) -> Result<(ServerMessage, Option<UsageMetadata>), serde_json::Error> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Frame {
+        setup_complete: Option<SetupComplete>,
+        server_content: Option<ServerContent>,
+        usage_metadata: Option<UsageMetadata>,
+        tool_call: Option<ToolCall>,
+        tool_call_cancellation: Option<ToolCallCancellation>,
+        go_away: Option<GoAway>,
+        session_resumption_update: Option<SessionResumptionUpdate>,
     }
 
-    /// Establish a websocket connection using the provided API key, setup and
-    /// custom endpoint. Returns the [`Client`] and a stream of
-    /// incoming [`ServerMessage`]s.
-    #[tracing::instrument(
-        skip(api_key, setup),
-        fields(endpoint = %endpoint, setup = ?setup)
-    )]
-    pub async fn connect_with_endpoint(
-        api_key: impl Into<String>,
-        setup: Setup,
-        endpoint: &str,
-    ) -> Result<(Self, ReceiverStream<ServerMessage>), Error> {
-        let config = ClientConfig::new(endpoint).query_parameter("key", &api_key.into());
-        let (tx, rx) = channel(DEFAULT_CHANNEL_CAPACITY);
-        let (tx_connected, rx_connected) = oneshot::channel();
-        let setup_clone = setup.clone();
-        let (handle, _fut) =
-            ezsockets::connect(move |h| WsClient::new(setup, tx, h, tx_connected), config).await;
-
-        match rx_connected.await {
-            Ok(_) => info!(endpoint = %endpoint, ?setup_clone, "websocket connection established"),
-            Err(e) => error!(endpoint = %endpoint, ?setup_clone, ?e, "websocket connection failed"),
+    let frame = Frame::deserialize(&value)?;
+    let mut usage = frame.usage_metadata;
+    let message = if frame.setup_complete.is_some() {
+        ServerMessage::SetupComplete
+    } else if let Some(content) = frame.server_content {
+        ServerMessage::ServerContent {
+            server_content: content,
+            usage_metadata: usage.take(),
         }
-
-        Ok((Self { client: handle }, ReceiverStream::new(rx)))
-    }
-
-    /// Send a message to the server.
-    pub fn call(&self, message: ClientMessage) -> Result<(), Error> {
-        Ok(self.client.call(message).map_err(EzError::from)?)
-    }
-
-    /// Close the websocket connection.
-    pub fn disconnect(self, reason: Option<CloseFrame>) -> Result<(), Error> {
-        self.client.close(reason).map_err(EzError::from)?;
-        Ok(())
+    } else if let Some(call) = frame.tool_call {
+        ServerMessage::ToolCall(call)
+    } else if let Some(cancel) = frame.tool_call_cancellation {
+        ServerMessage::ToolCallCancellation(cancel)
+    } else if let Some(go) = frame.go_away {
+        ServerMessage::GoAway(go)
+    } else if let Some(update) = frame.session_resumption_update {
+        ServerMessage::SessionResumptionUpdate(update)
+    } else if let Some(usage) = usage.take() {
+        ServerMessage::UsageMetadata(usage)
+    } else {
+        ServerMessage::Unknown(value)
+    };
+    Ok((message, usage))
+}
+
+/// Only the main message of a frame; [`ServerMessage::from_frame`] also keeps
+/// the usage sent along with it.
+impl<'de> Deserialize<'de> for ServerMessage {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let (message, _) = split_frame(value).map_err(serde::de::Error::custom)?;
+        Ok(message)
     }
 }
@@ -0,0 +1,233 @@
+//! Flattened, typed events over the raw [`ServerMessage`] stream.
+use super::{
+    Client, ConnectOptions, Error, FunctionCall, FunctionResponse, PartData, ServerMessage, Setup,
+    ToolRegistry, UsageMetadata,
+};
+use crate::v1beta::usage::UsageSnapshot;
+use ezsockets::CloseFrame;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio_stream::Stream;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::debug;
+
+/// Something that happened in a live session.
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// A piece of the model's text reply.
+    TextDelta(String),
+    /// A piece of the model's spoken reply, as 16-bit mono PCM.
+    AudioChunk { samples: Vec<i16>, sample_rate: u32 },
+    /// Transcription of the user's audio.
+    InputTranscript(String),
+    /// Transcription of the model's audio.
+    OutputTranscript(String),
+    /// A piece of the model's reasoning.
+    Thought(String),
+    /// The model wants these functions called; answer with
+    /// [`LiveSession::send_tool_responses`]. Calls of functions registered with
+    /// [`LiveSession::with_tools`] are answered automatically.
+    ToolCall(Vec<FunctionCall>),
+    /// The function calls with these ids are no longer needed.
+    ToolCallCancelled(Vec<String>),
+    /// The user spoke over the model; drop any audio still queued for playback.
+    Interrupted,
+    /// The model finished its turn. `usage` covers the turn when the server
+    /// reported it.
+    TurnComplete { usage: Option<UsageMetadata> },
+    /// The model finished generating; playback of the turn may still be going.
+    GenerationComplete,
+    /// The server will close the connection after `time_left`.
+    GoAway { time_left: Option<Duration> },
+    /// The client moved the session to a new connection.
+    Reconnected { resumed: bool },
+    /// The session ended. Always the last event.
+    Closed { reason: Option<String> },
+}
+
+/// A live session that turns server messages into [`Event`]s and sends typed
+/// input.
+///
+/// Poll it as a [`Stream`] of events. Use [`LiveSession::client`] to send from
+/// other tasks.
+pub struct LiveSession {
+    client: Client,
+    stream: ReceiverStream<Result<ServerMessage, Error>>,
+    pending: VecDeque<Event>,
+    /// Usage sent on its own, attached to the next [`Event::TurnComplete`].
+    usage: Option<UsageMetadata>,
+    tools: Option<ToolRegistry>,
+    closed: bool,
+}
+
+impl LiveSession {
+    /// Wrap a connected client and its message stream.
+    pub fn new(client: Client, stream: ReceiverStream<Result<ServerMessage, Error>>) -> Self {
+        Self {
+            client,
+            stream,
+            pending: VecDeque::new(),
+            usage: None,
+            tools: None,
+            closed: false,
+        }
+    }
+
+    /// Connect with the default options, see [`Client::connect`].
+    pub async fn connect(api_key: impl Into<String>, setup: Setup) -> Result<Self, Error> {
+        Self::connect_with_options(api_key, setup, ConnectOptions::new()).await
+    }
+
+    /// Connect with custom options, see [`Client::connect_with_options`].
+    pub async fn connect_with_options(
+        api_key: impl Into<String>,
+        setup: Setup,
+        options: ConnectOptions,
+    ) -> Result<Self, Error> {
+        let (client, stream) = Client::connect_with_options(api_key, setup, options).await?;
+        Ok(Self::new(client, stream))
+    }
+
+    /// Run the handlers in `tools` for incoming tool calls and send their
+    /// responses.
+    pub fn with_tools(mut self, tools: ToolRegistry) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    /// The underlying client, which can be cloned into other tasks.
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    pub fn send_text(&self, text: impl Into<String>) -> Result<(), Error> {
+        self.client.send_text(text)
+    }
+
+    pub fn send_audio(&self, samples: &[i16], sample_rate: u32) -> Result<(), Error> {
+        self.client.send_audio(samples, sample_rate)
+    }
+
+    pub fn send_video_frame(
+        &self,
+        mime_type: impl Into<String>,
+        bytes: impl Into<Vec<u8>>,
+    ) -> Result<(), Error> {
+        self.client.send_video_frame(mime_type, bytes)
+    }
+
+    pub fn send_tool_responses(&self, responses: Vec<FunctionResponse>) -> Result<(), Error> {
+        self.client.send_tool_responses(responses)
+    }
+
+    pub fn end_audio_stream(&self) -> Result<(), Error> {
+        self.client.end_audio_stream()
+    }
+
+    /// Usage of this session so far.
+    pub fn usage(&self) -> UsageSnapshot {
+        self.client.usage()
+    }
+
+    /// Close the connection.
+    pub fn close(self, reason: Option<CloseFrame>) -> Result<(), Error> {
+        self.client.disconnect(reason)
+    }
+
+    /// Queue the events of one server message.
+    fn push(&mut self, message: ServerMessage) {
+        if let Some(tools) = &self.tools {
+            tools.dispatch(&self.client, &message);
+        }
+        match message {
+            ServerMessage::ServerContent {
+                server_content: content,
+                usage_metadata,
+            } => {
+                if let Some(transcription) = content.input_transcription {
+                    self.pending
+                        .push_back(Event::InputTranscript(transcription.text));
+                }
+                for part in content.model_turn.into_iter().flat_map(|turn| turn.parts) {
+                    match part.data {
+                        PartData::Text(text) if part.thought => {
+                            self.pending.push_back(Event::Thought(text))
+                        }
+                        PartData::Text(text) => self.pending.push_back(Event::TextDelta(text)),
+                        PartData::InlineData(data) => match data.to_pcm() {
+                            Ok(audio) => self.pending.push_back(Event::AudioChunk {
+                                samples: audio.samples,
+                                sample_rate: audio.sample_rate,
+                            }),
+                            Err(e) => debug!(error = %e, "skipping inline data"),
+                        },
+                        _ => {}
+                    }
+                }
+                if let Some(transcription) = content.output_transcription {
+                    self.pending
+                        .push_back(Event::OutputTranscript(transcription.text));
+                }
+                if content.interrupted {
+                    self.pending.push_back(Event::Interrupted);
+                }
+                if content.generation_complete {
+                    self.pending.push_back(Event::GenerationComplete);
+                }
+                let usage = usage_metadata.or_else(|| self.usage.take());
+                if content.turn_complete {
+                    self.pending.push_back(Event::TurnComplete { usage });
+                } else if usage.is_some() {
+                    self.usage = usage;
+                }
+            }
+            ServerMessage::UsageMetadata(usage) => self.usage = Some(usage),
+            ServerMessage::ToolCall(call) => {
+                self.pending.push_back(Event::ToolCall(call.function_calls))
+            }
+            ServerMessage::ToolCallCancellation(cancellation) => self
+                .pending
+                .push_back(Event::ToolCallCancelled(cancellation.ids)),
+            ServerMessage::GoAway(go_away) => self.pending.push_back(Event::GoAway {
+                time_left: go_away.time_left,
+            }),
+            ServerMessage::Reconnected { resumed } => {
+                self.pending.push_back(Event::Reconnected { resumed })
+            }
+            ServerMessage::SetupComplete
+            | ServerMessage::SessionResumptionUpdate(_)
+            | ServerMessage::Unknown(_) => {}
+        }
+    }
+}
+
+impl Stream for LiveSession {
+    type Item = Result<Event, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Poll::Ready(Some(Ok(event)));
+            }
+            if self.closed {
+                return Poll::Ready(None);
+            }
+            match Pin::new(&mut self.stream).poll_next(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Some(Ok(message))) => self.push(message),
+                Poll::Ready(Some(Err(Error::Disconnected { reason, .. }))) => {
+                    self.closed = true;
+                    let reason = Some(reason).filter(|reason| !reason.is_empty());
+                    self.pending.push_back(Event::Closed { reason });
+                }
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                Poll::Ready(None) => {
+                    self.closed = true;
+                    self.pending.push_back(Event::Closed { reason: None });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Automatic handling of live tool calls.
+use super::{
+    Client, FunctionBehavior, FunctionCall, FunctionDeclaration, FunctionResponse,
+    FunctionResponseScheduling, FunctionResult, ServerMessage, Tool,
+};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::AbortHandle;
+use tokio_stream::{Stream, StreamExt};
+use tracing::{debug, warn};
+
+/// Default time a handler may run before an error response is sent instead.
+const DEFAULT_TOOL_TIMEOUT: Duration = Duration::from_secs(30);
+
+type ToolFuture = Pin<Box<dyn Future<Output = Result<Value, String>> + Send>>;
+type ToolStream = Pin<Box<dyn Stream<Item = Result<Value, String>> + Send>>;
+
+#[derive(Clone)]
+enum Handler {
+    Once(Arc<dyn Fn(Value) -> ToolFuture + Send + Sync>),
+    Streaming(Arc<dyn Fn(Value) -> ToolStream + Send + Sync>),
+}
+
+#[derive(Clone)]
+struct Function {
+    declaration: FunctionDeclaration,
+    handler: Handler,
+}
+
+/// Functions the model may call in a live session, with their handlers.
+///
+/// [`ToolRegistry::dispatch`] runs the handler of every call in its own task
+/// and answers with a [`FunctionResponse`] carrying the call's id. Calls listed
+/// in a `toolCallCancellation` are aborted and never answered. Clones share
+/// the running calls.
+#[derive(Clone)]
+pub struct ToolRegistry {
+    functions: Arc<HashMap<String, Function>>,
+    timeout: Duration,
+    scheduling: FunctionResponseScheduling,
+    running: Arc<Mutex<HashMap<String, AbortHandle>>>,
+}
+
+impl Default for ToolRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self {
+            functions: Arc::default(),
+            timeout: DEFAULT_TOOL_TIMEOUT,
+            scheduling: FunctionResponseScheduling::WhenIdle,
+            running: Arc::default(),
+        }
+    }
+
+    /// Register `handler` for the function described by `declaration`. The
+    /// handler gets the call's arguments; an `Err` is reported to the model as
+    /// `{"error": ...}`.
+    pub fn with_function<F, Fut, E>(self, declaration: FunctionDeclaration, handler: F) -> Self
+    where
+        F: Fn(Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Value, E>> + Send + 'static,
+        E: Display,
+    {
+        let handler = Handler::Once(Arc::new(move |args| {
+            let future = handler(args);
+            Box::pin(async move { future.await.map_err(|e| e.to_string()) })
+        }));
+        self.insert(declaration, handler)
+    }
+
+    /// Register a handler that reports progress for a
+    /// [`FunctionBehavior::NonBlocking`] function.
+    ///
+    /// Every result is sent as it arrives with `willContinue: true`. When the
+    /// stream ends, a last response with `willContinue: false` closes the
+    /// call: `null` after the final result, or the error of an `Err`.
+    /// [`ToolRegistry::with_timeout`] limits the wait for each result.
+    pub fn with_streaming_function<F, S, E>(
+        self,
+        declaration: FunctionDeclaration,
+        handler: F,
+    ) -> Self
+    where
+        F: Fn(Value) -> S + Send + Sync + 'static,
+        S: Stream<Item = Result<Value, E>> + Send + 'static,
+        E: Display,
+    {
+        let handler = Handler::Streaming(Arc::new(move |args| {
+            Box::pin(handler(args).map(|result| result.map_err(|e| e.to_string())))
+        }));
+        self.insert(declaration, handler)
+    }
+
+    fn insert(mut self, declaration: FunctionDeclaration, handler: Handler) -> Self {
+        let function = Function {
+            declaration,
+            handler,
+        };
+        Arc::make_mut(&mut self.functions).insert(function.declaration.name.clone(), function);
+        self
+    }
+
+    /// How long a handler may run. Defaults to 30 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// When the model should use results of [`FunctionBehavior::NonBlocking`]
+    /// functions. Defaults to [`FunctionResponseScheduling::WhenIdle`].
+    pub fn with_scheduling(mut self, scheduling: FunctionResponseScheduling) -> Self {
+        self.scheduling = scheduling;
+        self
+    }
+
+    /// The declarations of all registered functions, for [`super::Setup::with_tools`].
+    pub fn tool(&self) -> Tool {
+        let mut declarations: Vec<_> = self
+            .functions
+            .values()
+            .map(|function| function.declaration.clone())
+            .collect();
+        declarations.sort_by(|a, b| a.name.cmp(&b.name));
+        Tool::FunctionDeclarations(declarations)
+    }
+
+    /// Whether a handler is registered for `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.functions.contains_key(name)
+    }
+
+    /// Start the handlers of a tool call, or abort the calls of a tool call
+    /// cancellation. Other messages and calls of unregistered functions are
+    /// ignored.
+    pub fn dispatch(&self, client: &Client, message: &ServerMessage) {
+        match message {
+            ServerMessage::ToolCall(call) => {
+                for call in &call.function_calls {
+                    self.spawn(client, call);
+                }
+            }
+            ServerMessage::ToolCallCancellation(cancellation) => {
+                self.cancel(&cancellation.ids);
+            }
+            _ => {}
+        }
+    }
+
+    /// Abort the running calls with the given ids.
+    pub fn cancel(&self, ids: &[String]) {
+        let mut running = self.running.lock().unwrap();
+        for id in ids {
+            if let Some(handle) = running.remove(id) {
+                debug!(%id, "tool call cancelled");
+                handle.abort();
+            }
+        }
+    }
+
+    fn spawn(&self, client: &Client, call: &FunctionCall) {
+        let Some(function) = self.functions.get(&call.name) else {
+            return;
+        };
+        let scheduling = matches!(
+            function.declaration.behavior,
+            Some(FunctionBehavior::NonBlocking)
+        )
+        .then(|| self.scheduling.clone());
+        let responder = Responder {
+            client: client.clone(),
+            id: call.id.clone(),
+            name: call.name.clone(),
+            scheduling,
+            running: self.running.clone(),
+        };
+        let timeout = self.timeout;
+
+        // Run the handler before taking the lock, which user code must never hold.
+        let work: Pin<Box<dyn Future<Output = ()> + Send>> = match &function.handler {
+            Handler::Once(handler) => {
+                let future = handler(call.args.clone());
+                Box::pin(async move {
+                    let result = match tokio::time::timeout(timeout, future).await {
+                        Ok(result) => result,
+                        Err(_) => Err(format!("timed out after {timeout:?}")),
+                    };
+                    responder.send(result, None);
+                })
+            }
+            Handler::Streaming(handler) => {
+                let stream = handler(call.args.clone());
+                Box::pin(responder.stream(stream, timeout))
+            }
+        };
+
+        // Hold the lock until the handle is stored so the task cannot finish first.
+        let mut running = self.running.lock().unwrap();
+        let task = tokio::spawn(work);
+        if let Some(id) = &call.id {
+            running.insert(id.clone(), task.abort_handle());
+        }
+    }
+}
+
+/// Sends the responses of one call.
+struct Responder {
+    client: Client,
+    id: Option<String>,
+    name: String,
+    scheduling: Option<FunctionResponseScheduling>,
+    running: Arc<Mutex<HashMap<String, AbortHandle>>>,
+}
+
+impl Responder {
+    /// Send a response unless the call was cancelled. Returns whether it was sent.
+    fn send(&self, result: Result<Value, String>, will_continue: Option<bool>) -> bool {
+        let mut running = self.running.lock().unwrap();
+        if let Some(id) = &self.id {
+            // A cancelled call has already been removed: drop its result.
+            let active = match will_continue {
+                Some(true) => running.contains_key(id),
+                _ => running.remove(id).is_some(),
+            };
+            if !active {
+                return false;
+            }
+        }
+        let result = result.unwrap_or_else(|e| json!({ "error": e }));
+        let mut response = FunctionResponse::new(
+            self.id.clone(),
+            self.name.clone(),
+            FunctionResult::new(result),
+        );
+        response.will_continue = will_continue;
+        response.scheduling = self.scheduling.clone();
+        if let Err(e) = self.client.send_tool_responses(vec![response]) {
+            warn!(error = %e, "failed to send tool response");
+        }
+        true
+    }
+
+    async fn stream(self, mut results: ToolStream, timeout: Duration) {
+        loop {
+            let next = match tokio::time::timeout(timeout, results.next()).await {
+                Ok(next) => next,
+                Err(_) => Some(Err(format!("timed out after {timeout:?}"))),
+            };
+            match next {
+                Some(Ok(value)) => {
+                    if !self.send(Ok(value), Some(true)) {
+                        return;
+                    }
+                }
+                Some(Err(e)) => {
+                    self.send(Err(e), Some(false));
+                    return;
+                }
+                None => {
+                    self.send(Ok(Value::Null), Some(false));
+                    return;
+                }
+            }
+        }
+    }
+}
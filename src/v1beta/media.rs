@@ -0,0 +1,388 @@
+//! Building multimodal [`Part`](super::Part)s from files, bytes and URIs.
+//!
+//! The constructors are available on both the REST [`Part`](super::Part) and the
+//! live [`Part`](super::live::Part). MIME types are detected from the leading
+//! bytes of the payload first and from the file extension second.
+use super::live;
+use base64::{Engine as _, engine::general_purpose};
+use derive_new::new;
+use std::path::Path;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Largest base64 encoded payload accepted inline in a single request, for all
+/// parts together. Bigger files have to be uploaded and referenced by URI
+/// instead.
+pub const MAX_INLINE_DATA_BYTES: usize = 20 * 1024 * 1024;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(
+        "inline data is {size} bytes once encoded, above the {limit} byte request limit; upload it and use a file URI instead"
+    )]
+    TooLarge { size: usize, limit: usize },
+    #[error("could not detect the MIME type of {0}")]
+    UnknownMimeType(String),
+    #[error(transparent)]
+    Base64(#[from] base64::DecodeError),
+    #[error("{0} is not 16-bit PCM audio with a sample rate")]
+    NotPcm(String),
+    #[error("expected {expected} data, found {found}")]
+    UnexpectedMimeType {
+        expected: &'static str,
+        found: String,
+    },
+}
+
+/// Detect the MIME type of `bytes` from their signature.
+pub fn detect_mime_type(bytes: &[u8]) -> Option<&'static str> {
+    let at = |offset: usize, magic: &[u8]| bytes.get(offset..offset + magic.len()) == Some(magic);
+
+    let mime_type = match () {
+        _ if at(0, b"\x89PNG\r\n\x1a\n") => "image/png",
+        _ if at(0, b"\xff\xd8\xff") => "image/jpeg",
+        _ if at(0, b"GIF87a") || at(0, b"GIF89a") => "image/gif",
+        _ if at(0, b"RIFF") && at(8, b"WEBP") => "image/webp",
+        _ if at(0, b"RIFF") && at(8, b"WAVE") => "audio/wav",
+        _ if at(0, b"RIFF") && at(8, b"AVI ") => "video/avi",
+        _ if at(0, b"%PDF-") => "application/pdf",
+        _ if at(0, b"ID3") => "audio/mp3",
+        _ if at(0, b"OggS") => "audio/ogg",
+        _ if at(0, b"fLaC") => "audio/flac",
+        _ if at(0, b"FORM") && (at(8, b"AIFF") || at(8, b"AIFC")) => "audio/aiff",
+        _ if at(0, b"\x1a\x45\xdf\xa3") => "video/webm",
+        _ if at(0, b"FLV") => "video/x-flv",
+        _ if at(0, b"\x30\x26\xb2\x75\x8e\x66\xcf\x11") => "video/wmv",
+        _ if at(0, b"\x00\x00\x01\xba") || at(0, b"\x00\x00\x01\xb3") => "video/mpeg",
+        _ if at(4, b"ftyp") => match bytes.get(8..12)? {
+            b"heic" | b"heix" | b"hevc" => "image/heic",
+            b"mif1" | b"msf1" => "image/heif",
+            b"qt  " => "video/mov",
+            b"3gp4" | b"3gp5" | b"3gp6" => "video/3gpp",
+            b"M4A " => "audio/aac",
+            _ => "video/mp4",
+        },
+        // MPEG audio frame sync without an ID3 tag.
+        _ if bytes.len() > 1 && bytes[0] == 0xff && bytes[1] & 0xe0 == 0xe0 => {
+            if bytes[1] & 0x06 == 0 {
+                "audio/aac"
+            } else {
+                "audio/mp3"
+            }
+        }
+        _ => return None,
+    };
+    Some(mime_type)
+}
+
+/// Guess the MIME type of `path` from its extension.
+pub fn mime_type_from_extension(path: impl AsRef<Path>) -> Option<&'static str> {
+    let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+    let mime_type = match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "heic" => "image/heic",
+        "heif" => "image/heif",
+        "pdf" => "application/pdf",
+        "wav" => "audio/wav",
+        "mp3" => "audio/mp3",
+        "aif" | "aiff" => "audio/aiff",
+        "aac" | "m4a" => "audio/aac",
+        "ogg" | "oga" => "audio/ogg",
+        "flac" => "audio/flac",
+        "mp4" | "m4v" => "video/mp4",
+        "mpeg" | "mpg" => "video/mpeg",
+        "mov" => "video/mov",
+        "avi" => "video/avi",
+        "flv" => "video/x-flv",
+        "webm" => "video/webm",
+        "wmv" => "video/wmv",
+        "3gp" => "video/3gpp",
+        "txt" => "text/plain",
+        "md" => "text/md",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "csv" => "text/csv",
+        "xml" => "text/xml",
+        "rtf" => "text/rtf",
+        "json" => "application/json",
+        "js" => "text/javascript",
+        "py" => "text/x-python",
+        _ => return None,
+    };
+    Some(mime_type)
+}
+
+/// Conventional file extension for `mime_type`, ignoring any parameters.
+pub fn extension_for_mime_type(mime_type: &str) -> Option<&'static str> {
+    let essence = mime_type.split(';').next()?.trim().to_ascii_lowercase();
+    let extension = match essence.as_str() {
+        "image/png" => "png",
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/heic" => "heic",
+        "image/heif" => "heif",
+        "application/pdf" => "pdf",
+        "audio/wav" | "audio/x-wav" => "wav",
+        "audio/mp3" | "audio/mpeg" => "mp3",
+        "audio/aiff" => "aiff",
+        "audio/aac" => "aac",
+        "audio/ogg" => "ogg",
+        "audio/flac" => "flac",
+        "audio/pcm" => "pcm",
+        "video/mp4" => "mp4",
+        "video/mpeg" => "mpeg",
+        "video/mov" | "video/quicktime" => "mov",
+        "video/avi" => "avi",
+        "video/x-flv" => "flv",
+        "video/webm" => "webm",
+        "video/wmv" => "wmv",
+        "video/3gpp" => "3gp",
+        "text/plain" => "txt",
+        "text/md" | "text/markdown" => "md",
+        "text/html" => "html",
+        "text/csv" => "csv",
+        "application/json" => "json",
+        _ => return None,
+    };
+    Some(extension)
+}
+
+/// MIME type of 16-bit PCM at `sample_rate`, e.g. `audio/pcm;rate=16000`.
+pub fn pcm_mime_type(sample_rate: u32) -> String {
+    format!("audio/pcm;rate={sample_rate}")
+}
+
+/// Sample rate of an `audio/pcm;rate=<rate>` MIME type.
+pub fn pcm_sample_rate(mime_type: &str) -> Option<u32> {
+    let mut params = mime_type.split(';');
+    if !params.next()?.trim().eq_ignore_ascii_case("audio/pcm") {
+        return None;
+    }
+    params.find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("rate")
+            .then(|| value.trim().parse().ok())?
+    })
+}
+
+/// 16-bit little endian PCM audio, as produced by the speech models.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct PcmAudio {
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// Interleaved samples when there is more than one channel.
+    pub samples: Vec<i16>,
+}
+
+impl PcmAudio {
+    /// Decode `bytes` of an `audio/pcm;rate=<rate>` payload. The models always
+    /// return mono audio.
+    pub fn from_inline(mime_type: &str, bytes: &[u8]) -> Result<Self, Error> {
+        let sample_rate =
+            pcm_sample_rate(mime_type).ok_or_else(|| Error::NotPcm(mime_type.to_string()))?;
+        Ok(Self::new(sample_rate, 1, bytes_to_i16(bytes)))
+    }
+
+    /// Length of the audio.
+    pub fn duration(&self) -> Duration {
+        let frames = self.samples.len() as u64 / u64::from(self.channels.max(1));
+        Duration::from_secs_f64(frames as f64 / f64::from(self.sample_rate.max(1)))
+    }
+
+    /// Encode as a RIFF WAVE file.
+    pub fn to_wav_bytes(&self) -> Vec<u8> {
+        let data_len = (self.samples.len() * 2) as u32;
+        let block_align = self.channels * 2;
+        let byte_rate = self.sample_rate * u32::from(block_align);
+
+        let mut wav = Vec::with_capacity(44 + data_len as usize);
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes()); // integer PCM
+        wav.extend_from_slice(&self.channels.to_le_bytes());
+        wav.extend_from_slice(&self.sample_rate.to_le_bytes());
+        wav.extend_from_slice(&byte_rate.to_le_bytes());
+        wav.extend_from_slice(&block_align.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        wav.extend_from_slice(&i16_to_bytes(&self.samples));
+        wav
+    }
+
+    /// Write the audio to `path` as a WAV file.
+    pub fn write_wav(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        std::fs::write(path, self.to_wav_bytes())?;
+        Ok(())
+    }
+}
+
+fn read_file(path: &Path) -> Result<(&'static str, Vec<u8>), Error> {
+    let bytes = std::fs::read(path)?;
+    let mime_type = detect_mime_type(&bytes)
+        .or_else(|| mime_type_from_extension(path))
+        .ok_or_else(|| Error::UnknownMimeType(path.display().to_string()))?;
+    Ok((mime_type, bytes))
+}
+
+fn expect_mime_type(bytes: &[u8], expected: &'static str) -> Result<&'static str, Error> {
+    match detect_mime_type(bytes) {
+        Some(found) if found.starts_with(expected) => Ok(found),
+        found => Err(Error::UnexpectedMimeType {
+            expected,
+            found: found.unwrap_or("unknown data").to_string(),
+        }),
+    }
+}
+
+fn check_size(bytes: &[u8]) -> Result<(), Error> {
+    check_encoded_size(base64::encoded_len(bytes.len(), true).unwrap_or(usize::MAX))
+}
+
+/// Check the inline data of all `contents` together against
+/// [`MAX_INLINE_DATA_BYTES`], as the API does for a whole request.
+pub fn check_total_size(contents: &[super::Content]) -> Result<(), Error> {
+    let size = contents
+        .iter()
+        .flat_map(|content| content.inline_data())
+        .map(|data| data.data.len())
+        .sum();
+    check_encoded_size(size)
+}
+
+fn check_encoded_size(size: usize) -> Result<(), Error> {
+    if size > MAX_INLINE_DATA_BYTES {
+        return Err(Error::TooLarge {
+            size,
+            limit: MAX_INLINE_DATA_BYTES,
+        });
+    }
+    Ok(())
+}
+
+/// Little endian bytes of 16-bit samples, as sent in `audio/pcm` payloads.
+pub fn i16_to_bytes(samples: &[i16]) -> Vec<u8> {
+    samples
+        .iter()
+        .flat_map(|sample| sample.to_le_bytes())
+        .collect()
+}
+
+/// 16-bit samples of a little endian `audio/pcm` payload. A trailing odd byte
+/// is ignored.
+pub fn bytes_to_i16(bytes: &[u8]) -> Vec<i16> {
+    bytes
+        .chunks_exact(2)
+        .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+        .collect()
+}
+
+impl super::Part {
+    /// Inline `bytes` of the given MIME type, base64 encoding them.
+    pub fn inline(mime_type: impl Into<String>, bytes: &[u8]) -> Result<Self, Error> {
+        check_size(bytes)?;
+        Ok(Self::new(super::PartData::InlineData(
+            super::InlineData::new(mime_type, general_purpose::STANDARD.encode(bytes)),
+        )))
+    }
+
+    /// Inline the file at `path`, detecting its MIME type.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let (mime_type, bytes) = read_file(path.as_ref())?;
+        Self::inline(mime_type, &bytes)
+    }
+
+    /// Inline an encoded image (PNG, JPEG, WebP, HEIC, ...).
+    pub fn image_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Self::inline(expect_mime_type(bytes, "image/")?, bytes)
+    }
+
+    /// Inline a PDF document.
+    pub fn pdf(bytes: &[u8]) -> Result<Self, Error> {
+        Self::inline(expect_mime_type(bytes, "application/pdf")?, bytes)
+    }
+
+    /// Inline 16-bit mono PCM audio as `audio/pcm;rate=<rate>`.
+    pub fn audio_pcm(rate: u32, samples: &[i16]) -> Result<Self, Error> {
+        Self::inline(pcm_mime_type(rate), &i16_to_bytes(samples))
+    }
+
+    /// Reference a file that was uploaded, or is otherwise reachable by URI.
+    pub fn file_uri(file_uri: impl Into<String>, mime_type: impl Into<String>) -> Self {
+        Self::new(super::PartData::FileData(super::FileData::new(
+            mime_type, file_uri,
+        )))
+    }
+
+    /// Reference a video by URI, clipped to the interval between `start` and `end`.
+    pub fn video_with_offsets(
+        file_uri: impl Into<String>,
+        mime_type: impl Into<String>,
+        start: Duration,
+        end: Duration,
+    ) -> Self {
+        Self::file_uri(file_uri, mime_type)
+            .with_video_metadata(super::VideoMetadata::new(start.into(), end.into()))
+    }
+}
+
+impl live::Part {
+    /// Inline `bytes` of the given MIME type.
+    pub fn inline(mime_type: impl Into<String>, bytes: impl Into<Vec<u8>>) -> Result<Self, Error> {
+        let bytes = bytes.into();
+        check_size(&bytes)?;
+        Ok(Self::new(live::PartData::InlineData(
+            live::InlineData::new(mime_type, bytes),
+        )))
+    }
+
+    /// Inline the file at `path`, detecting its MIME type.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let (mime_type, bytes) = read_file(path.as_ref())?;
+        Self::inline(mime_type, bytes)
+    }
+
+    /// Inline an encoded image (PNG, JPEG, WebP, HEIC, ...).
+    pub fn image_bytes(bytes: impl Into<Vec<u8>>) -> Result<Self, Error> {
+        let bytes = bytes.into();
+        Self::inline(expect_mime_type(&bytes, "image/")?, bytes)
+    }
+
+    /// Inline a PDF document.
+    pub fn pdf(bytes: impl Into<Vec<u8>>) -> Result<Self, Error> {
+        let bytes = bytes.into();
+        Self::inline(expect_mime_type(&bytes, "application/pdf")?, bytes)
+    }
+
+    /// Inline 16-bit mono PCM audio as `audio/pcm;rate=<rate>`.
+    pub fn audio_pcm(rate: u32, samples: &[i16]) -> Result<Self, Error> {
+        Self::inline(pcm_mime_type(rate), i16_to_bytes(samples))
+    }
+
+    /// Reference a file that was uploaded, or is otherwise reachable by URI.
+    pub fn file_uri(file_uri: impl Into<String>, mime_type: impl Into<String>) -> Self {
+        Self::new(live::PartData::FileData(live::FileData::new(
+            mime_type, file_uri,
+        )))
+    }
+
+    /// Reference a video by URI, clipped to the interval between `start` and `end`.
+    pub fn video_with_offsets(
+        file_uri: impl Into<String>,
+        mime_type: impl Into<String>,
+        start: Duration,
+        end: Duration,
+    ) -> Self {
+        Self::file_uri(file_uri, mime_type)
+            .with_video_metadata(live::VideoMetadata::new(start.into(), end.into()))
+    }
+}
@@ -13,6 +13,59 @@ pub struct Content {
     pub parts: Vec<Part>,
 }
 
+impl Content {
+    /// Text of all non-thought parts joined together, or `None` if there is none.
+    pub fn text(&self) -> Option<String> {
+        join_text(self.parts.iter().filter(|part| !part.is_thought()))
+    }
+
+    /// Text of all thought parts joined together, or `None` if there is none.
+    pub fn thoughts(&self) -> Option<String> {
+        join_text(self.parts.iter().filter(|part| part.is_thought()))
+    }
+
+    pub fn function_calls(&self) -> Vec<&FunctionCall> {
+        self.parts
+            .iter()
+            .filter_map(|part| match &part.data {
+                PartData::FunctionCall(call) => Some(call),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn inline_data(&self) -> Vec<&InlineData> {
+        self.parts
+            .iter()
+            .filter_map(|part| match &part.data {
+                PartData::InlineData(data) => Some(data),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn executable_code(&self) -> Vec<&ExecutableCode> {
+        self.parts
+            .iter()
+            .filter_map(|part| match &part.data {
+                PartData::ExecutableCode(code) => Some(code),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+fn join_text<'a>(parts: impl Iterator<Item = &'a Part>) -> Option<String> {
+    parts
+        .filter_map(|part| match &part.data {
+            PartData::Text(text) => Some(text.as_str()),
+            _ => None,
+        })
+        .fold(None, |joined: Option<String>, text| {
+            Some(joined.unwrap_or_default() + text)
+        })
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum PartData {
@@ -22,6 +75,8 @@ pub enum PartData {
     VideoMetadata(VideoMetadata),
     FunctionCall(FunctionCall),
     FunctionResponse(FunctionResponse),
+    ExecutableCode(ExecutableCode),
+    CodeExecutionResult(CodeExecutionResult),
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, new, Setters)]
@@ -31,9 +86,24 @@ pub struct Part {
     #[serde(flatten)]
     #[setters(skip)]
     pub data: PartData,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     #[new(default)]
     pub thought: Option<bool>,
+    /// Opaque signature of the model's reasoning. It has to be sent back
+    /// unchanged with the rest of the turn, e.g. alongside function calls.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[new(default)]
+    pub thought_signature: Option<String>,
+    /// Clipping interval for the video in a [`PartData::FileData`] part.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[new(default)]
+    pub video_metadata: Option<VideoMetadata>,
+}
+
+impl Part {
+    pub fn is_thought(&self) -> bool {
+        self.thought.unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, new)]
@@ -89,6 +159,37 @@ pub struct FunctionResponse {
     pub scheduling: Option<FunctionResponseScheduling>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, new)]
+pub struct ExecutableCode {
+    #[new(into)]
+    pub language: String,
+    #[new(into)]
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Enumeration of possible outcomes of the code execution.
+pub enum CodeExecutionOutcome {
+    /// Code execution completed successfully.
+    #[serde(rename = "OUTCOME_OK")]
+    Ok,
+    /// Code execution finished but with a failure. stderr should contain the reason.
+    #[serde(rename = "OUTCOME_FAILED")]
+    Failed,
+    /// Code execution ran for too long, and was cancelled. There may or may not be a partial output present.
+    #[serde(rename = "OUTCOME_DEADLINE_EXCEEDED")]
+    DeadlineExceeded,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, new, Setters)]
+#[setters(prefix = "with_", strip_option, into)]
+pub struct CodeExecutionResult {
+    pub outcome: CodeExecutionOutcome,
+    #[new(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+}
+
 #[derive(Clone, Deserialize, Serialize, new)]
 #[serde(rename_all = "camelCase")]
 pub struct InlineData {
@@ -98,6 +199,47 @@ pub struct InlineData {
     pub(crate) data: String, // Base64 encoded string
 }
 
+impl InlineData {
+    pub fn mime_type(&self) -> &str {
+        &self.mime_type
+    }
+
+    /// The base64 encoded payload.
+    pub fn data(&self) -> &str {
+        &self.data
+    }
+
+    /// Decode the base64 payload into raw bytes.
+    pub fn decode(&self) -> Result<Vec<u8>, base64::DecodeError> {
+        use base64::Engine as _;
+        base64::engine::general_purpose::STANDARD.decode(&self.data)
+    }
+
+    /// Conventional file extension for the MIME type, if it is a known one.
+    pub fn extension(&self) -> Option<&'static str> {
+        media::extension_for_mime_type(&self.mime_type)
+    }
+
+    /// Decode the payload and write it to `path`, with the extension replaced by
+    /// the one matching the MIME type. Returns the path written.
+    pub fn save(
+        &self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<std::path::PathBuf, media::Error> {
+        let mut path = path.as_ref().to_path_buf();
+        if let Some(extension) = self.extension() {
+            path.set_extension(extension);
+        }
+        std::fs::write(&path, self.decode()?)?;
+        Ok(path)
+    }
+
+    /// Decode an `audio/pcm` payload, such as generated speech, into samples.
+    pub fn to_pcm(&self) -> Result<media::PcmAudio, media::Error> {
+        media::PcmAudio::from_inline(&self.mime_type, &self.decode()?)
+    }
+}
+
 impl std::fmt::Debug for InlineData {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("InlineData")
@@ -115,6 +257,17 @@ pub struct FileData {
     #[new(into)]
     file_uri: String,
 }
+
+impl FileData {
+    pub fn mime_type(&self) -> &str {
+        &self.mime_type
+    }
+
+    pub fn file_uri(&self) -> &str {
+        &self.file_uri
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, new)]
 #[serde(rename_all = "camelCase")]
 pub struct VideoMetadata {
@@ -136,6 +289,24 @@ pub struct EndOffset {
     nanos: i32,
 }
 
+impl From<std::time::Duration> for StartOffset {
+    fn from(duration: std::time::Duration) -> Self {
+        Self {
+            seconds: duration.as_secs() as i64,
+            nanos: duration.subsec_nanos() as i32,
+        }
+    }
+}
+
+impl From<std::time::Duration> for EndOffset {
+    fn from(duration: std::time::Duration) -> Self {
+        Self {
+            seconds: duration.as_secs() as i64,
+            nanos: duration.subsec_nanos() as i32,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Role {
@@ -154,6 +325,7 @@ pub mod safety {
         HarmCategoryHateSpeech,
         HarmCategoryHarassment,
         HarmCategoryDangerousContent,
+        HarmCategoryCivicIntegrity,
     }
     #[derive(Debug, Clone, Deserialize, Serialize)]
     #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -202,6 +374,19 @@ pub mod request {
         #[serde(skip_serializing_if = "Option::is_none")]
         #[new(default)]
         system_instruction: Option<SystemInstructionContent>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[new(default)]
+        tool_config: Option<ToolConfig>,
+    }
+
+    impl Request {
+        pub fn contents(&self) -> &[super::Content] {
+            &self.contents
+        }
+
+        pub fn generation_config(&self) -> Option<&GenerationConfig> {
+            self.generation_config.as_ref()
+        }
     }
 
     #[derive(Debug, Clone, Deserialize, Serialize, new)]
@@ -212,6 +397,41 @@ pub mod request {
         function_declarations: Vec<FunctionDeclaration>,
     }
 
+    impl Tools {
+        pub fn function_declarations(&self) -> &[FunctionDeclaration] {
+            &self.function_declarations
+        }
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize, new)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ToolConfig {
+        function_calling_config: FunctionCallingConfig,
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize, new, Setters)]
+    #[setters(prefix = "with_", into)]
+    #[serde(rename_all = "camelCase")]
+    pub struct FunctionCallingConfig {
+        #[setters(skip)]
+        mode: FunctionCallingMode,
+        /// Functions the model may call in [`FunctionCallingMode::Any`] mode.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        #[new(default)]
+        allowed_function_names: Vec<String>,
+    }
+
+    #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    pub enum FunctionCallingMode {
+        /// The model decides between text and function calls.
+        Auto,
+        /// The model always calls a function.
+        Any,
+        /// The model never calls a function.
+        None,
+    }
+
     #[derive(Debug, Serialize, Deserialize, Clone, Default)]
     #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
     pub enum FunctionBehavior {
@@ -245,6 +465,20 @@ pub mod request {
         behavior: Option<FunctionBehavior>,
     }
 
+    impl FunctionDeclaration {
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+
+        pub fn description(&self) -> &str {
+            &self.description
+        }
+
+        pub fn parameters(&self) -> Option<&serde_json::Value> {
+            self.parameters.as_ref()
+        }
+    }
+
     #[derive(Debug, Clone, Deserialize, Serialize, new)]
     #[serde(rename_all = "camelCase")]
     pub struct SafetySettings {
@@ -280,6 +514,151 @@ pub mod request {
         #[serde(skip_serializing_if = "Option::is_none")]
         #[new(default)]
         response_schema: Option<serde_json::Value>, // OpenAPI Schema
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[new(default)]
+        response_modalities: Option<Vec<ResponseModality>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[new(default)]
+        image_config: Option<ImageConfig>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[new(default)]
+        speech_config: Option<SpeechConfig>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[new(default)]
+        thinking_config: Option<ThinkingConfig>,
+    }
+
+    impl GenerationConfig {
+        pub fn temperature(&self) -> Option<f32> {
+            self.temperature
+        }
+
+        pub fn top_p(&self) -> Option<f32> {
+            self.top_p
+        }
+
+        pub fn top_k(&self) -> Option<i32> {
+            self.top_k
+        }
+
+        pub fn max_output_tokens(&self) -> Option<i32> {
+            self.max_output_tokens
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    pub enum ResponseModality {
+        Text,
+        Image,
+        Audio,
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize, Setters, new, Default)]
+    #[setters(prefix = "with_")]
+    #[setters(into, strip_option)]
+    #[serde(rename_all = "camelCase")]
+    /// Options for models that generate images.
+    pub struct ImageConfig {
+        /// e.g. `"1:1"`, `"16:9"` or `"9:16"`.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[new(default)]
+        aspect_ratio: Option<String>,
+        /// e.g. `"1K"` or `"2K"`, on models that support it.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[new(default)]
+        image_size: Option<String>,
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize, Setters, new, Default)]
+    #[setters(prefix = "with_", strip_option)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ThinkingConfig {
+        /// Return summaries of the model's reasoning as thought parts.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[new(default)]
+        include_thoughts: Option<bool>,
+        /// Tokens the model may spend thinking; `0` disables thinking and `-1`
+        /// lets the model decide.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[new(default)]
+        thinking_budget: Option<i32>,
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize, Setters, new, Default)]
+    #[setters(prefix = "with_")]
+    #[setters(into, strip_option)]
+    #[serde(rename_all = "camelCase")]
+    /// Voice settings for models that generate speech. Set either a single
+    /// `voice_config` or a `multi_speaker_voice_config`, not both.
+    pub struct SpeechConfig {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[new(default)]
+        voice_config: Option<VoiceConfig>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[new(default)]
+        multi_speaker_voice_config: Option<MultiSpeakerVoiceConfig>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[new(default)]
+        language_code: Option<String>,
+    }
+
+    impl SpeechConfig {
+        /// Speak with a single prebuilt voice, e.g. `"Kore"`.
+        pub fn voice(voice_name: impl Into<String>) -> Self {
+            Self::new().with_voice_config(VoiceConfig::prebuilt(voice_name))
+        }
+
+        /// Give each named speaker of a dialogue its own prebuilt voice. Speaker
+        /// names must match the ones used in the prompt.
+        pub fn multi_speaker<S, V>(speakers: impl IntoIterator<Item = (S, V)>) -> Self
+        where
+            S: Into<String>,
+            V: Into<String>,
+        {
+            let speaker_voice_configs = speakers
+                .into_iter()
+                .map(|(speaker, voice)| {
+                    SpeakerVoiceConfig::new(speaker, VoiceConfig::prebuilt(voice))
+                })
+                .collect();
+            Self::new().with_multi_speaker_voice_config(MultiSpeakerVoiceConfig::new(
+                speaker_voice_configs,
+            ))
+        }
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize, new)]
+    #[serde(rename_all = "camelCase")]
+    pub struct VoiceConfig {
+        prebuilt_voice_config: PrebuiltVoiceConfig,
+    }
+
+    impl VoiceConfig {
+        pub fn prebuilt(voice_name: impl Into<String>) -> Self {
+            Self::new(PrebuiltVoiceConfig::new(voice_name))
+        }
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize, new)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PrebuiltVoiceConfig {
+        #[new(into)]
+        voice_name: String,
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize, new)]
+    #[serde(rename_all = "camelCase")]
+    pub struct MultiSpeakerVoiceConfig {
+        speaker_voice_configs: Vec<SpeakerVoiceConfig>,
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize, new)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SpeakerVoiceConfig {
+        #[new(into)]
+        speaker: String,
+        voice_config: VoiceConfig,
     }
 
     #[derive(Debug, Clone, Deserialize, Serialize, new)]
@@ -290,17 +669,58 @@ pub mod request {
         parts: Vec<SystemInstructionPart>,
     }
 
+    impl SystemInstructionContent {
+        /// Text of all parts joined together.
+        pub fn text(&self) -> String {
+            self.parts.iter().map(|part| part.text.as_str()).collect()
+        }
+    }
+
     #[derive(Debug, Clone, Deserialize, Serialize, new)]
     #[serde(rename_all = "camelCase")]
     pub struct SystemInstructionPart {
         text: String,
     }
+
+    #[derive(Debug, Clone, Deserialize, Serialize, new, Setters)]
+    #[setters(prefix = "with_")]
+    #[setters(into, strip_option)]
+    #[serde(rename_all = "camelCase")]
+    pub struct EmbedContentRequest {
+        #[setters(skip)]
+        content: super::Content,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[new(default)]
+        task_type: Option<TaskType>,
+        /// Title of the document, for [`TaskType::RetrievalDocument`] only.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[new(default)]
+        title: Option<String>,
+        /// Truncate the embedding to this many dimensions.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[new(default)]
+        output_dimensionality: Option<u32>,
+    }
+
+    #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    pub enum TaskType {
+        TaskTypeUnspecified,
+        RetrievalQuery,
+        RetrievalDocument,
+        SemanticSimilarity,
+        Classification,
+        Clustering,
+        QuestionAnswering,
+        FactVerification,
+        CodeRetrievalQuery,
+    }
 }
 
 pub mod response {
-    use serde::Deserialize;
+    use serde::{Deserialize, Serialize};
 
-    #[derive(Debug, Clone, Deserialize, Default)]
+    #[derive(Debug, Clone, Deserialize, Serialize, Default)]
     #[serde(rename_all = "camelCase")]
     pub struct Response {
         #[serde(default)]
@@ -311,7 +731,222 @@ pub mod response {
         pub usage_metadata: Option<UsageMetadata>,
     }
 
-    #[derive(Debug, Clone, Deserialize, Default)]
+    /// The accessors read the first candidate only, which is the only one unless
+    /// `candidate_count` was raised. Use the [`Candidate`] accessors for the others.
+    impl Response {
+        /// Text of the first candidate, without thought parts.
+        pub fn text(&self) -> Option<String> {
+            self.candidates.first()?.text()
+        }
+
+        /// Thought summaries of the first candidate.
+        pub fn thoughts(&self) -> Option<String> {
+            self.candidates.first()?.thoughts()
+        }
+
+        pub fn function_calls(&self) -> Vec<&super::FunctionCall> {
+            self.first_content()
+                .map(super::Content::function_calls)
+                .unwrap_or_default()
+        }
+
+        pub fn inline_data(&self) -> Vec<&super::InlineData> {
+            self.first_content()
+                .map(super::Content::inline_data)
+                .unwrap_or_default()
+        }
+
+        pub fn executable_code(&self) -> Vec<&super::ExecutableCode> {
+            self.first_content()
+                .map(super::Content::executable_code)
+                .unwrap_or_default()
+        }
+
+        /// Text and image parts of the first candidate in the order they were
+        /// generated, without thought parts.
+        pub fn outputs(&self) -> Vec<Output<'_>> {
+            let Some(content) = self.first_content() else {
+                return Vec::new();
+            };
+            content
+                .parts
+                .iter()
+                .filter(|part| !part.is_thought())
+                .filter_map(|part| match &part.data {
+                    super::PartData::Text(text) => Some(Output::Text(text)),
+                    super::PartData::InlineData(data) if data.mime_type().starts_with("image/") => {
+                        Some(Output::Image(data))
+                    }
+                    _ => None,
+                })
+                .collect()
+        }
+
+        /// Generated images of the first candidate.
+        pub fn images(&self) -> Vec<&super::InlineData> {
+            self.outputs()
+                .into_iter()
+                .filter_map(|output| match output {
+                    Output::Image(data) => Some(data),
+                    Output::Text(_) => None,
+                })
+                .collect()
+        }
+
+        /// Write every generated image to `dir` as `<stem>-<n>.<ext>`, returning
+        /// the paths written.
+        pub fn save_images(
+            &self,
+            dir: impl AsRef<std::path::Path>,
+            stem: &str,
+        ) -> Result<Vec<std::path::PathBuf>, super::media::Error> {
+            self.images()
+                .into_iter()
+                .enumerate()
+                .map(|(i, image)| image.save(dir.as_ref().join(format!("{stem}-{i}"))))
+                .collect()
+        }
+
+        /// Generated speech of the first candidate, with all `audio/pcm` parts
+        /// joined in order. `None` if the response holds no audio.
+        pub fn audio(&self) -> Result<Option<super::media::PcmAudio>, super::media::Error> {
+            let mut audio: Option<super::media::PcmAudio> = None;
+            for data in self.inline_data() {
+                if super::media::pcm_sample_rate(data.mime_type()).is_none() {
+                    continue;
+                }
+                let chunk = data.to_pcm()?;
+                match &mut audio {
+                    Some(audio) => audio.samples.extend(chunk.samples),
+                    None => audio = Some(chunk),
+                }
+            }
+            Ok(audio)
+        }
+
+        pub fn finish_reason(&self) -> Option<&FinishReason> {
+            self.candidates.first()?.finish_reason.as_ref()
+        }
+
+        /// Whether the prompt was blocked or the first candidate was stopped for
+        /// safety, recitation or policy reasons.
+        pub fn is_blocked(&self) -> bool {
+            self.blocked().is_some()
+        }
+
+        /// Why the prompt or the first candidate was blocked, or `None` if the
+        /// model answered normally.
+        pub fn blocked(&self) -> Option<Blocked> {
+            if let Some(feedback) = &self.prompt_feedback
+                && (feedback.block_reason.is_some()
+                    || feedback.safety_ratings.iter().any(|r| r.blocked))
+            {
+                return Some(Blocked::Prompt {
+                    reason: feedback.block_reason.clone(),
+                    safety_ratings: feedback.safety_ratings.clone(),
+                });
+            }
+
+            let candidate = self.candidates.first().filter(|c| c.is_blocked())?;
+            Some(Blocked::Candidate {
+                reason: candidate.finish_reason.clone(),
+                safety_ratings: candidate.safety_ratings.clone(),
+                partial: candidate
+                    .content
+                    .clone()
+                    .filter(|content| !content.parts.is_empty()),
+            })
+        }
+
+        pub fn usage(&self) -> Option<&UsageMetadata> {
+            self.usage_metadata.as_ref()
+        }
+
+        fn first_content(&self) -> Option<&super::Content> {
+            self.candidates.first()?.content.as_ref()
+        }
+
+        /// Move the thought parts of the first candidate out of the response, so
+        /// reasoning can be shown apart from the answer.
+        pub fn split_thoughts(mut self) -> Split {
+            let thoughts = match self
+                .candidates
+                .first_mut()
+                .and_then(|candidate| candidate.content.as_mut())
+            {
+                Some(content) => {
+                    let (thoughts, answer) = std::mem::take(&mut content.parts)
+                        .into_iter()
+                        .partition(super::Part::is_thought);
+                    content.parts = answer;
+                    thoughts
+                }
+                None => Vec::new(),
+            };
+            Split {
+                thoughts,
+                answer: self,
+            }
+        }
+    }
+
+    /// A response with its thought parts separated from the answer.
+    #[derive(Debug, Clone, Default)]
+    pub struct Split {
+        pub thoughts: Vec<super::Part>,
+        /// The response without thought parts; usage and finish reason stay here.
+        pub answer: Response,
+    }
+
+    impl Split {
+        /// Thought summary text, if any.
+        pub fn thought_text(&self) -> Option<String> {
+            super::join_text(self.thoughts.iter())
+        }
+    }
+
+    /// A refusal by the model, as opposed to an empty answer.
+    #[derive(Debug, Clone, thiserror::Error)]
+    pub enum Blocked {
+        /// The prompt itself was blocked and no candidates were generated.
+        #[error("prompt blocked: {}", reason.as_ref().map_or("BLOCKED", BlockReason::as_str))]
+        Prompt {
+            reason: Option<BlockReason>,
+            safety_ratings: Vec<SafetyRating>,
+        },
+        /// Generation was stopped, possibly after some content was produced.
+        #[error("response stopped: {}", reason.as_ref().map_or("BLOCKED", FinishReason::as_str))]
+        Candidate {
+            reason: Option<FinishReason>,
+            /// The ratings of the candidate, including the offending ones.
+            safety_ratings: Vec<SafetyRating>,
+            /// Whatever was generated before the stop.
+            partial: Option<super::Content>,
+        },
+    }
+
+    impl Blocked {
+        /// The ratings that caused the block, or all ratings if none is flagged.
+        pub fn offending_ratings(&self) -> Vec<&SafetyRating> {
+            let (Blocked::Prompt { safety_ratings, .. }
+            | Blocked::Candidate { safety_ratings, .. }) = self;
+            let blocked: Vec<_> = safety_ratings.iter().filter(|r| r.blocked).collect();
+            if blocked.is_empty() {
+                safety_ratings.iter().collect()
+            } else {
+                blocked
+            }
+        }
+    }
+
+    /// A text or image part of a generated response.
+    #[derive(Debug, Clone, Copy)]
+    pub enum Output<'a> {
+        Text(&'a str),
+        Image(&'a super::InlineData),
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize, Default)]
     #[serde(rename_all = "camelCase")]
     pub struct Candidate {
         #[serde(default)]
@@ -323,21 +958,105 @@ pub mod response {
         #[serde(default)]
         pub safety_ratings: Vec<SafetyRating>,
     }
-    #[derive(Debug, Clone, Deserialize, Default)]
+
+    impl Candidate {
+        pub fn text(&self) -> Option<String> {
+            self.content.as_ref()?.text()
+        }
+
+        pub fn thoughts(&self) -> Option<String> {
+            self.content.as_ref()?.thoughts()
+        }
+
+        pub fn is_blocked(&self) -> bool {
+            self.finish_reason
+                .as_ref()
+                .is_some_and(FinishReason::is_blocked)
+                || self.safety_ratings.iter().any(|rating| rating.blocked)
+        }
+    }
+    #[derive(Debug, Clone, Deserialize, Serialize, Default)]
     #[serde(rename_all = "camelCase")]
     pub struct UsageMetadata {
         pub prompt_token_count: Option<u32>,
         pub candidates_token_count: Option<u32>,
         pub total_token_count: Option<u32>,
+        #[serde(default)]
+        pub cached_content_token_count: Option<u32>,
+        #[serde(default)]
+        pub thoughts_token_count: Option<u32>,
+        #[serde(default)]
+        pub tool_use_prompt_token_count: Option<u32>,
+        #[serde(default)]
+        pub prompt_tokens_details: Vec<ModalityTokenCount>,
+        #[serde(default)]
+        pub cache_tokens_details: Vec<ModalityTokenCount>,
+        #[serde(default)]
+        pub candidates_tokens_details: Vec<ModalityTokenCount>,
+        #[serde(default)]
+        pub tool_use_prompt_tokens_details: Vec<ModalityTokenCount>,
     }
-    #[derive(Debug, Clone, Deserialize, Default)]
+
+    #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    pub enum Modality {
+        ModalityUnspecified,
+        Text,
+        Image,
+        Video,
+        Audio,
+        Document,
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ModalityTokenCount {
+        pub modality: Modality,
+        pub token_count: u32,
+    }
+    #[derive(Debug, Clone, Deserialize, Serialize, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CountTokensResponse {
+        #[serde(default)]
+        pub total_tokens: u32,
+        #[serde(default)]
+        pub cached_content_token_count: Option<u32>,
+    }
+    #[derive(Debug, Clone, Deserialize, Serialize, Default)]
     #[serde(rename_all = "camelCase")]
     pub struct PromptFeedback {
+        #[serde(default)]
+        pub block_reason: Option<BlockReason>,
         #[serde(default)]
         pub safety_ratings: Vec<SafetyRating>,
     }
 
-    #[derive(Debug, Clone, Deserialize)]
+    #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    pub enum BlockReason {
+        BlockReasonUnspecified,
+        Safety,
+        Other,
+        Blocklist,
+        ProhibitedContent,
+        ImageSafety,
+    }
+
+    impl BlockReason {
+        /// The reason as sent by the API, e.g. `"SAFETY"`.
+        pub fn as_str(&self) -> &'static str {
+            match self {
+                BlockReason::BlockReasonUnspecified => "BLOCK_REASON_UNSPECIFIED",
+                BlockReason::Safety => "SAFETY",
+                BlockReason::Other => "OTHER",
+                BlockReason::Blocklist => "BLOCKLIST",
+                BlockReason::ProhibitedContent => "PROHIBITED_CONTENT",
+                BlockReason::ImageSafety => "IMAGE_SAFETY",
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize)]
     #[serde(rename_all = "camelCase")]
     pub struct SafetyRating {
         pub category: super::safety::HarmCategory,
@@ -346,7 +1065,7 @@ pub mod response {
         pub blocked: bool,
     }
 
-    #[derive(Debug, Clone, Deserialize, PartialEq)]
+    #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
     #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
     pub enum FinishReason {
         FinishReasonUnspecified,
@@ -354,9 +1073,175 @@ pub mod response {
         MaxTokens,
         Safety,
         Recitation,
+        Language,
         Other,
+        Blocklist,
+        ProhibitedContent,
+        Spii,
+        MalformedFunctionCall,
+        ImageSafety,
+        ImageProhibitedContent,
+        ImageRecitation,
+        ImageOther,
+        NoImage,
+        UnexpectedToolCall,
+        TooManyToolCalls,
+    }
+
+    impl FinishReason {
+        /// The reason as sent by the API, e.g. `"STOP"`.
+        pub fn as_str(&self) -> &'static str {
+            match self {
+                FinishReason::FinishReasonUnspecified => "FINISH_REASON_UNSPECIFIED",
+                FinishReason::Stop => "STOP",
+                FinishReason::MaxTokens => "MAX_TOKENS",
+                FinishReason::Safety => "SAFETY",
+                FinishReason::Recitation => "RECITATION",
+                FinishReason::Language => "LANGUAGE",
+                FinishReason::Other => "OTHER",
+                FinishReason::Blocklist => "BLOCKLIST",
+                FinishReason::ProhibitedContent => "PROHIBITED_CONTENT",
+                FinishReason::Spii => "SPII",
+                FinishReason::MalformedFunctionCall => "MALFORMED_FUNCTION_CALL",
+                FinishReason::ImageSafety => "IMAGE_SAFETY",
+                FinishReason::ImageProhibitedContent => "IMAGE_PROHIBITED_CONTENT",
+                FinishReason::ImageRecitation => "IMAGE_RECITATION",
+                FinishReason::ImageOther => "IMAGE_OTHER",
+                FinishReason::NoImage => "NO_IMAGE",
+                FinishReason::UnexpectedToolCall => "UNEXPECTED_TOOL_CALL",
+                FinishReason::TooManyToolCalls => "TOO_MANY_TOOL_CALLS",
+            }
+        }
+
+        /// Whether generation was stopped by a safety, recitation or content
+        /// policy filter rather than finishing or failing on its own.
+        pub fn is_blocked(&self) -> bool {
+            matches!(
+                self,
+                FinishReason::Safety
+                    | FinishReason::Recitation
+                    | FinishReason::Blocklist
+                    | FinishReason::ProhibitedContent
+                    | FinishReason::Spii
+                    | FinishReason::ImageSafety
+                    | FinishReason::ImageProhibitedContent
+                    | FinishReason::ImageRecitation
+            )
+        }
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct EmbedContentResponse {
+        pub embedding: ContentEmbedding,
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ContentEmbedding {
+        #[serde(default)]
+        pub values: Vec<f32>,
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Model {
+        /// Resource name, e.g. `models/gemini-2.5-flash`.
+        pub name: String,
+        #[serde(default)]
+        pub base_model_id: Option<String>,
+        #[serde(default)]
+        pub version: Option<String>,
+        #[serde(default)]
+        pub display_name: Option<String>,
+        #[serde(default)]
+        pub description: Option<String>,
+        #[serde(default)]
+        pub input_token_limit: Option<u32>,
+        #[serde(default)]
+        pub output_token_limit: Option<u32>,
+        #[serde(default)]
+        pub supported_generation_methods: Vec<String>,
+        #[serde(default)]
+        pub thinking: Option<bool>,
+        #[serde(default)]
+        pub temperature: Option<f32>,
+        #[serde(default)]
+        pub max_temperature: Option<f32>,
+        #[serde(default)]
+        pub top_p: Option<f32>,
+        #[serde(default)]
+        pub top_k: Option<u32>,
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ListModelsResponse {
+        #[serde(default)]
+        pub models: Vec<Model>,
+        #[serde(default)]
+        pub next_page_token: Option<String>,
+    }
+
+    /// A file uploaded through the Files API, referenced from prompts by `uri`.
+    #[derive(Debug, Clone, Deserialize, Serialize, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct File {
+        /// Resource name, e.g. `files/abc-123`.
+        pub name: String,
+        #[serde(default)]
+        pub display_name: Option<String>,
+        #[serde(default)]
+        pub mime_type: Option<String>,
+        /// Size in bytes; the API encodes it as a string.
+        #[serde(default)]
+        pub size_bytes: Option<String>,
+        #[serde(default)]
+        pub create_time: Option<String>,
+        #[serde(default)]
+        pub update_time: Option<String>,
+        #[serde(default)]
+        pub expiration_time: Option<String>,
+        #[serde(default)]
+        pub sha256_hash: Option<String>,
+        #[serde(default)]
+        pub uri: Option<String>,
+        #[serde(default)]
+        pub state: Option<FileState>,
+    }
+
+    #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    pub enum FileState {
+        StateUnspecified,
+        Processing,
+        Active,
+        Failed,
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ListFilesResponse {
+        #[serde(default)]
+        pub files: Vec<File>,
+        #[serde(default)]
+        pub next_page_token: Option<String>,
     }
 }
 
+#[cfg(feature = "audio")]
+pub mod audio;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "rest")]
+pub mod chat;
+#[cfg(feature = "dotprompt")]
+pub mod dotprompt;
 pub mod live;
+pub mod media;
+pub mod openai;
+#[cfg(feature = "rest")]
 pub mod rest;
+pub mod telemetry;
+pub mod twilio;
+pub mod usage;
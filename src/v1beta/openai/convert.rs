@@ -0,0 +1,390 @@
+//! Conversion between Gemini contents and OpenAI chat messages.
+//!
+//! Gemini often leaves [`FunctionCall::id`] unset and pairs function responses
+//! with calls by name and order, while OpenAI answers every `tool_calls[].id`
+//! with a `tool` message carrying the same `tool_call_id`. Calls without an id
+//! therefore get a synthetic one starting with [`SYNTHETIC_ID_PREFIX`], which
+//! is dropped again when converting back, so Gemini sees the conversation as it
+//! produced it. Thought signatures travel in
+//! `tool_calls[].extra_content.google.thought_signature`, like on Google's own
+//! OpenAI-compatible endpoint.
+use super::{
+    ContentPart, ExtraContent, FunctionDefinition, ImageUrl, Message, MessageContent, Tool,
+    ToolCall, ToolCallFunction,
+};
+use crate::v1beta::{
+    Content, FileData, FunctionCall, FunctionResponse, FunctionResult, InlineData, Part, PartData,
+    Role, media,
+    request::{FunctionDeclaration, SystemInstructionContent, SystemInstructionPart},
+};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use thiserror::Error;
+
+/// Prefix of the tool call ids made up for function calls without an id.
+pub const SYNTHETIC_ID_PREFIX: &str = "gemini_call_";
+
+/// Schema keywords OpenAI clients send that Gemini rejects.
+const UNSUPPORTED_SCHEMA_KEYS: [&str; 3] = ["$schema", "additionalProperties", "strict"];
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("arguments of tool call {id} are not a JSON object: {source}")]
+    InvalidArguments {
+        id: String,
+        source: serde_json::Error,
+    },
+    #[error("no tool call with id {0}")]
+    UnknownToolCall(String),
+    #[error("no call to {0} precedes its function response")]
+    UnmatchedFunctionResponse(String),
+    #[error("image data URLs must be base64 encoded")]
+    InvalidImageUrl,
+    #[error("{0} parts have no OpenAI equivalent")]
+    Unsupported(&'static str),
+}
+
+/// A conversation in Gemini form.
+#[derive(Debug, Clone, Default)]
+pub struct Conversation {
+    /// The `system` and `developer` messages, joined by blank lines.
+    pub system_instruction: Option<SystemInstructionContent>,
+    pub contents: Vec<Content>,
+}
+
+/// Convert OpenAI chat messages to Gemini contents. Consecutive `tool`
+/// messages become one user content, as Gemini expects all responses to a turn
+/// of calls together.
+pub fn to_contents(messages: impl IntoIterator<Item = Message>) -> Result<Conversation, Error> {
+    let mut system = Vec::new();
+    let mut contents: Vec<Content> = Vec::new();
+    let mut call_names = HashMap::new();
+
+    for message in messages {
+        match message {
+            Message::System { content } | Message::Developer { content } => {
+                system.push(content.text());
+            }
+            Message::User { content } => {
+                contents.push(Content::new(Role::User, user_parts(content)?));
+            }
+            Message::Assistant {
+                content,
+                tool_calls,
+            } => {
+                let mut parts: Vec<Part> = content
+                    .map(|content| content.text())
+                    .filter(|text| !text.is_empty())
+                    .map(|text| Part::new(PartData::Text(text)))
+                    .into_iter()
+                    .collect();
+                for call in tool_calls {
+                    call_names.insert(call.id.clone(), call.function.name.clone());
+                    parts.push(function_call(call)?);
+                }
+                if !parts.is_empty() {
+                    contents.push(Content::new(Role::Model, parts));
+                }
+            }
+            Message::Tool {
+                content,
+                tool_call_id,
+            } => {
+                let name = call_names
+                    .get(&tool_call_id)
+                    .cloned()
+                    .ok_or_else(|| Error::UnknownToolCall(tool_call_id.clone()))?;
+                let text = content.text();
+                let result = serde_json::from_str(&text).unwrap_or(Value::String(text));
+                let part = Part::new(PartData::FunctionResponse(FunctionResponse::new(
+                    gemini_id(tool_call_id),
+                    name,
+                    FunctionResult::new(result),
+                )));
+                match contents.last_mut() {
+                    Some(last) if is_function_responses(last) => last.parts.push(part),
+                    _ => contents.push(Content::new(Role::User, vec![part])),
+                }
+            }
+        }
+    }
+
+    let system_instruction = (!system.is_empty()).then(|| {
+        SystemInstructionContent::new(vec![SystemInstructionPart::new(system.join("\n\n"))])
+    });
+    Ok(Conversation {
+        system_instruction,
+        contents,
+    })
+}
+
+/// Convert Gemini contents to OpenAI chat messages. Thought parts are left out;
+/// images are only carried in user messages.
+pub fn to_messages(
+    system_instruction: Option<&SystemInstructionContent>,
+    contents: &[Content],
+) -> Result<Vec<Message>, Error> {
+    let mut messages = Vec::new();
+    if let Some(system) = system_instruction {
+        messages.push(Message::System {
+            content: MessageContent::Text(system.text()),
+        });
+    }
+
+    // Ids of the calls still waiting for a response, by function name in call order.
+    let mut pending: HashMap<String, VecDeque<String>> = HashMap::new();
+    for (index, content) in contents.iter().enumerate() {
+        match content.role {
+            Role::Model => {
+                if let Some(part) = content.parts.iter().find(|part| {
+                    !matches!(
+                        part.data,
+                        PartData::Text(_) | PartData::FunctionCall(_) | PartData::VideoMetadata(_)
+                    )
+                }) {
+                    return Err(Error::Unsupported(kind(&part.data)));
+                }
+                let tool_calls = tool_calls(content, |part| {
+                    format!("{SYNTHETIC_ID_PREFIX}{index}_{part}")
+                });
+                pending.clear();
+                for call in &tool_calls {
+                    pending
+                        .entry(call.function.name.clone())
+                        .or_default()
+                        .push_back(call.id.clone());
+                }
+                let content = content.text().map(MessageContent::Text);
+                if content.is_some() || !tool_calls.is_empty() {
+                    messages.push(Message::Assistant {
+                        content,
+                        tool_calls,
+                    });
+                }
+            }
+            Role::User => {
+                let mut parts = Vec::new();
+                for part in &content.parts {
+                    match &part.data {
+                        PartData::FunctionResponse(response) => {
+                            let tool_call_id = match &response.id {
+                                Some(id) => {
+                                    for ids in pending.values_mut() {
+                                        ids.retain(|pending| pending != id);
+                                    }
+                                    id.clone()
+                                }
+                                None => pending
+                                    .get_mut(&response.name)
+                                    .and_then(VecDeque::pop_front)
+                                    .ok_or_else(|| {
+                                        Error::UnmatchedFunctionResponse(response.name.clone())
+                                    })?,
+                            };
+                            messages.push(Message::Tool {
+                                content: MessageContent::Text(result_text(
+                                    &response.response.result,
+                                )),
+                                tool_call_id,
+                            });
+                        }
+                        PartData::Text(_) if part.is_thought() => {}
+                        PartData::Text(text) => {
+                            parts.push(ContentPart::Text { text: text.clone() })
+                        }
+                        PartData::InlineData(data) if data.mime_type.starts_with("image/") => {
+                            parts.push(image_url(format!(
+                                "data:{};base64,{}",
+                                data.mime_type, data.data
+                            )));
+                        }
+                        PartData::FileData(data) if data.mime_type().starts_with("image/") => {
+                            parts.push(image_url(data.file_uri().to_string()));
+                        }
+                        PartData::VideoMetadata(_) => {}
+                        other => return Err(Error::Unsupported(kind(other))),
+                    }
+                }
+                match parts.as_slice() {
+                    [] => {}
+                    [ContentPart::Text { text }] => messages.push(Message::User {
+                        content: MessageContent::Text(text.clone()),
+                    }),
+                    _ => messages.push(Message::User {
+                        content: MessageContent::Parts(parts),
+                    }),
+                }
+            }
+        }
+    }
+    Ok(messages)
+}
+
+/// The function calls of `content` as tool calls. Calls without an id get
+/// `synthetic_id` of their part index.
+pub(crate) fn tool_calls(
+    content: &Content,
+    synthetic_id: impl Fn(usize) -> String,
+) -> Vec<ToolCall> {
+    content
+        .parts
+        .iter()
+        .enumerate()
+        .filter_map(|(index, part)| {
+            let PartData::FunctionCall(call) = &part.data else {
+                return None;
+            };
+            Some(ToolCall {
+                id: call.id.clone().unwrap_or_else(|| synthetic_id(index)),
+                kind: "function".to_string(),
+                function: ToolCallFunction {
+                    name: call.name.clone(),
+                    arguments: call.args.to_string(),
+                },
+                extra_content: part
+                    .thought_signature
+                    .clone()
+                    .map(ExtraContent::thought_signature),
+            })
+        })
+        .collect()
+}
+
+impl From<Tool> for FunctionDeclaration {
+    fn from(Tool::Function { function }: Tool) -> Self {
+        let declaration =
+            FunctionDeclaration::new(function.name, function.description.unwrap_or_default());
+        match function.parameters {
+            Some(parameters) => declaration.with_parameters(sanitize_schema(parameters)),
+            None => declaration,
+        }
+    }
+}
+
+impl From<&FunctionDeclaration> for Tool {
+    fn from(declaration: &FunctionDeclaration) -> Self {
+        let description = declaration.description();
+        Tool::Function {
+            function: FunctionDefinition {
+                name: declaration.name().to_string(),
+                description: (!description.is_empty()).then(|| description.to_string()),
+                parameters: declaration.parameters().cloned(),
+                strict: None,
+            },
+        }
+    }
+}
+
+/// Remove the JSON Schema keywords Gemini does not accept, at any depth.
+pub(crate) fn sanitize_schema(mut schema: Value) -> Value {
+    match &mut schema {
+        Value::Object(object) => {
+            for key in UNSUPPORTED_SCHEMA_KEYS {
+                object.remove(key);
+            }
+            for value in object.values_mut() {
+                *value = sanitize_schema(value.take());
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                *value = sanitize_schema(value.take());
+            }
+        }
+        _ => {}
+    }
+    schema
+}
+
+fn function_call(call: ToolCall) -> Result<Part, Error> {
+    let arguments = match call.function.arguments.trim() {
+        "" => "{}",
+        arguments => arguments,
+    };
+    let args: serde_json::Map<String, Value> =
+        serde_json::from_str(arguments).map_err(|source| Error::InvalidArguments {
+            id: call.id.clone(),
+            source,
+        })?;
+    let part = Part::new(PartData::FunctionCall(FunctionCall::new(
+        gemini_id(call.id),
+        call.function.name,
+        Value::Object(args),
+    )));
+    Ok(
+        match call
+            .extra_content
+            .and_then(|extra| extra.google.thought_signature)
+        {
+            Some(signature) => part.with_thought_signature(signature),
+            None => part,
+        },
+    )
+}
+
+/// The id to send to Gemini; synthetic ids were never Gemini's.
+fn gemini_id(id: String) -> Option<String> {
+    (!id.starts_with(SYNTHETIC_ID_PREFIX)).then_some(id)
+}
+
+fn user_parts(content: MessageContent) -> Result<Vec<Part>, Error> {
+    let parts = match content {
+        MessageContent::Text(text) => return Ok(vec![Part::new(PartData::Text(text))]),
+        MessageContent::Parts(parts) => parts,
+    };
+    parts
+        .into_iter()
+        .map(|part| match part {
+            ContentPart::Text { text } => Ok(Part::new(PartData::Text(text))),
+            ContentPart::ImageUrl { image_url } => image_part(&image_url.url),
+        })
+        .collect()
+}
+
+fn image_part(url: &str) -> Result<Part, Error> {
+    if let Some(data_url) = url.strip_prefix("data:") {
+        let (mime_type, data) = data_url
+            .split_once(";base64,")
+            .ok_or(Error::InvalidImageUrl)?;
+        return Ok(Part::new(PartData::InlineData(InlineData::new(
+            mime_type, data,
+        ))));
+    }
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let mime_type = media::mime_type_from_extension(path).unwrap_or("image/jpeg");
+    Ok(Part::new(PartData::FileData(FileData::new(mime_type, url))))
+}
+
+fn image_url(url: String) -> ContentPart {
+    ContentPart::ImageUrl {
+        image_url: ImageUrl { url, detail: None },
+    }
+}
+
+fn is_function_responses(content: &Content) -> bool {
+    !content.parts.is_empty()
+        && content
+            .parts
+            .iter()
+            .all(|part| matches!(part.data, PartData::FunctionResponse(_)))
+}
+
+fn result_text(result: &Value) -> String {
+    match result {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn kind(data: &PartData) -> &'static str {
+    match data {
+        PartData::Text(_) => "text",
+        PartData::InlineData(_) => "inline data",
+        PartData::FileData(_) => "file data",
+        PartData::VideoMetadata(_) => "video metadata",
+        PartData::FunctionCall(_) => "function call",
+        PartData::FunctionResponse(_) => "function response",
+        PartData::ExecutableCode(_) => "executable code",
+        PartData::CodeExecutionResult(_) => "code execution result",
+    }
+}
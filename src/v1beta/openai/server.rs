@@ -0,0 +1,452 @@
+use super::{
+    ChatCompletion, ChatCompletionChunk, ChatCompletionRequest, Choice, ChunkChoice,
+    CompletionTokensDetails, Delta, Embedding, EmbeddingInput, EmbeddingRequest, EmbeddingResponse,
+    EmbeddingUsage, EmbeddingVector, EncodingFormat, ErrorBody, ErrorResponse, FinishReason,
+    Message, MessageContent, Model, ModelList, PromptTokensDetails, ResponseFormat, Stop,
+    ToolCallDelta, ToolChoice, Usage, convert,
+};
+use crate::v1beta::{
+    Content, Part, PartData, Role,
+    request::{
+        self, EmbedContentRequest, FunctionCallingConfig, FunctionCallingMode, GenerationConfig,
+        ToolConfig, Tools,
+    },
+    response, rest,
+};
+use axum::{
+    Json, Router,
+    extract::{Path, State, rejection::JsonRejection},
+    http::StatusCode,
+    response::{
+        IntoResponse, Response,
+        sse::{Event, Sse},
+    },
+    routing::{get, post},
+};
+use base64::{Engine, engine::general_purpose};
+use std::convert::Infallible;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tokio::sync::mpsc::channel;
+use tokio_stream::{StreamExt, wrappers::ReceiverStream};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("{0}")]
+    InvalidRequest(String),
+    #[error(transparent)]
+    Convert(#[from] convert::Error),
+    #[error(transparent)]
+    Rest(#[from] rest::Error),
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let (status, kind) = match &self {
+            Error::InvalidRequest(_) | Error::Convert(_) => {
+                (StatusCode::BAD_REQUEST, "invalid_request_error")
+            }
+            Error::Rest(_) => (StatusCode::BAD_GATEWAY, "api_error"),
+        };
+        (status, Json(error_response(&self, kind))).into_response()
+    }
+}
+
+fn error_response(error: &Error, kind: &str) -> ErrorResponse {
+    ErrorResponse {
+        error: ErrorBody {
+            message: error.to_string(),
+            kind: kind.to_string(),
+            param: None,
+            code: None,
+        },
+    }
+}
+
+/// Routes serving the OpenAI API on top of `client`. The `model` of each
+/// request replaces the client's; the incoming API key is ignored in favour of
+/// the client's.
+///
+/// ```no_run
+/// # async fn run() -> std::io::Result<()> {
+/// use gemini::v1beta::{openai, rest::Client};
+///
+/// let client = Client::new("GEMINI_API_KEY", "gemini-2.5-flash");
+/// let listener = tokio::net::TcpListener::bind("127.0.0.1:8080").await?;
+/// axum::serve(listener, openai::router(client)).await
+/// # }
+/// ```
+pub fn router(client: rest::Client) -> Router {
+    Router::new()
+        .route("/v1/chat/completions", post(chat_completions))
+        .route("/v1/embeddings", post(embeddings))
+        .route("/v1/models", get(list_models))
+        .route("/v1/models/{id}", get(get_model))
+        .with_state(client)
+}
+
+async fn chat_completions(
+    State(client): State<rest::Client>,
+    body: Result<Json<ChatCompletionRequest>, JsonRejection>,
+) -> Result<Response, Error> {
+    let Json(request) = body.map_err(|e| Error::InvalidRequest(e.body_text()))?;
+    let model = model_id(&request.model).to_string();
+    let client = client.with_model(&model);
+    let stream = request.stream;
+    let include_usage = request
+        .stream_options
+        .as_ref()
+        .is_some_and(|options| options.include_usage);
+    let request = to_request(request)?;
+
+    if !stream {
+        let response = client.generate_content(request).await?;
+        return Ok(Json(completion(response, model)).into_response());
+    }
+
+    let mut chunks = client.stream_content(request).await?;
+    let (tx, rx) = channel(16);
+    tokio::spawn(async move {
+        let mut stream = ChunkStream::new(model, include_usage);
+        while let Some(chunk) = chunks.next().await {
+            let event = match chunk {
+                Ok(chunk) => Event::default().json_data(stream.chunk(&chunk)),
+                Err(e) => Event::default().json_data(error_response(&e.into(), "api_error")),
+            };
+            if tx.send(event).await.is_err() {
+                return;
+            }
+        }
+        if let Some(usage) = stream.usage_chunk() {
+            let _ = tx.send(Event::default().json_data(usage)).await;
+        }
+        let _ = tx.send(Ok(Event::default().data("[DONE]"))).await;
+    });
+
+    let events = ReceiverStream::new(rx)
+        .map(|event| Ok::<_, Infallible>(event.unwrap_or_else(|_| Event::default())));
+    Ok(Sse::new(events).into_response())
+}
+
+async fn embeddings(
+    State(client): State<rest::Client>,
+    body: Result<Json<EmbeddingRequest>, JsonRejection>,
+) -> Result<Json<EmbeddingResponse>, Error> {
+    let Json(request) = body.map_err(|e| Error::InvalidRequest(e.body_text()))?;
+    let model = model_id(&request.model).to_string();
+    let client = client.with_model(&model);
+    let inputs = match request.input {
+        EmbeddingInput::One(input) => vec![input],
+        EmbeddingInput::Many(inputs) => inputs,
+    };
+
+    let mut data = Vec::with_capacity(inputs.len());
+    for (index, input) in inputs.into_iter().enumerate() {
+        let mut embed = EmbedContentRequest::new(text_content(Role::User, input));
+        if let Some(dimensions) = request.dimensions {
+            embed = embed.with_output_dimensionality(dimensions);
+        }
+        let values = client.embed_content(embed).await?.embedding.values;
+        let embedding = match request.encoding_format {
+            Some(EncodingFormat::Base64) => {
+                let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+                EmbeddingVector::Base64(general_purpose::STANDARD.encode(bytes))
+            }
+            _ => EmbeddingVector::Float(values),
+        };
+        data.push(Embedding {
+            object: "embedding".to_string(),
+            index: index as u32,
+            embedding,
+        });
+    }
+
+    Ok(Json(EmbeddingResponse {
+        object: "list".to_string(),
+        data,
+        model,
+        usage: EmbeddingUsage::default(),
+    }))
+}
+
+async fn list_models(State(client): State<rest::Client>) -> Result<Json<ModelList>, Error> {
+    let mut data = Vec::new();
+    let mut page_token = None;
+    loop {
+        let page = client.list_models(page_token.as_deref()).await?;
+        data.extend(page.models.iter().map(model));
+        page_token = page.next_page_token.filter(|token| !token.is_empty());
+        if page_token.is_none() {
+            break;
+        }
+    }
+    Ok(Json(ModelList {
+        object: "list".to_string(),
+        data,
+    }))
+}
+
+async fn get_model(
+    State(client): State<rest::Client>,
+    Path(id): Path<String>,
+) -> Result<Json<Model>, Error> {
+    Ok(Json(model(&client.get_model(&id).await?)))
+}
+
+fn model(model: &response::Model) -> Model {
+    Model {
+        id: model_id(&model.name).to_string(),
+        object: "model".to_string(),
+        created: 0,
+        owned_by: "google".to_string(),
+    }
+}
+
+fn model_id(model: &str) -> &str {
+    model.strip_prefix("models/").unwrap_or(model)
+}
+
+/// Translate a chat completion request into a Gemini request.
+fn to_request(request: ChatCompletionRequest) -> Result<request::Request, Error> {
+    let conversation = convert::to_contents(request.messages)?;
+
+    let mut config = GenerationConfig::new();
+    if let Some(temperature) = request.temperature {
+        config = config.with_temperature(temperature);
+    }
+    if let Some(top_p) = request.top_p {
+        config = config.with_top_p(top_p);
+    }
+    if let Some(max_tokens) = request.max_completion_tokens.or(request.max_tokens) {
+        config = config.with_max_output_tokens(max_tokens);
+    }
+    if let Some(n) = request.n {
+        config = config.with_candidate_count(n);
+    }
+    match request.stop {
+        Some(Stop::One(stop)) => config = config.with_stop_sequences(vec![stop]),
+        Some(Stop::Many(stop)) => config = config.with_stop_sequences(stop),
+        None => {}
+    }
+    match request.response_format {
+        Some(ResponseFormat::JsonObject) => {
+            config = config.with_response_mime_type("application/json");
+        }
+        Some(ResponseFormat::JsonSchema { json_schema }) => {
+            config = config.with_response_mime_type("application/json");
+            if let Some(schema) = json_schema.schema {
+                config = config.with_response_schema(convert::sanitize_schema(schema));
+            }
+        }
+        Some(ResponseFormat::Text) | None => {}
+    }
+
+    let mut gemini = request::Request::new(conversation.contents).with_generation_config(config);
+    if let Some(system_instruction) = conversation.system_instruction {
+        gemini = gemini.with_system_instruction(system_instruction);
+    }
+    if !request.tools.is_empty() {
+        let declarations = request.tools.into_iter().map(Into::into).collect();
+        gemini = gemini.with_tools(vec![Tools::new(declarations)]);
+    }
+    if let Some(choice) = request.tool_choice {
+        gemini = gemini.with_tool_config(tool_config(choice)?);
+    }
+    Ok(gemini)
+}
+
+fn tool_config(choice: ToolChoice) -> Result<ToolConfig, Error> {
+    let config = match choice {
+        ToolChoice::Mode(mode) => FunctionCallingConfig::new(match mode.as_str() {
+            "none" => FunctionCallingMode::None,
+            "auto" => FunctionCallingMode::Auto,
+            "required" => FunctionCallingMode::Any,
+            other => {
+                return Err(Error::InvalidRequest(format!(
+                    "unknown tool_choice {other}"
+                )));
+            }
+        }),
+        ToolChoice::Function(choice) => FunctionCallingConfig::new(FunctionCallingMode::Any)
+            .with_allowed_function_names(vec![choice.function.name]),
+    };
+    Ok(ToolConfig::new(config))
+}
+
+fn completion(response: response::Response, model: String) -> ChatCompletion {
+    let choices = response
+        .candidates
+        .iter()
+        .enumerate()
+        .map(|(index, candidate)| {
+            let content = candidate.content.as_ref();
+            let tool_calls = content
+                .map(|content| convert::tool_calls(content, |_| call_id()))
+                .unwrap_or_default();
+            Choice {
+                index: index as u32,
+                finish_reason: finish_reason(
+                    candidate.finish_reason.as_ref(),
+                    !tool_calls.is_empty(),
+                ),
+                message: Message::Assistant {
+                    content: content.and_then(Content::text).map(MessageContent::Text),
+                    tool_calls,
+                },
+            }
+        })
+        .collect();
+    ChatCompletion {
+        id: completion_id(),
+        object: "chat.completion".to_string(),
+        created: now(),
+        model,
+        choices,
+        usage: response.usage_metadata.as_ref().map(usage),
+    }
+}
+
+fn finish_reason(
+    reason: Option<&response::FinishReason>,
+    tool_calls: bool,
+) -> Option<FinishReason> {
+    match reason? {
+        _ if tool_calls => Some(FinishReason::ToolCalls),
+        response::FinishReason::MaxTokens => Some(FinishReason::Length),
+        reason if reason.is_blocked() => Some(FinishReason::ContentFilter),
+        _ => Some(FinishReason::Stop),
+    }
+}
+
+fn usage(usage: &response::UsageMetadata) -> Usage {
+    let prompt_tokens = usage.prompt_token_count.unwrap_or_default();
+    let reasoning_tokens = usage.thoughts_token_count.unwrap_or_default();
+    let completion_tokens = usage.candidates_token_count.unwrap_or_default() + reasoning_tokens;
+    Usage {
+        prompt_tokens,
+        completion_tokens,
+        total_tokens: usage
+            .total_token_count
+            .unwrap_or(prompt_tokens + completion_tokens),
+        prompt_tokens_details: usage
+            .cached_content_token_count
+            .map(|cached_tokens| PromptTokensDetails { cached_tokens }),
+        completion_tokens_details: usage
+            .thoughts_token_count
+            .map(|reasoning_tokens| CompletionTokensDetails { reasoning_tokens }),
+    }
+}
+
+/// Turns streamed Gemini responses into chat completion chunks of one completion.
+struct ChunkStream {
+    id: String,
+    created: u64,
+    model: String,
+    include_usage: bool,
+    started: bool,
+    tool_calls: u32,
+    usage: Option<Usage>,
+}
+
+impl ChunkStream {
+    fn new(model: String, include_usage: bool) -> Self {
+        Self {
+            id: completion_id(),
+            created: now(),
+            model,
+            include_usage,
+            started: false,
+            tool_calls: 0,
+            usage: None,
+        }
+    }
+
+    fn chunk(&mut self, response: &response::Response) -> ChatCompletionChunk {
+        if let Some(metadata) = &response.usage_metadata {
+            self.usage = Some(usage(metadata));
+        }
+        let choices = response
+            .candidates
+            .iter()
+            .enumerate()
+            .map(|(index, candidate)| {
+                let content = candidate.content.as_ref();
+                let tool_calls: Vec<ToolCallDelta> = content
+                    .map(|content| convert::tool_calls(content, |_| call_id()))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|call| {
+                        self.tool_calls += 1;
+                        ToolCallDelta {
+                            index: self.tool_calls - 1,
+                            id: Some(call.id),
+                            kind: Some(call.kind),
+                            function: call.function,
+                            extra_content: call.extra_content,
+                        }
+                    })
+                    .collect();
+                let delta = Delta {
+                    role: (!self.started).then(|| "assistant".to_string()),
+                    content: content.and_then(Content::text),
+                    tool_calls,
+                };
+                ChunkChoice {
+                    index: index as u32,
+                    delta,
+                    finish_reason: finish_reason(
+                        candidate.finish_reason.as_ref(),
+                        self.tool_calls > 0,
+                    ),
+                }
+            })
+            .collect();
+        self.started = true;
+        ChatCompletionChunk {
+            id: self.id.clone(),
+            object: "chat.completion.chunk".to_string(),
+            created: self.created,
+            model: self.model.clone(),
+            choices,
+            usage: None,
+        }
+    }
+
+    /// The final chunk carrying the usage, if it was asked for.
+    fn usage_chunk(&mut self) -> Option<ChatCompletionChunk> {
+        if !self.include_usage {
+            return None;
+        }
+        Some(ChatCompletionChunk {
+            id: self.id.clone(),
+            object: "chat.completion.chunk".to_string(),
+            created: self.created,
+            model: self.model.clone(),
+            choices: Vec::new(),
+            usage: Some(self.usage.take().unwrap_or_default()),
+        })
+    }
+}
+
+fn text_content(role: Role, text: String) -> Content {
+    Content::new(role, vec![Part::new(PartData::Text(text))])
+}
+
+fn completion_id() -> String {
+    format!("chatcmpl-{}", uuid::Uuid::new_v4().simple())
+}
+
+fn call_id() -> String {
+    format!(
+        "{}{}",
+        convert::SYNTHETIC_ID_PREFIX,
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
@@ -1,9 +1,10 @@
-use super::{API_BASE, request, response};
+use super::{API_BASE, media, request, response, telemetry, usage};
 use derive_new::new;
 use derive_setters::Setters;
 use serde_json;
 use thiserror::Error;
 use tokio_stream::StreamExt;
+use tracing::Instrument;
 
 #[derive(Debug, Error)]
 pub enum Error {
@@ -13,6 +14,26 @@ pub enum Error {
     Reqwest(#[from] reqwest::Error),
     #[error(transparent)]
     Json(#[from] serde_json::Error),
+    /// The prompt or response was blocked, in [strict](Client::with_strict) mode.
+    #[error(transparent)]
+    Blocked(Box<response::Blocked>),
+    /// The request was not sent, e.g. because its inline data is too large.
+    #[error(transparent)]
+    Media(#[from] media::Error),
+}
+
+impl Error {
+    /// Low-cardinality name of the error, used as the `error.type` attribute.
+    fn kind(&self) -> &'static str {
+        match self {
+            Error::ApiError(_) => "api_error",
+            Error::Reqwest(e) if e.is_timeout() => "timeout",
+            Error::Reqwest(_) => "http_error",
+            Error::Json(_) => "json_error",
+            Error::Blocked(_) => "blocked",
+            Error::Media(_) => "invalid_request",
+        }
+    }
 }
 
 #[derive(Debug, Clone, new, Setters)]
@@ -28,57 +49,274 @@ pub struct Client {
     api_base: String,
     #[new(value = "reqwest::Client::new()")]
     client: reqwest::Client,
+    /// Record prompts and completions on the telemetry spans. Defaults to the
+    /// value of [`telemetry::CAPTURE_CONTENT_ENV`].
+    #[new(value = "telemetry::capture_content_from_env()")]
+    capture_content: bool,
+    /// Ledger the usage of every response is recorded in. Share one between
+    /// clients to account for them together.
+    #[new(default)]
+    usage_tracker: usage::UsageTracker,
+    /// Return [`Error::Blocked`] instead of `Ok` when the prompt is blocked or
+    /// the first candidate is stopped by a safety or policy filter.
+    #[new(default)]
+    strict: bool,
 }
 
 impl Client {
+    /// Usage of all responses received so far.
+    pub fn usage(&self) -> usage::UsageSnapshot {
+        self.usage_tracker.snapshot()
+    }
+
+    pub fn usage_tracker(&self) -> &usage::UsageTracker {
+        &self.usage_tracker
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// The same client talking to another model. Usage keeps being recorded in
+    /// the same tracker.
+    pub fn with_model(self, model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            ..self
+        }
+    }
+
     pub async fn generate_content(
         &self,
         request: request::Request,
     ) -> Result<response::Response, Error> {
+        media::check_total_size(request.contents())?;
+        let operation = self.operation(&request);
+        let result = async {
+            let url = self.url("generateContent", "");
+            let response = self.post(&url, &request).await?;
+            Ok(response.json().await?)
+        }
+        .instrument(operation.span().clone())
+        .await;
+
+        let result = result.and_then(|response| {
+            operation.record_response(&response);
+            check_blocked(self.strict, response)
+        });
+        operation.finish(result.as_ref().err().map(Error::kind));
+        result
+    }
+
+    pub async fn stream_content(
+        &self,
+        request: request::Request,
+    ) -> Result<impl tokio_stream::Stream<Item = Result<response::Response, Error>> + use<>, Error>
+    {
+        media::check_total_size(request.contents())?;
+        let operation = self.operation(&request);
+        let url = self.url("streamGenerateContent", "alt=sse&");
+        let response = match self
+            .post(&url, &request)
+            .instrument(operation.span().clone())
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                operation.finish(Some(e.kind()));
+                return Err(e);
+            }
+        };
+
+        let stream = response.bytes_stream().map(|x| {
+            let bytes = x.map_err(Error::from)?;
+            let text = String::from_utf8_lossy(&bytes);
+            text.strip_prefix("data: ")
+                .ok_or_else(|| {
+                    Error::ApiError(format!(
+                        "Invalid SSE data format, missing 'data: ' prefix. Full text: '{}'",
+                        text
+                    ))
+                })
+                .and_then(|s| serde_json::from_str::<response::Response>(s).map_err(Error::from))
+        });
+        let strict = self.strict;
+        let mut streamed = None;
+        let stream = stream.map(move |chunk| {
+            chunk.and_then(|chunk| check_blocked_chunk(strict, &mut streamed, chunk))
+        });
+        Ok(telemetry::Instrumented::new(stream, operation, Error::kind))
+    }
+
+    /// Like [`Client::stream_content`], with the thought parts of every chunk
+    /// separated from the answer.
+    pub async fn stream_content_split(
+        &self,
+        request: request::Request,
+    ) -> Result<impl tokio_stream::Stream<Item = Result<response::Split, Error>> + use<>, Error>
+    {
+        let stream = self.stream_content(request).await?;
+        Ok(stream.map(|chunk| chunk.map(response::Response::split_thoughts)))
+    }
+
+    /// Count the tokens `request` would consume as a prompt, including its
+    /// system instruction and tools.
+    pub async fn count_tokens(
+        &self,
+        request: request::Request,
+    ) -> Result<response::CountTokensResponse, Error> {
+        let url = self.url("countTokens", "");
+        let mut generate_content_request = serde_json::to_value(&request)?;
+        generate_content_request["model"] = self.model_name().into();
+        let body = serde_json::json!({ "generateContentRequest": generate_content_request });
+        let response = self.post(&url, &body).await?;
+
+        Ok(response.json().await?)
+    }
+
+    /// Embed the content of `request` with the client's model.
+    pub async fn embed_content(
+        &self,
+        request: request::EmbedContentRequest,
+    ) -> Result<response::EmbedContentResponse, Error> {
+        let url = self.url("embedContent", "");
+        let mut body = serde_json::to_value(&request)?;
+        body["model"] = self.model_name().into();
+        let response = self.post(&url, &body).await?;
+
+        Ok(response.json().await?)
+    }
+
+    /// One page of the available models.
+    pub async fn list_models(
+        &self,
+        page_token: Option<&str>,
+    ) -> Result<response::ListModelsResponse, Error> {
+        let url = format!("{}?key={}", self.api_base, self.api_key);
+        let response = self.send(paged(self.client.get(url), page_token)).await?;
+
+        Ok(response.json().await?)
+    }
+
+    /// Details of the model `name`, with or without the `models/` prefix.
+    pub async fn get_model(&self, name: &str) -> Result<response::Model, Error> {
+        let name = name.strip_prefix("models/").unwrap_or(name);
+        let url = format!("{}/{name}?key={}", self.api_base, self.api_key);
+        let response = self.send(self.client.get(url)).await?;
+
+        Ok(response.json().await?)
+    }
+
+    /// Upload `bytes` through the Files API. Uploaded files can be referenced
+    /// with [`Part::file_uri`](super::Part::file_uri) once they are active.
+    pub async fn upload_file(
+        &self,
+        bytes: Vec<u8>,
+        mime_type: &str,
+        display_name: Option<&str>,
+    ) -> Result<response::File, Error> {
+        let (host, version) = self.root().rsplit_once('/').unwrap_or(("", self.root()));
         let url = format!(
-            "{api_base}/{model}:generateContent?key={api_key}",
-            api_base = self.api_base,
-            model = self.model,
-            api_key = self.api_key,
+            "{host}/upload/{version}/files?uploadType=multipart&key={}",
+            self.api_key
         );
+        let metadata = serde_json::json!({ "file": { "displayName": display_name } });
+
+        let boundary = format!("gemini-{}", uuid::Uuid::new_v4().simple());
+        let mut body = format!(
+            "--{boundary}\r\nContent-Type: application/json; charset=UTF-8\r\n\r\n{metadata}\r\n--{boundary}\r\nContent-Type: {mime_type}\r\n\r\n"
+        )
+        .into_bytes();
+        body.extend_from_slice(&bytes);
+        body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
 
-        let response = self
+        let request = self
             .client
-            .post(&url)
-            .header(reqwest::header::USER_AGENT, env!("CARGO_CRATE_NAME"))
-            .header(reqwest::header::CONTENT_TYPE, "application/json")
-            .json(&request)
-            .send()
-            .await?;
+            .post(url)
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                format!("multipart/related; boundary={boundary}"),
+            )
+            .body(body);
+        let response: serde_json::Value = self.send(request).await?.json().await?;
 
-        if !response.status().is_success() {
-            let error_body = response
-                .text()
-                .await
-                .unwrap_or_else(|e| format!("Failed to read error body: {}", e));
-            return Err(Error::ApiError(error_body));
-        }
+        Ok(serde_json::from_value(response["file"].clone())?)
+    }
+
+    /// One page of the uploaded files.
+    pub async fn list_files(
+        &self,
+        page_token: Option<&str>,
+    ) -> Result<response::ListFilesResponse, Error> {
+        let url = format!("{}/files?key={}", self.root(), self.api_key);
+        let response = self.send(paged(self.client.get(url), page_token)).await?;
 
         Ok(response.json().await?)
     }
 
-    pub async fn stream_content(
-        &self,
-        request: request::Request,
-    ) -> Result<impl tokio_stream::Stream<Item = Result<response::Response, Error>>, Error> {
-        let url = format!(
-            "{api_base}/{model}:streamGenerateContent?alt=sse&key={api_key}",
+    /// Details of the uploaded file `name`, e.g. `files/abc-123`.
+    pub async fn get_file(&self, name: &str) -> Result<response::File, Error> {
+        let url = format!("{}/{}?key={}", self.root(), file_name(name), self.api_key);
+        let response = self.send(self.client.get(url)).await?;
+
+        Ok(response.json().await?)
+    }
+
+    pub async fn delete_file(&self, name: &str) -> Result<(), Error> {
+        let url = format!("{}/{}?key={}", self.root(), file_name(name), self.api_key);
+        self.send(self.client.delete(url)).await?;
+
+        Ok(())
+    }
+
+    fn operation(&self, request: &request::Request) -> telemetry::Operation {
+        let operation =
+            telemetry::Operation::start("generate_content", &self.model, self.capture_content)
+                .with_usage_tracker(self.usage_tracker.clone());
+        operation.record_request(request);
+        operation
+    }
+
+    fn url(&self, method: &str, query: &str) -> String {
+        format!(
+            "{api_base}/{model}:{method}?{query}key={api_key}",
             api_base = self.api_base,
             model = self.model,
             api_key = self.api_key,
-        );
+        )
+    }
+
+    /// The model as a resource name, e.g. `models/gemini-2.5-flash`.
+    fn model_name(&self) -> String {
+        format!(
+            "models/{}",
+            self.model.strip_prefix("models/").unwrap_or(&self.model)
+        )
+    }
 
-        let response = self
+    /// The API version root, e.g. `https://.../v1beta`, derived from `api_base`.
+    fn root(&self) -> &str {
+        self.api_base
+            .strip_suffix("/models")
+            .unwrap_or(&self.api_base)
+    }
+
+    async fn post(
+        &self,
+        url: &str,
+        body: &impl serde::Serialize,
+    ) -> Result<reqwest::Response, Error> {
+        let request = self
             .client
-            .post(&url)
-            .header(reqwest::header::USER_AGENT, env!("CARGO_CRATE_NAME"))
+            .post(url)
             .header(reqwest::header::CONTENT_TYPE, "application/json")
-            .json(&request)
+            .json(body);
+        self.send(request).await
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response, Error> {
+        let response = request
+            .header(reqwest::header::USER_AGENT, env!("CARGO_CRATE_NAME"))
             .send()
             .await?;
 
@@ -90,17 +328,63 @@ impl Client {
             return Err(Error::ApiError(error_body));
         }
 
-        Ok(response.bytes_stream().map(|x| {
-            let bytes = x.map_err(Error::from)?;
-            let text = String::from_utf8_lossy(&bytes);
-            text.strip_prefix("data: ")
-                .ok_or_else(|| {
-                    Error::ApiError(format!(
-                        "Invalid SSE data format, missing 'data: ' prefix. Full text: '{}'",
-                        text
-                    ))
-                })
-                .and_then(|s| serde_json::from_str::<response::Response>(s).map_err(Error::from))
-        }))
+        Ok(response)
+    }
+}
+
+fn paged(request: reqwest::RequestBuilder, page_token: Option<&str>) -> reqwest::RequestBuilder {
+    match page_token {
+        Some(token) => request.query(&[("pageToken", token)]),
+        None => request,
+    }
+}
+
+fn file_name(name: &str) -> String {
+    if name.starts_with("files/") {
+        name.to_string()
+    } else {
+        format!("files/{name}")
+    }
+}
+
+fn check_blocked(strict: bool, response: response::Response) -> Result<response::Response, Error> {
+    match response.blocked() {
+        Some(blocked) if strict => Err(Error::Blocked(Box::new(blocked))),
+        _ => Ok(response),
+    }
+}
+
+/// [`check_blocked`] for one chunk of a stream. `streamed` collects the
+/// content of the first candidate, so a stopped candidate's partial content
+/// holds everything generated before the stop, not just the last chunk.
+fn check_blocked_chunk(
+    strict: bool,
+    streamed: &mut Option<super::Content>,
+    chunk: response::Response,
+) -> Result<response::Response, Error> {
+    if !strict {
+        return Ok(chunk);
+    }
+    let Some(mut blocked) = chunk.blocked() else {
+        if let Some(content) = chunk
+            .candidates
+            .first()
+            .and_then(|candidate| candidate.content.as_ref())
+        {
+            match streamed {
+                Some(streamed) => streamed.parts.extend(content.parts.iter().cloned()),
+                None => *streamed = Some(content.clone()),
+            }
+        }
+        return Ok(chunk);
+    };
+    if let response::Blocked::Candidate { partial, .. } = &mut blocked
+        && let Some(mut content) = streamed.take()
+    {
+        if let Some(last) = partial.take() {
+            content.parts.extend(last.parts);
+        }
+        *partial = Some(content).filter(|content| !content.parts.is_empty());
     }
+    Err(Error::Blocked(Box::new(blocked)))
 }
@@ -0,0 +1,385 @@
+//! Spans and metrics following the OpenTelemetry GenAI semantic conventions.
+//!
+//! Every REST call runs in a `gen_ai` span carrying `gen_ai.*` attributes, and
+//! every live session in one per connection. Metrics are emitted as `tracing`
+//! events with `histogram.` prefixed fields, which the `MetricsLayer` of
+//! `tracing-opentelemetry` turns into histograms:
+//!
+//! - `gen_ai.client.operation.duration`, in seconds
+//! - `gen_ai.client.token.usage`, split by `gen_ai.token.type`
+//! - `gen_ai.server.time_to_first_token`, in seconds, for streamed responses
+//!
+//! Prompts and completions are only recorded, as `gen_ai.input.messages` and
+//! `gen_ai.output.messages`, when capture is enabled on the client or through
+//! [`CAPTURE_CONTENT_ENV`].
+#![cfg_attr(not(all(feature = "rest", feature = "live")), allow(dead_code))]
+
+use super::{Content, PartData, request, response, usage::UsageTracker};
+use serde_json::{Value, json};
+use std::time::Instant;
+use tracing::{Span, field::Empty};
+
+/// Set to `true` to record prompts and completions on spans by default.
+pub const CAPTURE_CONTENT_ENV: &str = "OTEL_INSTRUMENTATION_GENAI_CAPTURE_MESSAGE_CONTENT";
+
+const SYSTEM: &str = "gemini";
+
+/// Whether [`CAPTURE_CONTENT_ENV`] is set to `true`.
+pub fn capture_content_from_env() -> bool {
+    std::env::var(CAPTURE_CONTENT_ENV).is_ok_and(|value| value.eq_ignore_ascii_case("true"))
+}
+
+/// The span and timings of one GenAI operation.
+#[derive(Debug)]
+pub(crate) struct Operation {
+    span: Span,
+    name: &'static str,
+    model: String,
+    start: Instant,
+    first_chunk: bool,
+    capture_content: bool,
+    usage_tracker: Option<UsageTracker>,
+}
+
+impl Operation {
+    pub(crate) fn start(name: &'static str, model: &str, capture_content: bool) -> Self {
+        let model = model.strip_prefix("models/").unwrap_or(model).to_string();
+        let span = tracing::info_span!(
+            target: "gemini::telemetry",
+            "gen_ai",
+            "otel.name" = format!("{name} {model}"),
+            "otel.kind" = "client",
+            "gen_ai.system" = SYSTEM,
+            "gen_ai.operation.name" = name,
+            "gen_ai.request.model" = model.as_str(),
+            "gen_ai.request.temperature" = Empty,
+            "gen_ai.request.top_p" = Empty,
+            "gen_ai.request.top_k" = Empty,
+            "gen_ai.request.max_tokens" = Empty,
+            "gen_ai.response.finish_reasons" = Empty,
+            "gen_ai.usage.input_tokens" = Empty,
+            "gen_ai.usage.output_tokens" = Empty,
+            "gen_ai.input.messages" = Empty,
+            "gen_ai.output.messages" = Empty,
+            "error.type" = Empty,
+        );
+        Self {
+            span,
+            name,
+            model,
+            start: Instant::now(),
+            first_chunk: false,
+            capture_content,
+            usage_tracker: None,
+        }
+    }
+
+    /// Also record the usage of the response in `tracker`.
+    pub(crate) fn with_usage_tracker(self, tracker: UsageTracker) -> Self {
+        Self {
+            usage_tracker: Some(tracker),
+            ..self
+        }
+    }
+
+    pub(crate) fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// Whether prompts and completions are recorded.
+    pub(crate) fn captures_content(&self) -> bool {
+        self.capture_content
+    }
+
+    pub(crate) fn record_request(&self, request: &request::Request) {
+        if let Some(config) = request.generation_config() {
+            if let Some(temperature) = config.temperature() {
+                self.span
+                    .record("gen_ai.request.temperature", f64::from(temperature));
+            }
+            if let Some(top_p) = config.top_p() {
+                self.span.record("gen_ai.request.top_p", f64::from(top_p));
+            }
+            if let Some(top_k) = config.top_k() {
+                self.span.record("gen_ai.request.top_k", i64::from(top_k));
+            }
+            if let Some(max_tokens) = config.max_output_tokens() {
+                self.span
+                    .record("gen_ai.request.max_tokens", i64::from(max_tokens));
+            }
+        }
+        if self.capture_content {
+            self.record_messages("gen_ai.input.messages", request.contents().iter());
+        }
+    }
+
+    pub(crate) fn record_response(&self, response: &response::Response) {
+        let finish_reasons: Vec<_> = response
+            .candidates
+            .iter()
+            .filter_map(|candidate| candidate.finish_reason.as_ref())
+            .map(|reason| reason.as_str().to_ascii_lowercase())
+            .collect();
+        if !finish_reasons.is_empty() {
+            self.span.record(
+                "gen_ai.response.finish_reasons",
+                json!(finish_reasons).to_string(),
+            );
+        }
+        if let Some(usage) = &response.usage_metadata {
+            self.record_usage(usage.prompt_token_count, usage.candidates_token_count);
+            if let Some(tracker) = &self.usage_tracker {
+                tracker.record(&self.model, usage);
+            }
+        }
+        if self.capture_content {
+            self.record_messages(
+                "gen_ai.output.messages",
+                response
+                    .candidates
+                    .iter()
+                    .filter_map(|candidate| candidate.content.as_ref()),
+            );
+        }
+    }
+
+    pub(crate) fn record_usage(&self, input_tokens: Option<u32>, output_tokens: Option<u32>) {
+        for (field, token_type, tokens) in [
+            ("gen_ai.usage.input_tokens", "input", input_tokens),
+            ("gen_ai.usage.output_tokens", "output", output_tokens),
+        ] {
+            let Some(tokens) = tokens else { continue };
+            self.span.record(field, u64::from(tokens));
+            tracing::event!(
+                target: "gemini::telemetry",
+                parent: &self.span,
+                tracing::Level::INFO,
+                histogram.gen_ai.client.token.usage = u64::from(tokens),
+                "gen_ai.token.type" = token_type,
+                "gen_ai.system" = SYSTEM,
+                "gen_ai.operation.name" = self.name,
+                "gen_ai.request.model" = self.model.as_str(),
+            );
+        }
+    }
+
+    /// Record the time to the first chunk of a streamed response, once.
+    pub(crate) fn record_chunk(&mut self) {
+        if std::mem::replace(&mut self.first_chunk, true) {
+            return;
+        }
+        tracing::event!(
+            target: "gemini::telemetry",
+            parent: &self.span,
+            tracing::Level::INFO,
+            histogram.gen_ai.server.time_to_first_token = self.start.elapsed().as_secs_f64(),
+            "gen_ai.system" = SYSTEM,
+            "gen_ai.operation.name" = self.name,
+            "gen_ai.request.model" = self.model.as_str(),
+        );
+    }
+
+    /// Record the operation duration, and `error_type` if it failed.
+    pub(crate) fn finish(&self, error_type: Option<&str>) {
+        if let Some(error_type) = error_type {
+            self.span.record("error.type", error_type);
+        }
+        tracing::event!(
+            target: "gemini::telemetry",
+            parent: &self.span,
+            tracing::Level::INFO,
+            histogram.gen_ai.client.operation.duration = self.start.elapsed().as_secs_f64(),
+            "gen_ai.system" = SYSTEM,
+            "gen_ai.operation.name" = self.name,
+            "gen_ai.request.model" = self.model.as_str(),
+            "error.type" = error_type,
+        );
+    }
+
+    /// Record the contents exchanged in a live session, if capture is enabled.
+    #[cfg(feature = "live")]
+    pub(crate) fn record_live_contents(
+        &self,
+        input: &[super::live::Content],
+        output: &[super::live::Content],
+    ) {
+        if !self.capture_content {
+            return;
+        }
+        for (field, contents) in [
+            ("gen_ai.input.messages", input),
+            ("gen_ai.output.messages", output),
+        ] {
+            let messages: Vec<Value> = contents.iter().map(live_message).collect();
+            self.span.record(field, Value::from(messages).to_string());
+        }
+    }
+
+    fn record_messages<'a>(
+        &self,
+        field: &'static str,
+        contents: impl Iterator<Item = &'a Content>,
+    ) {
+        let messages: Vec<Value> = contents.map(message).collect();
+        self.span.record(field, Value::from(messages).to_string());
+    }
+}
+
+/// A content in the GenAI message format. Binary payloads are left out.
+fn message(content: &Content) -> Value {
+    let parts: Vec<Value> = content
+        .parts
+        .iter()
+        .map(|part| match &part.data {
+            PartData::Text(text) if part.is_thought() => {
+                json!({"type": "reasoning", "content": text})
+            }
+            PartData::Text(text) => json!({"type": "text", "content": text}),
+            PartData::FunctionCall(call) => json!({
+                "type": "tool_call",
+                "id": call.id,
+                "name": call.name,
+                "arguments": call.args,
+            }),
+            PartData::FunctionResponse(response) => json!({
+                "type": "tool_call_response",
+                "id": response.id,
+                "response": response.response,
+            }),
+            PartData::InlineData(data) => json!({"type": "blob", "mime_type": data.mime_type()}),
+            PartData::FileData(data) => json!({
+                "type": "uri",
+                "mime_type": data.mime_type(),
+                "uri": data.file_uri(),
+            }),
+            PartData::VideoMetadata(_) => json!({"type": "video_metadata"}),
+            PartData::ExecutableCode(code) => {
+                json!({"type": "executable_code", "content": code.code})
+            }
+            PartData::CodeExecutionResult(result) => {
+                json!({"type": "code_execution_result", "content": result.output})
+            }
+        })
+        .collect();
+    json!({"role": content.role, "parts": parts})
+}
+
+/// A live content in the GenAI message format, with its text and tool calls.
+#[cfg(feature = "live")]
+fn live_message(content: &super::live::Content) -> Value {
+    use super::live::PartData;
+    let parts: Vec<Value> = content
+        .parts
+        .iter()
+        .filter_map(|part| match &part.data {
+            PartData::Text(text) if part.thought => {
+                Some(json!({"type": "reasoning", "content": text}))
+            }
+            PartData::Text(text) => Some(json!({"type": "text", "content": text})),
+            PartData::FunctionCall(call) => Some(json!({
+                "type": "tool_call",
+                "id": call.id,
+                "name": call.name,
+                "arguments": call.args,
+            })),
+            _ => None,
+        })
+        .collect();
+    json!({"role": content.role, "parts": parts})
+}
+
+/// Fold a streamed chunk into the response accumulated so far. Content is only
+/// kept when it will be recorded.
+pub(crate) fn accumulate(
+    total: &mut response::Response,
+    chunk: &response::Response,
+    keep_content: bool,
+) {
+    if chunk.usage_metadata.is_some() {
+        total.usage_metadata = chunk.usage_metadata.clone();
+    }
+    let Some(candidate) = chunk.candidates.first() else {
+        return;
+    };
+    if total.candidates.is_empty() {
+        total.candidates.push(response::Candidate::default());
+    }
+    let target = &mut total.candidates[0];
+    if candidate.finish_reason.is_some() {
+        target.finish_reason = candidate.finish_reason.clone();
+    }
+    if keep_content && let Some(content) = &candidate.content {
+        match &mut target.content {
+            Some(target) => target.parts.extend(content.parts.iter().cloned()),
+            None => target.content = Some(content.clone()),
+        }
+    }
+}
+
+/// A streamed response that records its chunks on an [`Operation`] and finishes
+/// it when the stream ends or is dropped.
+#[cfg(feature = "rest")]
+pub(crate) struct Instrumented<S, E> {
+    inner: std::pin::Pin<Box<S>>,
+    operation: Operation,
+    total: response::Response,
+    error_type: fn(&E) -> &'static str,
+    error: Option<&'static str>,
+    finished: bool,
+}
+
+#[cfg(feature = "rest")]
+impl<S, E> Instrumented<S, E> {
+    pub(crate) fn new(inner: S, operation: Operation, error_type: fn(&E) -> &'static str) -> Self {
+        Self {
+            inner: Box::pin(inner),
+            operation,
+            total: response::Response::default(),
+            error_type,
+            error: None,
+            finished: false,
+        }
+    }
+
+    fn finish(&mut self) {
+        if !std::mem::replace(&mut self.finished, true) {
+            self.operation.record_response(&self.total);
+            self.operation.finish(self.error);
+        }
+    }
+}
+
+#[cfg(feature = "rest")]
+impl<S, E> tokio_stream::Stream for Instrumented<S, E>
+where
+    S: tokio_stream::Stream<Item = Result<response::Response, E>>,
+{
+    type Item = S::Item;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let item = {
+            let _entered = this.operation.span.enter();
+            std::task::ready!(this.inner.as_mut().poll_next(cx))
+        };
+        match &item {
+            Some(Ok(chunk)) => {
+                this.operation.record_chunk();
+                accumulate(&mut this.total, chunk, this.operation.capture_content);
+            }
+            Some(Err(error)) => this.error = Some((this.error_type)(error)),
+            None => this.finish(),
+        }
+        std::task::Poll::Ready(item)
+    }
+}
+
+#[cfg(feature = "rest")]
+impl<S, E> Drop for Instrumented<S, E> {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
@@ -0,0 +1,281 @@
+use super::{InboundMessage, MediaFormat, OutboundMessage};
+use crate::v1beta::audio::{
+    G711, INPUT_SAMPLE_RATE, TELEPHONY_SAMPLE_RATE, TelephonyDecoder, TelephonyEncoder,
+};
+use crate::v1beta::live::{self, ConnectOptions, Event, LiveSession, Setup, ToolRegistry};
+use derive_new::new;
+use derive_setters::Setters;
+use futures_util::{SinkExt, StreamExt};
+use std::collections::VecDeque;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::tungstenite::{self, Message};
+use tracing::{debug, info, warn};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Live(#[from] live::Error),
+    #[error(transparent)]
+    WebSocket(Box<tungstenite::Error>),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Base64(#[from] base64::DecodeError),
+    /// The media stream ended before its `start` message.
+    #[error("media stream closed before it started")]
+    NotStarted,
+    #[error("unsupported media format: {encoding}, {sample_rate} Hz, {channels} channels")]
+    UnsupportedFormat {
+        encoding: String,
+        sample_rate: u32,
+        channels: u16,
+    },
+}
+
+impl From<tungstenite::Error> for Error {
+    fn from(error: tungstenite::Error) -> Self {
+        Error::WebSocket(Box::new(error))
+    }
+}
+
+/// Connects one Twilio Media Stream to a Live session.
+///
+/// The caller's audio is sent to the model as 16 kHz PCM, and the model's
+/// audio is played back to the caller, each chunk followed by a `mark`. When
+/// the model is interrupted while some of its audio has not played yet, a
+/// `clear` drops that audio so the caller can barge in. The call ends when
+/// either side hangs up.
+#[derive(new, Setters)]
+#[setters(prefix = "with_", into, strip_option)]
+pub struct Bridge {
+    #[new(into)]
+    #[setters(skip)]
+    api_key: String,
+    #[setters(skip)]
+    setup: Setup,
+    #[new(value = "ConnectOptions::new()")]
+    options: ConnectOptions,
+    /// Handlers for the model's tool calls.
+    #[new(default)]
+    tools: Option<ToolRegistry>,
+}
+
+impl Bridge {
+    /// Complete the websocket handshake on `stream`, then [`Bridge::run`].
+    pub async fn accept<S>(self, stream: S) -> Result<(), Error>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let socket = tokio_tungstenite::accept_async(stream).await?;
+        self.run(socket).await
+    }
+
+    /// Wait for the stream to start, connect to the Live API and relay audio
+    /// until the call ends.
+    pub async fn run<S>(self, mut socket: WebSocketStream<S>) -> Result<(), Error>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut call = wait_for_start(&mut socket).await?;
+        let mut session =
+            LiveSession::connect_with_options(self.api_key, self.setup, self.options).await?;
+        if let Some(tools) = self.tools {
+            session = session.with_tools(tools);
+        }
+
+        let result = call.relay(&mut socket, &mut session).await;
+        if let Err(e) = session.close(None) {
+            debug!(error = %e, "live session already closed");
+        }
+        result?;
+        // Twilio may already have closed its side.
+        if let Err(e) = socket.close(None).await {
+            debug!(error = %e, "media stream already closed");
+        }
+        Ok(())
+    }
+}
+
+/// Read messages up to `start`.
+async fn wait_for_start<S>(socket: &mut WebSocketStream<S>) -> Result<Call, Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    while let Some(message) = socket.next().await {
+        let Message::Text(text) = message? else {
+            continue;
+        };
+        match serde_json::from_str(text.as_str())? {
+            InboundMessage::Start { start, .. } => {
+                info!(call_sid = %start.call_sid, stream_sid = %start.stream_sid, "media stream started");
+                let law = law_of(&start.media_format)?;
+                return Ok(Call::new(start.stream_sid, law));
+            }
+            InboundMessage::Stop { .. } => break,
+            _ => {}
+        }
+    }
+    Err(Error::NotStarted)
+}
+
+fn law_of(format: &MediaFormat) -> Result<G711, Error> {
+    let law = match format.encoding.as_str() {
+        "audio/x-mulaw" => Some(G711::MuLaw),
+        "audio/x-alaw" => Some(G711::ALaw),
+        _ => None,
+    };
+    match law {
+        Some(law) if format.sample_rate == TELEPHONY_SAMPLE_RATE && format.channels == 1 => Ok(law),
+        _ => Err(Error::UnsupportedFormat {
+            encoding: format.encoding.clone(),
+            sample_rate: format.sample_rate,
+            channels: format.channels,
+        }),
+    }
+}
+
+/// State of one started media stream.
+struct Call {
+    stream_sid: String,
+    law: G711,
+    decoder: TelephonyDecoder,
+    /// Encoder for the model's audio and the sample rate it expects.
+    encoder: Option<(u32, TelephonyEncoder)>,
+    /// Names of the marks sent after audio that has not finished playing.
+    marks: VecDeque<String>,
+    next_mark: u64,
+}
+
+impl Call {
+    fn new(stream_sid: String, law: G711) -> Self {
+        Self {
+            stream_sid,
+            law,
+            decoder: TelephonyDecoder::new(law),
+            encoder: None,
+            marks: VecDeque::new(),
+            next_mark: 0,
+        }
+    }
+
+    /// Relay messages in both directions until one side hangs up.
+    async fn relay<S>(
+        &mut self,
+        socket: &mut WebSocketStream<S>,
+        session: &mut LiveSession,
+    ) -> Result<(), Error>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        loop {
+            tokio::select! {
+                message = socket.next() => {
+                    let text = match message.transpose()? {
+                        Some(Message::Text(text)) => text,
+                        Some(Message::Close(_)) | None => return Ok(()),
+                        Some(_) => continue,
+                    };
+                    match serde_json::from_str(text.as_str())? {
+                        InboundMessage::Stop { .. } => {
+                            info!(stream_sid = %self.stream_sid, "media stream stopped");
+                            return Ok(());
+                        }
+                        message => self.inbound(session, message)?,
+                    }
+                }
+                event = session.next() => match event {
+                    Some(Ok(Event::Closed { reason })) => {
+                        info!(?reason, "live session closed");
+                        return Ok(());
+                    }
+                    Some(Ok(event)) => {
+                        for message in self.outbound(event) {
+                            let text = serde_json::to_string(&message)?;
+                            socket.send(Message::text(text)).await?;
+                        }
+                    }
+                    Some(Err(e)) => warn!(error = %e, "skipping live message"),
+                    None => return Ok(()),
+                },
+            }
+        }
+    }
+
+    /// Handle a message from Twilio.
+    fn inbound(&mut self, session: &LiveSession, message: InboundMessage) -> Result<(), Error> {
+        match message {
+            InboundMessage::Media { media, .. } if media.track != "outbound" => {
+                let samples = self.decoder.process(&media.decode()?);
+                if !samples.is_empty() {
+                    session.send_audio(&samples, INPUT_SAMPLE_RATE)?;
+                }
+            }
+            InboundMessage::Mark { mark, .. } => {
+                // Marks come back in order; earlier ones may have been lost.
+                if let Some(position) = self.marks.iter().position(|name| *name == mark.name) {
+                    self.marks.drain(..=position);
+                }
+            }
+            InboundMessage::Dtmf { dtmf, .. } => debug!(digit = %dtmf.digit, "ignoring dtmf"),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Messages for Twilio in response to a Live event.
+    fn outbound(&mut self, event: Event) -> Vec<OutboundMessage> {
+        match event {
+            Event::AudioChunk {
+                samples,
+                sample_rate,
+            } => {
+                if self
+                    .encoder
+                    .as_ref()
+                    .is_some_and(|(rate, _)| *rate != sample_rate)
+                {
+                    self.encoder = None;
+                }
+                let law = self.law;
+                let (_, encoder) = self
+                    .encoder
+                    .get_or_insert_with(|| (sample_rate, TelephonyEncoder::new(law, sample_rate)));
+                let audio = encoder.process(&samples);
+                self.play(audio)
+            }
+            Event::TurnComplete { .. } => match &mut self.encoder {
+                Some((_, encoder)) => {
+                    let audio = encoder.flush();
+                    self.play(audio)
+                }
+                None => Vec::new(),
+            },
+            Event::Interrupted => {
+                self.encoder = None;
+                if self.marks.is_empty() {
+                    Vec::new()
+                } else {
+                    debug!(queued = self.marks.len(), "clearing interrupted audio");
+                    vec![OutboundMessage::clear(&self.stream_sid)]
+                }
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Media for `audio`, followed by a mark to learn when it has played.
+    fn play(&mut self, audio: Vec<u8>) -> Vec<OutboundMessage> {
+        if audio.is_empty() {
+            return Vec::new();
+        }
+        self.next_mark += 1;
+        let name = self.next_mark.to_string();
+        self.marks.push_back(name.clone());
+        vec![
+            OutboundMessage::media(&self.stream_sid, &audio),
+            OutboundMessage::mark(&self.stream_sid, name),
+        ]
+    }
+}
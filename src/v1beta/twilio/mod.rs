@@ -0,0 +1,167 @@
+//! The Twilio Media Streams websocket protocol.
+//!
+//! Twilio sends a call's audio as JSON messages (`connected`, `start`,
+//! `media`, `mark`, `stop`) carrying base64 encoded 8 kHz mu-law, and plays
+//! back `media` sent the other way. With the `twilio` feature, [`Bridge`]
+//! connects such a stream to a Live session.
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[cfg(feature = "twilio")]
+mod bridge;
+#[cfg(feature = "twilio")]
+pub use bridge::{Bridge, Error};
+
+/// A message from Twilio.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(
+    tag = "event",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum InboundMessage {
+    /// The websocket is open. Always the first message.
+    Connected {
+        #[serde(default)]
+        protocol: String,
+        #[serde(default)]
+        version: String,
+    },
+    /// The stream started; sent once, before any media.
+    Start { stream_sid: String, start: Start },
+    /// A chunk of the caller's audio.
+    Media { stream_sid: String, media: Media },
+    /// Audio sent before the [`OutboundMessage::Mark`] of this name finished
+    /// playing, or was cleared.
+    Mark { stream_sid: String, mark: Mark },
+    /// The caller pressed a key.
+    Dtmf { stream_sid: String, dtmf: Dtmf },
+    /// The stream ended. Always the last message.
+    Stop {
+        stream_sid: String,
+        #[serde(default)]
+        stop: Stop,
+    },
+    /// An event this crate does not know yet.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Start {
+    pub stream_sid: String,
+    #[serde(default)]
+    pub account_sid: String,
+    #[serde(default)]
+    pub call_sid: String,
+    #[serde(default)]
+    pub tracks: Vec<String>,
+    /// `<Parameter>`s given in the TwiML `<Stream>`.
+    #[serde(default)]
+    pub custom_parameters: HashMap<String, String>,
+    pub media_format: MediaFormat,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaFormat {
+    /// `audio/x-mulaw` for phone calls.
+    pub encoding: String,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Media {
+    /// `inbound` for the caller's audio, `outbound` for audio played to them.
+    #[serde(default)]
+    pub track: String,
+    #[serde(default)]
+    pub chunk: String,
+    /// Milliseconds since the start of the stream.
+    #[serde(default)]
+    pub timestamp: String,
+    /// The base64 encoded audio.
+    pub payload: String,
+}
+
+impl Media {
+    /// Decode the base64 payload into raw bytes.
+    pub fn decode(&self) -> Result<Vec<u8>, base64::DecodeError> {
+        general_purpose::STANDARD.decode(&self.payload)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Mark {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Dtmf {
+    #[serde(default)]
+    pub track: String,
+    pub digit: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Stop {
+    #[serde(default)]
+    pub account_sid: String,
+    #[serde(default)]
+    pub call_sid: String,
+}
+
+/// A message to Twilio.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(
+    tag = "event",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum OutboundMessage {
+    /// Audio to play to the caller, queued after earlier media.
+    Media {
+        stream_sid: String,
+        media: OutboundMedia,
+    },
+    /// Ask Twilio to echo a [`InboundMessage::Mark`] once the media sent
+    /// before it has played.
+    Mark { stream_sid: String, mark: Mark },
+    /// Drop all queued media. Pending marks are echoed right away.
+    Clear { stream_sid: String },
+}
+
+impl OutboundMessage {
+    /// Media carrying `audio` in the stream's encoding.
+    pub fn media(stream_sid: impl Into<String>, audio: &[u8]) -> Self {
+        OutboundMessage::Media {
+            stream_sid: stream_sid.into(),
+            media: OutboundMedia {
+                payload: general_purpose::STANDARD.encode(audio),
+            },
+        }
+    }
+
+    pub fn mark(stream_sid: impl Into<String>, name: impl Into<String>) -> Self {
+        OutboundMessage::Mark {
+            stream_sid: stream_sid.into(),
+            mark: Mark { name: name.into() },
+        }
+    }
+
+    pub fn clear(stream_sid: impl Into<String>) -> Self {
+        OutboundMessage::Clear {
+            stream_sid: stream_sid.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OutboundMedia {
+    /// The base64 encoded audio.
+    pub payload: String,
+}
@@ -0,0 +1,299 @@
+//! Token usage and cost accounting.
+//!
+//! A [`UsageTracker`] collects the usage metadata of every response it sees,
+//! per model. Clients and chat sessions each keep one; pass the same tracker to
+//! several of them to attribute their usage to a single feature. Snapshots turn
+//! into money through a [`PriceTable`].
+use super::{live, response, response::Modality};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::AddAssign;
+use std::sync::{Arc, Mutex};
+
+/// Token counts, summed over any number of responses.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Usage {
+    pub responses: u64,
+    /// All prompt tokens, including the cached ones.
+    pub input_tokens: u64,
+    /// Prompt tokens served from the context cache.
+    pub cached_input_tokens: u64,
+    /// Tokens of the generated candidates, without thinking.
+    pub output_tokens: u64,
+    pub thinking_tokens: u64,
+    /// Prompt tokens of tool results, such as search or code execution.
+    pub tool_use_tokens: u64,
+    pub total_tokens: u64,
+    pub input_by_modality: BTreeMap<Modality, u64>,
+    pub cached_by_modality: BTreeMap<Modality, u64>,
+    pub output_by_modality: BTreeMap<Modality, u64>,
+}
+
+impl Usage {
+    pub fn uncached_input_tokens(&self) -> u64 {
+        self.input_tokens.saturating_sub(self.cached_input_tokens)
+    }
+}
+
+impl AddAssign<&Usage> for Usage {
+    fn add_assign(&mut self, other: &Usage) {
+        self.responses += other.responses;
+        self.input_tokens += other.input_tokens;
+        self.cached_input_tokens += other.cached_input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.thinking_tokens += other.thinking_tokens;
+        self.tool_use_tokens += other.tool_use_tokens;
+        self.total_tokens += other.total_tokens;
+        for (totals, counts) in [
+            (&mut self.input_by_modality, &other.input_by_modality),
+            (&mut self.cached_by_modality, &other.cached_by_modality),
+            (&mut self.output_by_modality, &other.output_by_modality),
+        ] {
+            for (modality, tokens) in counts {
+                *totals.entry(*modality).or_default() += tokens;
+            }
+        }
+    }
+}
+
+impl From<&response::UsageMetadata> for Usage {
+    fn from(usage: &response::UsageMetadata) -> Self {
+        let tokens = |count: Option<u32>| u64::from(count.unwrap_or_default());
+        let by_modality = |details: &[response::ModalityTokenCount]| {
+            let mut counts = BTreeMap::new();
+            for detail in details {
+                *counts.entry(detail.modality).or_default() += u64::from(detail.token_count);
+            }
+            counts
+        };
+        Self {
+            responses: 1,
+            input_tokens: tokens(usage.prompt_token_count),
+            cached_input_tokens: tokens(usage.cached_content_token_count),
+            output_tokens: tokens(usage.candidates_token_count),
+            thinking_tokens: tokens(usage.thoughts_token_count),
+            tool_use_tokens: tokens(usage.tool_use_prompt_token_count),
+            total_tokens: tokens(usage.total_token_count),
+            input_by_modality: by_modality(&usage.prompt_tokens_details),
+            cached_by_modality: by_modality(&usage.cache_tokens_details),
+            output_by_modality: by_modality(&usage.candidates_tokens_details),
+        }
+    }
+}
+
+impl From<&live::UsageMetadata> for Usage {
+    fn from(usage: &live::UsageMetadata) -> Self {
+        let tokens = |count: Option<i32>| count.map_or(0, |count| count.max(0) as u64);
+        let by_modality = |details: &[live::ModalityTokenCount]| {
+            let mut counts = BTreeMap::new();
+            for detail in details {
+                *counts.entry(Modality::from(&detail.modality)).or_default() +=
+                    detail.token_count.max(0) as u64;
+            }
+            counts
+        };
+        Self {
+            responses: 1,
+            input_tokens: tokens(usage.prompt_token_count),
+            cached_input_tokens: tokens(usage.cached_content_token_count),
+            output_tokens: tokens(usage.response_token_count),
+            thinking_tokens: tokens(usage.thoughts_token_count),
+            tool_use_tokens: tokens(usage.tool_use_prompt_token_count),
+            total_tokens: tokens(usage.total_token_count),
+            input_by_modality: by_modality(&usage.prompt_tokens_details),
+            cached_by_modality: by_modality(&usage.cache_tokens_details),
+            output_by_modality: by_modality(&usage.response_tokens_details),
+        }
+    }
+}
+
+impl From<&live::Modality> for Modality {
+    fn from(modality: &live::Modality) -> Self {
+        match modality {
+            live::Modality::ModalityUnspecified => Modality::ModalityUnspecified,
+            live::Modality::Text => Modality::Text,
+            live::Modality::Image => Modality::Image,
+            live::Modality::Video => Modality::Video,
+            live::Modality::Audio => Modality::Audio,
+            live::Modality::Document => Modality::Document,
+        }
+    }
+}
+
+/// Prices of a model in currency units per million tokens.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Price {
+    pub input: f64,
+    /// Cached prompt tokens; defaults to the `input` price when zero.
+    pub cached_input: f64,
+    /// Generated tokens, thinking included.
+    pub output: f64,
+    /// Audio prompt tokens, when they are billed differently from `input`.
+    pub audio_input: Option<f64>,
+}
+
+impl Price {
+    pub fn new(input: f64, output: f64) -> Self {
+        Self {
+            input,
+            output,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_cached_input(self, cached_input: f64) -> Self {
+        Self {
+            cached_input,
+            ..self
+        }
+    }
+
+    pub fn with_audio_input(self, audio_input: f64) -> Self {
+        Self {
+            audio_input: Some(audio_input),
+            ..self
+        }
+    }
+
+    /// Cost of `usage`. Tool-use prompt tokens are billed as input.
+    pub fn cost(&self, usage: &Usage) -> f64 {
+        let cached_price = if self.cached_input > 0.0 {
+            self.cached_input
+        } else {
+            self.input
+        };
+        let mut uncached = (usage.uncached_input_tokens() + usage.tool_use_tokens) as f64;
+        let mut cost = usage.cached_input_tokens as f64 * cached_price;
+
+        if let Some(audio_price) = self.audio_input {
+            let audio = usage
+                .input_by_modality
+                .get(&Modality::Audio)
+                .copied()
+                .unwrap_or_default()
+                .saturating_sub(
+                    usage
+                        .cached_by_modality
+                        .get(&Modality::Audio)
+                        .copied()
+                        .unwrap_or_default(),
+                ) as f64;
+            let audio = audio.min(uncached);
+            cost += audio * audio_price;
+            uncached -= audio;
+        }
+
+        cost += uncached * self.input;
+        cost += (usage.output_tokens + usage.thinking_tokens) as f64 * self.output;
+        cost / 1_000_000.0
+    }
+}
+
+/// Per-model prices. Models are looked up by exact name first and by the
+/// longest matching prefix second, so `gemini-2.5-flash` also prices
+/// `gemini-2.5-flash-preview-05-20`.
+#[derive(Debug, Clone, Default)]
+pub struct PriceTable {
+    prices: HashMap<String, Price>,
+}
+
+impl PriceTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_price(mut self, model: impl Into<String>, price: Price) -> Self {
+        self.prices.insert(normalize_model(&model.into()), price);
+        self
+    }
+
+    pub fn price(&self, model: &str) -> Option<&Price> {
+        let model = normalize_model(model);
+        self.prices.get(&model).or_else(|| {
+            self.prices
+                .iter()
+                .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+                .max_by_key(|(prefix, _)| prefix.len())
+                .map(|(_, price)| price)
+        })
+    }
+}
+
+/// Usage per model at a point in time.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageSnapshot {
+    pub by_model: BTreeMap<String, Usage>,
+}
+
+impl UsageSnapshot {
+    /// Usage of all models together.
+    pub fn total(&self) -> Usage {
+        let mut total = Usage::default();
+        for usage in self.by_model.values() {
+            total += usage;
+        }
+        total
+    }
+
+    /// Cost of all models with a price.
+    pub fn cost(&self, prices: &PriceTable) -> f64 {
+        self.cost_by_model(prices).values().flatten().sum()
+    }
+
+    /// Cost per model, `None` for models missing from `prices`.
+    pub fn cost_by_model(&self, prices: &PriceTable) -> BTreeMap<String, Option<f64>> {
+        self.by_model
+            .iter()
+            .map(|(model, usage)| {
+                let cost = prices.price(model).map(|price| price.cost(usage));
+                (model.clone(), cost)
+            })
+            .collect()
+    }
+}
+
+impl AddAssign<&UsageSnapshot> for UsageSnapshot {
+    fn add_assign(&mut self, other: &UsageSnapshot) {
+        for (model, usage) in &other.by_model {
+            *self.by_model.entry(model.clone()).or_default() += usage;
+        }
+    }
+}
+
+/// Shared, thread-safe usage ledger. Clones record into the same ledger.
+#[derive(Debug, Clone, Default)]
+pub struct UsageTracker {
+    ledger: Arc<Mutex<UsageSnapshot>>,
+}
+
+impl UsageTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, model: &str, usage: impl Into<Usage>) {
+        let usage = usage.into();
+        let mut ledger = self.ledger.lock().unwrap_or_else(|e| e.into_inner());
+        *ledger.by_model.entry(normalize_model(model)).or_default() += &usage;
+    }
+
+    /// Add usage recorded elsewhere, e.g. the snapshot of a finished live session.
+    pub fn add(&self, snapshot: &UsageSnapshot) {
+        *self.ledger.lock().unwrap_or_else(|e| e.into_inner()) += snapshot;
+    }
+
+    pub fn snapshot(&self) -> UsageSnapshot {
+        self.ledger
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Return the usage so far and start over.
+    pub fn take(&self) -> UsageSnapshot {
+        std::mem::take(&mut *self.ledger.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+fn normalize_model(model: &str) -> String {
+    model.strip_prefix("models/").unwrap_or(model).to_string()
+}
@@ -0,0 +1,125 @@
+use gemini::v1beta::{
+    Part, live,
+    request::{GenerationConfig, ThinkingConfig},
+    response::{FinishReason, Response},
+};
+use serde_json::json;
+
+fn response() -> Response {
+    serde_json::from_value(json!({
+        "candidates": [
+            {
+                "content": {
+                    "role": "model",
+                    "parts": [
+                        {"text": "planning the answer", "thought": true},
+                        {"text": "Hello, "},
+                        {"functionCall": {"id": "1", "name": "lookup", "args": {"q": "x"}}},
+                        {"text": "world"},
+                        {"inlineData": {"mimeType": "image/png", "data": "AA=="}},
+                        {"executableCode": {"language": "PYTHON", "code": "print(1)"}}
+                    ]
+                },
+                "finishReason": "STOP"
+            },
+            {
+                "content": {"role": "model", "parts": [{"text": "second candidate"}]},
+                "finishReason": "SAFETY"
+            }
+        ],
+        "usageMetadata": {"promptTokenCount": 3, "candidatesTokenCount": 4, "totalTokenCount": 7}
+    }))
+    .unwrap()
+}
+
+#[test]
+fn response_accessors_read_first_candidate() {
+    let response = response();
+
+    assert_eq!(response.text().as_deref(), Some("Hello, world"));
+    assert_eq!(response.thoughts().as_deref(), Some("planning the answer"));
+    assert_eq!(response.function_calls()[0].name, "lookup");
+    assert_eq!(response.inline_data().len(), 1);
+    assert_eq!(response.executable_code()[0].code, "print(1)");
+    assert_eq!(response.finish_reason(), Some(&FinishReason::Stop));
+    assert!(!response.is_blocked());
+    assert_eq!(response.usage().unwrap().total_token_count, Some(7));
+
+    let second = &response.candidates[1];
+    assert_eq!(second.text().as_deref(), Some("second candidate"));
+    assert!(second.is_blocked());
+}
+
+#[test]
+fn empty_response_accessors() {
+    let response: Response = serde_json::from_value(json!({
+        "promptFeedback": {
+            "safetyRatings": [
+                {"category": "HARM_CATEGORY_HARASSMENT", "probability": "HIGH", "blocked": true}
+            ]
+        }
+    }))
+    .unwrap();
+
+    assert_eq!(response.text(), None);
+    assert_eq!(response.thoughts(), None);
+    assert!(response.function_calls().is_empty());
+    assert_eq!(response.finish_reason(), None);
+    assert!(response.is_blocked());
+}
+
+#[test]
+fn live_server_content_accessors() {
+    let msg: live::ServerMessage = serde_json::from_value(json!({
+        "serverContent": {
+            "modelTurn": {
+                "parts": [
+                    {"text": "thinking", "thought": true},
+                    {"text": "Hi"},
+                    {"inlineData": {"mimeType": "audio/pcm;rate=24000", "data": "AAAA"}}
+                ]
+            }
+        }
+    }))
+    .unwrap();
+
+    let live::ServerMessage::ServerContent { server_content, .. } = msg else {
+        panic!("unexpected message");
+    };
+    assert_eq!(server_content.text().as_deref(), Some("Hi"));
+    assert_eq!(server_content.thoughts().as_deref(), Some("thinking"));
+    assert_eq!(server_content.inline_data()[0].data(), &[0, 0, 0]);
+    assert!(server_content.function_calls().is_empty());
+}
+
+#[test]
+fn split_thoughts_keeps_signatures_on_answer_parts() {
+    let split = response().split_thoughts();
+    assert_eq!(split.thought_text().as_deref(), Some("planning the answer"));
+    assert_eq!(split.answer.text().as_deref(), Some("Hello, world"));
+    assert_eq!(split.answer.thoughts(), None);
+    assert!(split.answer.usage().is_some());
+
+    let part: Part = serde_json::from_value(json!({
+        "functionCall": {"name": "lookup", "args": {}},
+        "thoughtSignature": "c2lnbmF0dXJl"
+    }))
+    .unwrap();
+    let json = serde_json::to_value(&part).unwrap();
+    assert_eq!(json["thoughtSignature"], "c2lnbmF0dXJl");
+    assert!(json.get("thought").is_none());
+}
+
+#[test]
+fn thinking_config_serializes() {
+    let config = GenerationConfig::new().with_thinking_config(
+        ThinkingConfig::new()
+            .with_include_thoughts(true)
+            .with_thinking_budget(1024),
+    );
+    let json = serde_json::to_value(&config).unwrap();
+    assert_eq!(
+        json["thinkingConfig"],
+        json!({"includeThoughts": true, "thinkingBudget": 1024})
+    );
+}
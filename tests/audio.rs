@@ -0,0 +1,184 @@
+#![cfg(feature = "audio")]
+
+use gemini::v1beta::audio::{
+    Chunker, G711, Resampler, TelephonyDecoder, TelephonyEncoder, bytes_to_i16, downmix_to_mono,
+    f32_to_i16, i16_to_bytes, i16_to_f32, pcm_mime_type, pcm_sample_rate, resample,
+};
+use gemini::v1beta::live::RealtimeInput;
+use std::f64::consts::PI;
+use std::time::Duration;
+
+fn sine(frequency: f64, rate: u32, seconds: f64, amplitude: f64) -> Vec<f32> {
+    let len = (seconds * f64::from(rate)) as usize;
+    (0..len)
+        .map(|n| (amplitude * (2.0 * PI * frequency * n as f64 / f64::from(rate)).sin()) as f32)
+        .collect()
+}
+
+/// Amplitude of `frequency` in the middle half of `samples`.
+fn amplitude(samples: &[f32], rate: u32, frequency: f64) -> f64 {
+    let middle = &samples[samples.len() / 4..samples.len() * 3 / 4];
+    let (mut re, mut im) = (0.0, 0.0);
+    for (n, &sample) in middle.iter().enumerate() {
+        let phase = 2.0 * PI * frequency * n as f64 / f64::from(rate);
+        re += f64::from(sample) * phase.cos();
+        im += f64::from(sample) * phase.sin();
+    }
+    2.0 * (re * re + im * im).sqrt() / middle.len() as f64
+}
+
+#[test]
+fn sample_and_mime_conversions() {
+    assert_eq!(i16_to_f32(&[0, 16384, -32768]), [0.0, 0.5, -1.0]);
+    assert_eq!(
+        f32_to_i16(&[0.5, -1.0, 1.5, -2.0]),
+        [16384, -32768, 32767, -32768]
+    );
+    let samples = [1, -2, i16::MAX, i16::MIN];
+    assert_eq!(bytes_to_i16(&i16_to_bytes(&samples)), samples);
+    assert_eq!(bytes_to_i16(&[1, 0, 7]), [1]);
+
+    assert_eq!(pcm_mime_type(24_000), "audio/pcm;rate=24000");
+    assert_eq!(pcm_sample_rate("audio/pcm; rate=16000"), Some(16_000));
+    assert_eq!(pcm_sample_rate("audio/wav"), None);
+
+    assert_eq!(downmix_to_mono(&[0.5, -0.5, 1.0, 0.0, 0.3], 2), [0.0, 0.5]);
+    assert_eq!(downmix_to_mono(&[0.1, 0.2], 1), [0.1, 0.2]);
+}
+
+#[test]
+fn downsampling_keeps_the_pass_band_and_rejects_aliases() {
+    for from in [48_000, 44_100] {
+        let tone = sine(1_000.0, from, 1.0, 0.5);
+        let output = resample(&tone, from, 16_000);
+        assert_eq!(
+            output.len() as u64,
+            (tone.len() as u64 * 16_000).div_ceil(u64::from(from))
+        );
+        let level = amplitude(&output, 16_000, 1_000.0);
+        assert!((level - 0.5).abs() < 0.005, "{from} Hz: {level}");
+    }
+
+    // 10 kHz does not fit below the 8 kHz Nyquist frequency and would fold to 6 kHz.
+    let tone = sine(10_000.0, 48_000, 1.0, 0.5);
+    let output = resample(&tone, 48_000, 16_000);
+    assert!(amplitude(&output, 16_000, 6_000.0) < 0.5e-3);
+}
+
+#[test]
+fn upsampling_has_no_images() {
+    let tone = sine(3_000.0, 24_000, 1.0, 0.5);
+    let output = resample(&tone, 24_000, 48_000);
+    assert_eq!(output.len(), 48_000);
+    assert!((amplitude(&output, 48_000, 3_000.0) - 0.5).abs() < 0.005);
+    assert!(amplitude(&output, 48_000, 21_000.0) < 0.5e-3);
+}
+
+#[test]
+fn streaming_matches_one_shot() {
+    let tone = sine(440.0, 44_100, 0.2, 0.8);
+    let expected = resample(&tone, 44_100, 16_000);
+
+    let mut resampler = Resampler::new(44_100, 16_000);
+    let mut output = Vec::new();
+    for chunk in tone.chunks(333) {
+        output.extend(resampler.process(chunk));
+    }
+    output.extend(resampler.flush());
+    assert_eq!(output, expected);
+
+    // The same rate passes audio through.
+    assert_eq!(resample(&tone, 16_000, 16_000), tone);
+}
+
+#[test]
+fn chunks_of_fixed_duration() {
+    let mut chunker = Chunker::new(16_000, Duration::from_millis(20));
+    assert_eq!(chunker.chunk_len(), 320);
+
+    let samples: Vec<i16> = (0..500).collect();
+    let chunks = chunker.push(&samples);
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0], samples[..320]);
+    let chunks = chunker.push(&samples);
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[0][..180], samples[320..]);
+    assert_eq!(chunker.flush().map(|rest| rest.len()), Some(40));
+    assert_eq!(chunker.flush(), None);
+
+    let inputs = chunker.push_input(&[1; 320]);
+    let value = serde_json::to_value(&inputs[0]).unwrap();
+    assert!(matches!(inputs[0], RealtimeInput::Audio(_)));
+    assert_eq!(value["audio"]["mimeType"], "audio/pcm;rate=16000");
+}
+
+/// Reference values from the ITU-T G.711 reference implementation.
+const PCM: [i16; 16] = [
+    -32768, -32767, -10000, -1000, -100, -9, -8, -1, 0, 1, 7, 8, 100, 1000, 10000, 32767,
+];
+const MU_LAW: [u8; 16] = [
+    0x00, 0x00, 0x1C, 0x4E, 0x72, 0x7D, 0x7E, 0x7E, 0xFF, 0xFF, 0xFE, 0xFE, 0xF2, 0xCE, 0x9C, 0x80,
+];
+const A_LAW: [u8; 16] = [
+    0x2A, 0x2A, 0x36, 0x7A, 0x53, 0x55, 0x55, 0x55, 0xD5, 0xD5, 0xD5, 0xD5, 0xD3, 0xFA, 0xB6, 0xAA,
+];
+const CODES: [u8; 10] = [0x00, 0x0F, 0x35, 0x7E, 0x7F, 0x80, 0xAA, 0xD5, 0xFE, 0xFF];
+
+#[test]
+fn g711_matches_reference_vectors() {
+    assert_eq!(G711::MuLaw.encode(&PCM), MU_LAW);
+    assert_eq!(G711::ALaw.encode(&PCM), A_LAW);
+    assert_eq!(
+        G711::MuLaw.decode(&CODES),
+        [-32124, -16764, -3260, -8, 0, 32124, 5372, 716, 8, 0]
+    );
+    assert_eq!(
+        G711::ALaw.decode(&CODES),
+        [-5504, -6784, -8448, -880, -848, 5504, 32256, 8, 880, 848]
+    );
+
+    // Every code decodes to a value that encodes back to it, except the
+    // second mu-law zero.
+    let codes: Vec<u8> = (0..=255).collect();
+    for law in [G711::MuLaw, G711::ALaw] {
+        let round_trip = law.encode(&law.decode(&codes));
+        for (&code, &back) in codes.iter().zip(&round_trip) {
+            if !(law == G711::MuLaw && code == 0x7F) {
+                assert_eq!(back, code, "{law:?} {code:#04x}");
+            }
+        }
+    }
+    assert_eq!(G711::MuLaw.mime_type(), "audio/x-mulaw");
+}
+
+#[test]
+fn telephony_audio_is_transcoded_to_and_from_live_rates() {
+    // A caller's 440 Hz tone at 8 kHz becomes 16 kHz PCM.
+    let call = G711::MuLaw.encode(&f32_to_i16(&sine(440.0, 8_000, 1.0, 0.5)));
+    let mut decoder = TelephonyDecoder::new(G711::MuLaw);
+    let mut pcm = Vec::new();
+    for chunk in call.chunks(160) {
+        pcm.extend(decoder.process(chunk));
+    }
+    pcm.extend(decoder.flush());
+    assert_eq!(pcm.len(), 16_000);
+    let level = amplitude(&i16_to_f32(&pcm), 16_000, 440.0);
+    assert!((level - 0.5).abs() < 0.01, "{level}");
+
+    // The model's 24 kHz reply becomes 8 kHz A-law, without the 5.5 kHz tone
+    // that does not fit below 4 kHz.
+    let reply: Vec<f32> = sine(1_000.0, 24_000, 1.0, 0.4)
+        .iter()
+        .zip(sine(5_500.0, 24_000, 1.0, 0.4))
+        .map(|(a, b)| a + b)
+        .collect();
+    let mut encoder = TelephonyEncoder::new(G711::ALaw, 24_000);
+    let mut line = encoder.process(&f32_to_i16(&reply));
+    line.extend(encoder.flush());
+    assert_eq!(line.len(), 8_000);
+    let decoded = i16_to_f32(&G711::ALaw.decode(&line));
+    let level = amplitude(&decoded, 8_000, 1_000.0);
+    assert!((level - 0.4).abs() < 0.01, "{level}");
+    // 5.5 kHz would fold to 2.5 kHz.
+    assert!(amplitude(&decoded, 8_000, 2_500.0) < 0.005);
+}
@@ -0,0 +1,169 @@
+#![cfg(feature = "blocking")]
+
+use gemini::v1beta::{
+    Content, Part, PartData, Role,
+    blocking::Client,
+    request::{EmbedContentRequest, Request},
+};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener};
+use std::thread::JoinHandle;
+
+/// Answers a single request with `content_type` and `body`, and hands back
+/// the request line.
+fn start_server(
+    content_type: &'static str,
+    body: &'static str,
+) -> (SocketAddr, JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let handle = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = [0u8; 4096];
+        let n = stream.read(&mut buf).unwrap_or(0);
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(response.as_bytes()).unwrap();
+        let request = String::from_utf8_lossy(&buf[..n]).into_owned();
+        request.lines().next().unwrap_or_default().to_string()
+    });
+    (addr, handle)
+}
+
+fn client(addr: SocketAddr) -> Client {
+    Client::new("key", "test").with_api_base(format!("http://{}/v1beta/models", addr))
+}
+
+#[test]
+fn generate_content_blocks_until_response() {
+    let (addr, _) = start_server(
+        "application/json",
+        r#"{
+            "candidates": [{"content": {"role": "model", "parts": [{"text": "hi"}]}}],
+            "usageMetadata": {"promptTokenCount": 3, "candidatesTokenCount": 1, "totalTokenCount": 4}
+        }"#,
+    );
+    let client = client(addr);
+    let response = client.generate_content(Request::new(vec![])).unwrap();
+    assert_eq!(response.text().as_deref(), Some("hi"));
+    let usage = client.usage().total();
+    assert_eq!((usage.input_tokens, usage.output_tokens), (3, 1));
+}
+
+#[test]
+fn stream_content_iterates_chunks() {
+    let (addr, _) = start_server("text/event-stream", "data: {\"candidates\": []}\n\n");
+    let chunks: Vec<_> = client(addr)
+        .stream_content(Request::new(vec![]))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(chunks.len(), 1);
+}
+
+#[test]
+fn count_tokens_returns_total() {
+    let (addr, _) = start_server("application/json", r#"{"totalTokens": 7}"#);
+    let count = client(addr).count_tokens(Request::new(vec![])).unwrap();
+    assert_eq!(count.total_tokens, 7);
+}
+
+#[test]
+fn embed_content_returns_values() {
+    let (addr, request) = start_server("application/json", r#"{"embedding": {"values": [0.5]}}"#);
+    let content = Content::new(Role::User, vec![Part::new(PartData::Text("hi".into()))]);
+    let response = client(addr)
+        .embed_content(EmbedContentRequest::new(content))
+        .unwrap();
+    assert_eq!(response.embedding.values, vec![0.5]);
+    assert!(
+        request
+            .join()
+            .unwrap()
+            .starts_with("POST /v1beta/models/test:embedContent")
+    );
+}
+
+#[test]
+fn models_are_listed_and_fetched() {
+    let (addr, request) = start_server(
+        "application/json",
+        r#"{"models": [{"name": "models/a"}], "nextPageToken": "next"}"#,
+    );
+    let page = client(addr).list_models(Some("page")).unwrap();
+    assert_eq!(page.models[0].name, "models/a");
+    assert_eq!(page.next_page_token.as_deref(), Some("next"));
+    assert!(request.join().unwrap().contains("pageToken=page"));
+
+    let (addr, request) = start_server("application/json", r#"{"name": "models/a"}"#);
+    let model = client(addr).get_model("models/a").unwrap();
+    assert_eq!(model.name, "models/a");
+    assert!(
+        request
+            .join()
+            .unwrap()
+            .starts_with("GET /v1beta/models/a?key=key ")
+    );
+}
+
+#[test]
+fn files_are_uploaded_listed_fetched_and_deleted() {
+    let (addr, request) = start_server("application/json", r#"{"file": {"name": "files/a"}}"#);
+    let file = client(addr)
+        .upload_file(b"hello".to_vec(), "text/plain", None)
+        .unwrap();
+    assert_eq!(file.name, "files/a");
+    assert!(
+        request
+            .join()
+            .unwrap()
+            .starts_with("POST /upload/v1beta/files?")
+    );
+
+    let (addr, request) = start_server("application/json", r#"{"files": [{"name": "files/a"}]}"#);
+    let page = client(addr).list_files(None).unwrap();
+    assert_eq!(page.files[0].name, "files/a");
+    assert!(
+        request
+            .join()
+            .unwrap()
+            .starts_with("GET /v1beta/files?key=key ")
+    );
+
+    let (addr, request) = start_server("application/json", r#"{"name": "files/a"}"#);
+    let file = client(addr).get_file("a").unwrap();
+    assert_eq!(file.name, "files/a");
+    assert!(
+        request
+            .join()
+            .unwrap()
+            .starts_with("GET /v1beta/files/a?key=key ")
+    );
+
+    let (addr, request) = start_server("application/json", "{}");
+    client(addr).delete_file("files/a").unwrap();
+    assert!(
+        request
+            .join()
+            .unwrap()
+            .starts_with("DELETE /v1beta/files/a?key=key ")
+    );
+}
+
+#[tokio::test]
+async fn client_can_be_dropped_in_async_code() {
+    let (addr, _) = start_server(
+        "application/json",
+        r#"{"candidates": [{"content": {"role": "model", "parts": [{"text": "hi"}]}}]}"#,
+    );
+    let client = client(addr);
+    let (response, client) = tokio::task::spawn_blocking(move || {
+        (client.generate_content(Request::new(vec![])), client)
+    })
+    .await
+    .unwrap();
+    assert_eq!(response.unwrap().text().as_deref(), Some("hi"));
+    drop(client);
+}
@@ -0,0 +1,237 @@
+#![cfg(feature = "rest")]
+
+use gemini::v1beta::{
+    Content, FunctionCall, FunctionResponse, FunctionResult, Part, PartData, Role,
+    chat::{Chat, HistoryPolicy},
+    request::{FunctionDeclaration, SystemInstructionContent, SystemInstructionPart, Tools},
+    rest::Client,
+};
+use serde_json::{Value, json};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+type Requests = Arc<Mutex<Vec<(String, Value)>>>;
+
+/// Serves `bodies` in order, one per request, and records each request's path and JSON body.
+async fn start_server(bodies: Vec<Value>) -> (SocketAddr, Requests) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let requests: Requests = Arc::default();
+    let recorded = requests.clone();
+    tokio::spawn(async move {
+        for body in bodies {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = Vec::new();
+            let mut chunk = [0u8; 4096];
+            let header_end = loop {
+                let n = stream.read(&mut chunk).await.unwrap();
+                buf.extend_from_slice(&chunk[..n]);
+                if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break pos + 4;
+                }
+            };
+            let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+            let length = head
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length")
+                        .then(|| value.trim().parse::<usize>().unwrap())
+                })
+                .unwrap_or(0);
+            while buf.len() < header_end + length {
+                let n = stream.read(&mut chunk).await.unwrap();
+                buf.extend_from_slice(&chunk[..n]);
+            }
+            let path = head.split_whitespace().nth(1).unwrap().to_string();
+            let request = serde_json::from_slice(&buf[header_end..]).unwrap_or(Value::Null);
+            recorded.lock().unwrap().push((path, request));
+
+            let body = body.to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+    (addr, requests)
+}
+
+fn reply(text: &str, prompt_tokens: u32, candidates_tokens: u32) -> Value {
+    json!({
+        "candidates": [{"content": {"role": "model", "parts": [{"text": text}]}}],
+        "usageMetadata": {
+            "promptTokenCount": prompt_tokens,
+            "candidatesTokenCount": candidates_tokens,
+            "totalTokenCount": prompt_tokens + candidates_tokens
+        }
+    })
+}
+
+fn user(text: &str) -> Content {
+    Content::new(Role::User, vec![Part::new(PartData::Text(text.into()))])
+}
+
+fn client(addr: SocketAddr) -> Client {
+    Client::new("key", "test").with_api_base(format!("http://{}/v1beta/models", addr))
+}
+
+#[tokio::test]
+async fn last_turns_keeps_function_call_pairs_together() {
+    let (addr, requests) = start_server(vec![reply("sunny", 10, 2), reply("bye", 10, 2)]).await;
+    let history = vec![
+        user("hello"),
+        Content::new(Role::Model, vec![Part::new(PartData::Text("hi".into()))]),
+        user("weather?"),
+        Content::new(
+            Role::Model,
+            vec![Part::new(PartData::FunctionCall(FunctionCall::new(
+                Some("1".to_string()),
+                "weather",
+                json!({}),
+            )))],
+        ),
+    ];
+    let mut chat = Chat::new(client(addr))
+        .with_policy(HistoryPolicy::LastTurns(1))
+        .with_history(history);
+
+    chat.send(Content::new(
+        Role::User,
+        vec![Part::new(PartData::FunctionResponse(
+            FunctionResponse::new(
+                Some("1".to_string()),
+                "weather",
+                FunctionResult::new(json!("sunny")),
+            ),
+        ))],
+    ))
+    .await
+    .unwrap();
+
+    let sent = requests.lock().unwrap()[0].1["contents"].clone();
+    assert_eq!(sent.as_array().unwrap().len(), 3);
+    assert_eq!(sent[0]["parts"][0]["text"], "weather?");
+    assert_eq!(sent[1]["parts"][0]["functionCall"]["name"], "weather");
+    assert_eq!(sent[2]["parts"][0]["functionResponse"]["name"], "weather");
+
+    chat.send(user("thanks")).await.unwrap();
+    let sent = requests.lock().unwrap()[1].1["contents"].clone();
+    assert_eq!(sent.as_array().unwrap().len(), 1);
+    assert_eq!(chat.history().count(), 2);
+}
+
+#[tokio::test]
+async fn token_budget_drops_oldest_turns() {
+    let (addr, requests) = start_server(vec![
+        json!({"totalTokens": 5}),
+        reply("first answer", 5, 5),
+        json!({"totalTokens": 4}),
+        reply("second answer", 14, 5),
+    ])
+    .await;
+    let mut chat = Chat::new(client(addr)).with_policy(HistoryPolicy::TokenBudget(12));
+
+    chat.send(user("first")).await.unwrap();
+    chat.send(user("second")).await.unwrap();
+
+    let requests = requests.lock().unwrap();
+    assert!(requests[0].0.contains(":countTokens"));
+    assert_eq!(
+        requests[0].1["generateContentRequest"]["model"],
+        "models/test"
+    );
+    // The first turn was measured at 10 tokens from usage metadata, so only the
+    // 4 token second turn fits in the budget.
+    let sent = requests[3].1["contents"].as_array().unwrap();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0]["parts"][0]["text"], "second");
+    assert_eq!(chat.history().count(), 2);
+}
+
+#[tokio::test]
+async fn summarize_replaces_older_turns() {
+    let (addr, requests) = start_server(vec![
+        json!({"totalTokens": 50}),
+        json!({"totalTokens": 50}),
+        json!({"totalTokens": 5}),
+        reply("the user said one and two", 110, 8),
+        reply("three it is", 20, 3),
+    ])
+    .await;
+    let history = vec![
+        user("one"),
+        Content::new(Role::Model, vec![Part::new(PartData::Text("1".into()))]),
+        user("two"),
+        Content::new(Role::Model, vec![Part::new(PartData::Text("2".into()))]),
+    ];
+    let mut chat = Chat::new(client(addr))
+        .with_policy(HistoryPolicy::Summarize {
+            max_tokens: 60,
+            keep_last: 1,
+        })
+        .with_system_instruction(SystemInstructionContent::new(vec![
+            SystemInstructionPart::new("Be brief.".into()),
+        ]))
+        .with_tools(vec![Tools::new(vec![FunctionDeclaration::new(
+            "weather",
+            "Current weather",
+        )])])
+        .with_history(history);
+
+    chat.send(user("three")).await.unwrap();
+
+    let requests = requests.lock().unwrap();
+    let summary_request = &requests[3].1;
+    assert_eq!(summary_request["contents"].as_array().unwrap().len(), 5);
+    assert_eq!(
+        summary_request["systemInstruction"]["parts"][0]["text"],
+        "Be brief."
+    );
+    assert_eq!(
+        summary_request["tools"][0]["functionDeclarations"][0]["name"],
+        "weather"
+    );
+    assert_eq!(
+        summary_request["toolConfig"]["functionCallingConfig"]["mode"],
+        "NONE"
+    );
+    // The summary is the model's answer to the summary request, followed by
+    // the next user message.
+    let sent = requests[4].1["contents"].as_array().unwrap();
+    let roles: Vec<_> = sent.iter().map(|content| content["role"].clone()).collect();
+    assert_eq!(roles, ["user", "model", "user"]);
+    assert_eq!(sent[1]["parts"][0]["text"], "the user said one and two");
+    assert_eq!(sent[2]["parts"][0]["text"], "three");
+}
+
+#[tokio::test]
+async fn failed_send_leaves_history_untouched() {
+    let (addr, _) = start_server(vec![]).await;
+    let mut chat = Chat::new(client(addr)).with_history(vec![user("hello")]);
+
+    assert!(chat.send(user("again")).await.is_err());
+    assert_eq!(chat.history().count(), 1);
+}
+
+#[tokio::test]
+async fn usage_is_tracked_per_chat_and_per_client() {
+    let (addr, _) = start_server(vec![reply("one", 3, 1), reply("two", 6, 2)]).await;
+    let client = client(addr);
+    let mut chat = Chat::new(client.clone());
+
+    chat.send(user("a")).await.unwrap();
+    chat.send(user("b")).await.unwrap();
+
+    let usage = chat.usage().total();
+    assert_eq!(usage.responses, 2);
+    assert_eq!(usage.input_tokens, 9);
+    assert_eq!(usage.output_tokens, 3);
+    assert_eq!(
+        client.usage().by_model["test"],
+        chat.usage().by_model["test"]
+    );
+}
@@ -0,0 +1,228 @@
+#![cfg(feature = "cli")]
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{Value, json};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::process::Command;
+use tokio_tungstenite::tungstenite::Message;
+
+fn gemini() -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_gemini"));
+    command
+        .env("GEMINI_API_KEY", "key")
+        .env_remove("GEMINI_MODEL")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    command
+}
+
+/// Serve `body` once over HTTP and hand back the raw request.
+async fn start_http_server(body: &'static str) -> (String, tokio::task::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let api_base = format!("http://{}/v1beta/models", listener.local_addr().unwrap());
+    let handle = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let length = head
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().ok())?
+                    })
+                    .unwrap_or(0);
+                if body.len() >= length {
+                    break;
+                }
+            }
+            if n == 0 {
+                break;
+            }
+        }
+        let headers = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+            body.len()
+        );
+        stream.write_all(headers.as_bytes()).await.unwrap();
+        stream.write_all(body.as_bytes()).await.unwrap();
+        String::from_utf8_lossy(&request).into_owned()
+    });
+    (api_base, handle)
+}
+
+/// Fake Live API: answer the first client message with `frames`.
+async fn start_live_server(frames: Vec<Value>) -> (String, tokio::task::JoinHandle<Value>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("ws://{}/ws", listener.local_addr().unwrap());
+    let handle = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        ws.next().await.unwrap().unwrap();
+        ws.send(Message::text(r#"{"setupComplete": {}}"#))
+            .await
+            .unwrap();
+        let text = ws.next().await.unwrap().unwrap().into_text().unwrap();
+        for frame in frames {
+            ws.send(Message::text(frame.to_string())).await.unwrap();
+        }
+        while let Some(Ok(_)) = ws.next().await {}
+        serde_json::from_str(&text).unwrap()
+    });
+    (endpoint, handle)
+}
+
+#[tokio::test]
+async fn live_json_prints_one_json_object_per_message() {
+    let (endpoint, server) = start_live_server(vec![
+        json!({"serverContent": {"modelTurn": {"role": "model", "parts": [{"text": "Hel"}]}}}),
+        json!({"serverContent": {
+            "modelTurn": {"role": "model", "parts": [{"text": "lo"}]},
+            "turnComplete": true,
+            "turnCompleteReason": "NEED_MORE_INPUT"
+        }}),
+    ])
+    .await;
+    let mut child = gemini()
+        .args(["live", "--json", "--live-endpoint", &endpoint])
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(b"hi\n").await.unwrap();
+    drop(stdin);
+    let output = child.wait_with_output().await.unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let lines: Vec<Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["modelTurn"]["parts"][0]["text"], "Hel");
+    assert_eq!(lines[1]["modelTurn"]["parts"][0]["text"], "lo");
+    assert_eq!(lines[1]["turnComplete"], true);
+    assert_eq!(lines[1]["turnCompleteReason"], "NEED_MORE_INPUT");
+
+    let sent = server.await.unwrap();
+    assert_eq!(sent["clientContent"]["turns"][0]["parts"][0]["text"], "hi");
+}
+
+const MODEL: &str = r#"{
+    "name": "models/gemini-2.5-flash",
+    "displayName": "Gemini 2.5 Flash",
+    "inputTokenLimit": 1048576,
+    "outputTokenLimit": 65536
+}"#;
+
+#[tokio::test]
+async fn models_get_prints_text_or_json() {
+    let (api_base, server) = start_http_server(MODEL).await;
+    let output = gemini()
+        .args(["models", "get", "gemini-2.5-flash", "--api-base", &api_base])
+        .output()
+        .await
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "models/gemini-2.5-flash\n\
+         display name: Gemini 2.5 Flash\n\
+         input token limit: 1048576\n\
+         output token limit: 65536\n"
+    );
+    assert!(server.await.unwrap().contains("/models/gemini-2.5-flash"));
+
+    let (api_base, _) = start_http_server(MODEL).await;
+    let output = gemini()
+        .args([
+            "models",
+            "get",
+            "gemini-2.5-flash",
+            "--json",
+            "--api-base",
+            &api_base,
+        ])
+        .output()
+        .await
+        .unwrap();
+    assert!(output.status.success());
+    let model: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(model["displayName"], "Gemini 2.5 Flash");
+    assert_eq!(model["outputTokenLimit"], 65536);
+}
+
+const RESPONSE: &str =
+    r#"{"candidates": [{"content": {"role": "model", "parts": [{"text": "Hello"}]}}]}"#;
+
+fn prompt_text(request: &str) -> Value {
+    let body: Value = serde_json::from_str(request.split_once("\r\n\r\n").unwrap().1).unwrap();
+    body["contents"][0]["parts"].clone()
+}
+
+#[tokio::test]
+async fn prompt_arguments_do_not_wait_for_stdin() {
+    let (api_base, server) = start_http_server(RESPONSE).await;
+    let mut child = gemini()
+        .args([
+            "generate",
+            "hi",
+            "there",
+            "--no-stream",
+            "--api-base",
+            &api_base,
+        ])
+        .spawn()
+        .unwrap();
+    // Stdin stays open, as when run from a script or a service.
+    let stdin = child.stdin.take().unwrap();
+    let output = tokio::time::timeout(Duration::from_secs(10), child.wait_with_output())
+        .await
+        .expect("generate waited for stdin")
+        .unwrap();
+    drop(stdin);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "Hello\n");
+    assert_eq!(
+        prompt_text(&server.await.unwrap()),
+        json!([{"text": "hi there"}])
+    );
+}
+
+#[tokio::test]
+async fn dash_or_no_prompt_reads_stdin() {
+    for args in [vec!["Summarize:", "-"], vec![]] {
+        let (api_base, server) = start_http_server(RESPONSE).await;
+        let mut child = gemini()
+            .args(["generate", "--no-stream", "--api-base", &api_base])
+            .args(&args)
+            .spawn()
+            .unwrap();
+        let mut stdin = child.stdin.take().unwrap();
+        stdin.write_all(b"some text\n").await.unwrap();
+        drop(stdin);
+        let output = child.wait_with_output().await.unwrap();
+        assert!(output.status.success());
+
+        let mut expected = Vec::new();
+        if !args.is_empty() {
+            expected.push(json!({"text": "Summarize:"}));
+        }
+        expected.push(json!({"text": "some text\n"}));
+        assert_eq!(prompt_text(&server.await.unwrap()), Value::Array(expected));
+    }
+}
@@ -0,0 +1,356 @@
+#![cfg(feature = "dotprompt")]
+
+use gemini::v1beta::{
+    Content, Part, PartData, Role,
+    dotprompt::{Error, Prompt},
+    request::FunctionDeclaration,
+};
+use serde_json::{Value, json};
+
+const TRAVEL: &str = r#"---
+name: travel
+model: googleai/gemini-2.5-flash
+config:
+  temperature: 0.4
+  maxOutputTokens: 256
+input:
+  schema:
+    city: string, the city to describe
+    days?: integer
+    interests(array): string
+  default:
+    days: 2
+output:
+  schema:
+    summary: string
+    sights(array, places worth a visit): string
+    pace(enum): [slow, fast]
+---
+{{role "system"}}
+You are a travel guide. Plan {{days}} days.
+{{role "user"}}
+Describe {{city}} for someone into {{#each interests}}{{this}}{{#unless @last}}, {{/unless}}{{/each}}.
+{{media url=photo}}
+"#;
+
+fn to_json(value: &impl serde::Serialize) -> Value {
+    serde_json::to_value(value).unwrap()
+}
+
+#[test]
+fn renders_frontmatter_and_messages_into_a_request() {
+    let prompt = Prompt::parse(TRAVEL).unwrap();
+    assert_eq!(prompt.name(), Some("travel"));
+    assert_eq!(prompt.model(), Some("gemini-2.5-flash"));
+
+    let request = prompt
+        .render(&json!({
+            "city": "Oslo & Bergen",
+            "interests": ["food", "boats"],
+            "photo": "https://example.com/fjord.jpg"
+        }))
+        .unwrap();
+    let value = to_json(&request);
+
+    assert_eq!(
+        value["systemInstruction"],
+        json!({"parts": [{"text": "You are a travel guide. Plan 2 days."}]})
+    );
+    assert_eq!(
+        value["contents"],
+        json!([{"role": "user", "parts": [
+            {"text": "Describe Oslo & Bergen for someone into food, boats."},
+            {"fileData": {"mimeType": "image/jpeg", "fileUri": "https://example.com/fjord.jpg"}}
+        ]}])
+    );
+    let config = &value["generationConfig"];
+    assert!(config["temperature"].is_number());
+    assert_eq!(config["maxOutputTokens"], 256);
+    assert_eq!(config["responseMimeType"], "application/json");
+    assert_eq!(
+        config["responseSchema"],
+        json!({
+            "type": "object",
+            "properties": {
+                "summary": {"type": "string"},
+                "sights": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "places worth a visit"
+                },
+                "pace": {"type": "string", "enum": ["slow", "fast"]}
+            },
+            "required": ["pace", "sights", "summary"]
+        })
+    );
+}
+
+#[test]
+fn input_is_validated_against_the_schema() {
+    let prompt = Prompt::parse(TRAVEL).unwrap();
+    assert_eq!(
+        prompt.input_schema().unwrap()["required"],
+        json!(["city", "interests"])
+    );
+
+    let missing = prompt.render(&json!({"interests": []}));
+    assert!(matches!(
+        missing,
+        Err(Error::InvalidInput { path, message })
+            if path == "input" && message == "missing required field `city`"
+    ));
+
+    let wrong_item = prompt.render(&json!({"city": "Oslo", "interests": ["food", 3]}));
+    assert!(matches!(
+        wrong_item,
+        Err(Error::InvalidInput { path, .. }) if path == "input.interests[1]"
+    ));
+
+    let wrong_default_override =
+        prompt.render(&json!({"city": "Oslo", "interests": [], "days": 1.5}));
+    assert!(matches!(
+        wrong_default_override,
+        Err(Error::InvalidInput { path, message }) if path == "input.days" && message == "expected integer"
+    ));
+}
+
+#[test]
+fn picoschema_converts_to_types_the_api_accepts() {
+    let prompt = Prompt::parse(
+        r#"---
+input:
+  schema:
+    note?: any
+output:
+  schema:
+    title?: string
+    mood(enum, how it feels): [calm, tense, null]
+---
+{{note}}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        prompt.output_schema().unwrap(),
+        &json!({
+            "type": "object",
+            "properties": {
+                "title": {"type": "string", "nullable": true},
+                "mood": {
+                    "type": "string",
+                    "enum": ["calm", "tense"],
+                    "nullable": true,
+                    "description": "how it feels"
+                }
+            },
+            "required": ["mood"]
+        })
+    );
+    assert_eq!(
+        prompt.input_schema().unwrap()["properties"]["note"],
+        json!({"nullable": true})
+    );
+    assert!(prompt.render(&json!({"note": [1, "two"]})).is_ok());
+
+    for schema in ["value: any", "value(array): any", "value: 'null'"] {
+        let source = format!("---\noutput:\n  schema:\n    {schema}\n---\nHi");
+        assert!(
+            matches!(Prompt::parse(&source), Err(Error::Schema(message)) if message.contains("concrete type")),
+            "{schema}"
+        );
+    }
+    assert!(matches!(
+        Prompt::parse("---\noutput:\n  schema:\n    stars(enum): [1, 2]\n---\nHi"),
+        Err(Error::Schema(_))
+    ));
+}
+
+#[test]
+fn history_is_placed_at_the_marker() {
+    let prompt =
+        Prompt::parse("{{role \"system\"}}Be brief.{{history}}{{role \"user\"}}{{question}}")
+            .unwrap();
+    let history = vec![
+        Content::new(Role::User, vec![Part::new(PartData::Text("Hi".into()))]),
+        Content::new(
+            Role::Model,
+            vec![Part::new(PartData::Text("Hello!".into()))],
+        ),
+    ];
+
+    let request = prompt
+        .render_with_history(&json!({"question": "Why?"}), &history)
+        .unwrap();
+    let value = to_json(&request);
+    assert_eq!(value["contents"].as_array().unwrap().len(), 3);
+    assert_eq!(value["contents"][1]["role"], "model");
+    assert_eq!(value["contents"][2]["parts"][0]["text"], "Why?");
+    assert!(value.get("generationConfig").is_none());
+
+    // Without a marker the history goes ahead of the rendered turns.
+    let prompt = Prompt::parse("Summarise {{topic}}\n{{role \"model\"}}Sure:").unwrap();
+    let value = to_json(
+        &prompt
+            .render_with_history(&json!({"topic": "rust"}), &history)
+            .unwrap(),
+    );
+    assert_eq!(value["contents"][0]["parts"][0]["text"], "Hi");
+    assert_eq!(
+        value["contents"][2],
+        json!({"role": "user", "parts": [{"text": "Summarise rust"}]})
+    );
+    assert_eq!(value["contents"][3]["role"], "model");
+}
+
+#[test]
+fn tools_resolve_by_name_and_from_tool_defs() {
+    let source = r#"---
+tools: [weather]
+toolDefs:
+  - name: convert
+    description: Convert a currency amount
+    inputSchema:
+      amount: number
+      to?: string
+---
+Plan a trip."#;
+
+    let prompt = Prompt::parse(source).unwrap();
+    assert!(matches!(
+        prompt.render(&()),
+        Err(Error::UnknownTool(name)) if name == "weather"
+    ));
+
+    let prompt = prompt.with_tool(FunctionDeclaration::new("weather", "Weather of a city"));
+    let value = to_json(&prompt.render(&()).unwrap());
+    assert_eq!(
+        value["tools"],
+        json!([{"functionDeclarations": [
+            {"name": "weather", "description": "Weather of a city"},
+            {
+                "name": "convert",
+                "description": "Convert a currency amount",
+                "parameters": {
+                    "type": "object",
+                    "properties": {"amount": {"type": "number"}, "to": {"type": "string", "nullable": true}},
+                    "required": ["amount"]
+                }
+            }
+        ]}])
+    );
+}
+
+#[test]
+fn json_schema_and_enum_outputs() {
+    let prompt = Prompt::parse(
+        r#"---
+output:
+  format: enum
+  schema:
+    type: string
+    enum: [positive, negative]
+---
+Classify: {{json review}}"#,
+    )
+    .unwrap();
+    let value = to_json(&prompt.render(&json!({"review": {"stars": 5}})).unwrap());
+    assert_eq!(
+        value["contents"][0]["parts"][0]["text"],
+        r#"Classify: {"stars":5}"#
+    );
+    assert_eq!(
+        value["generationConfig"],
+        json!({
+            "responseMimeType": "text/x.enum",
+            "responseSchema": {"type": "string", "enum": ["positive", "negative"]}
+        })
+    );
+
+    assert!(matches!(
+        Prompt::parse("---\noutput:\n  format: xml\n---\nHi").unwrap().render(&()),
+        Err(Error::OutputFormat(format)) if format == "xml"
+    ));
+    assert!(matches!(
+        Prompt::parse("---\ninput:\n  schema:\n    name: text\n---\nHi"),
+        Err(Error::Schema(_))
+    ));
+    assert!(matches!(
+        Prompt::parse("---\nmodel: x\nHi"),
+        Err(Error::UnterminatedFrontmatter)
+    ));
+}
+
+#[test]
+fn inline_media_and_content_types() {
+    let prompt = Prompt::parse(
+        "{{media url=image}}{{media url=clip contentType=\"video/mp4\"}}{{media url=unknown}}",
+    )
+    .unwrap();
+    let input = json!({
+        "image": "data:image/png;base64,iVBORw0KGgo=",
+        "clip": "gs://bucket/clip",
+        "unknown": "https://example.com/file"
+    });
+    assert!(matches!(
+        prompt.render(&input),
+        Err(Error::MediaType(url)) if url == "https://example.com/file"
+    ));
+
+    let prompt =
+        Prompt::parse("{{media url=image}}{{media url=clip contentType=\"video/mp4\"}}").unwrap();
+    let value = to_json(&prompt.render(&input).unwrap());
+    assert_eq!(
+        value["contents"][0]["parts"],
+        json!([
+            {"inlineData": {"mimeType": "image/png", "data": "iVBORw0KGgo="}},
+            {"fileData": {"mimeType": "video/mp4", "fileUri": "gs://bucket/clip"}}
+        ])
+    );
+}
+
+#[test]
+fn renders_live_setups() {
+    let prompt = Prompt::parse(
+        "---\nmodel: gemini-live-2.5-flash\nconfig:\n  temperature: 0.2\n---\nYou are {{persona}}.",
+    )
+    .unwrap();
+    let setup = prompt
+        .render_setup(&json!({"persona": "a pirate"}))
+        .unwrap();
+    let value = to_json(&setup);
+    assert_eq!(value["model"], "models/gemini-live-2.5-flash");
+    assert!(value["generationConfig"]["temperature"].is_number());
+    assert_eq!(
+        value["systemInstruction"]["parts"][0]["text"],
+        "You are a pirate."
+    );
+
+    let prompt = Prompt::parse("---\nmodel: m\n---\n{{role \"user\"}}Hi").unwrap();
+    assert!(matches!(
+        prompt.render_setup(&()),
+        Err(Error::SetupRole("user"))
+    ));
+    let prompt = Prompt::parse("Hi").unwrap();
+    assert!(matches!(prompt.render_setup(&()), Err(Error::MissingModel)));
+}
+
+#[test]
+fn loads_files_with_partials() {
+    let dir = std::env::temp_dir().join(format!("dotprompt-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("_tone.prompt"), "Answer in a {{tone}} tone.").unwrap();
+    std::fs::write(
+        dir.join("greet.prompt"),
+        "---\ninput:\n  default:\n    tone: warm\n---\n{{role \"system\"}}{{> tone}}\n{{role \"user\"}}Greet {{name}}.",
+    )
+    .unwrap();
+
+    let prompt = Prompt::from_path(dir.join("greet.prompt")).unwrap();
+    let value = to_json(&prompt.render(&json!({"name": "Ada"})).unwrap());
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(
+        value["systemInstruction"]["parts"][0]["text"],
+        "Answer in a warm tone."
+    );
+    assert_eq!(value["contents"][0]["parts"][0]["text"], "Greet Ada.");
+}
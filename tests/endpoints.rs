@@ -0,0 +1,197 @@
+#![cfg(feature = "rest")]
+
+use gemini::v1beta::{
+    Content, Part, PartData, Role,
+    request::{EmbedContentRequest, TaskType},
+    response::FileState,
+    rest::Client,
+};
+use serde_json::{Value, json};
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+/// Serve `body` once and hand back the raw request it answered.
+async fn start_server(body: &'static str) -> (SocketAddr, oneshot::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = oneshot::channel();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let length = head
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().ok())?
+                    })
+                    .unwrap_or(0);
+                if body.len() >= length {
+                    break;
+                }
+            }
+            if n == 0 {
+                break;
+            }
+        }
+        let headers = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+            body.len()
+        );
+        stream.write_all(headers.as_bytes()).await.unwrap();
+        stream.write_all(body.as_bytes()).await.unwrap();
+        let _ = tx.send(String::from_utf8_lossy(&request).into_owned());
+    });
+    (addr, rx)
+}
+
+fn client(addr: SocketAddr, model: &str) -> Client {
+    Client::new("key", model).with_api_base(format!("http://{addr}/v1beta/models"))
+}
+
+fn request_line(request: &str) -> &str {
+    request.lines().next().unwrap()
+}
+
+fn json_body(request: &str) -> Value {
+    serde_json::from_str(request.split_once("\r\n\r\n").unwrap().1).unwrap()
+}
+
+#[tokio::test]
+async fn embed_content_posts_model_and_options() {
+    let (addr, request) = start_server(r#"{"embedding": {"values": [0.5, -1.0]}}"#).await;
+    let content = Content::new(Role::User, vec![Part::new(PartData::Text("hi".into()))]);
+    let embed = EmbedContentRequest::new(content)
+        .with_task_type(TaskType::RetrievalQuery)
+        .with_output_dimensionality(2u32);
+
+    let response = client(addr, "gemini-embedding-001")
+        .embed_content(embed)
+        .await
+        .unwrap();
+    assert_eq!(response.embedding.values, vec![0.5, -1.0]);
+
+    let request = request.await.unwrap();
+    assert!(
+        request_line(&request)
+            .starts_with("POST /v1beta/models/gemini-embedding-001:embedContent?key=key")
+    );
+    let body = json_body(&request);
+    assert_eq!(body["model"], "models/gemini-embedding-001");
+    assert_eq!(body["taskType"], "RETRIEVAL_QUERY");
+    assert_eq!(body["outputDimensionality"], 2);
+    assert_eq!(body["content"]["parts"][0]["text"], "hi");
+}
+
+#[tokio::test]
+async fn list_models_passes_page_token() {
+    let body = r#"{
+        "models": [{
+            "name": "models/gemini-2.5-flash",
+            "displayName": "Gemini 2.5 Flash",
+            "inputTokenLimit": 1048576,
+            "supportedGenerationMethods": ["generateContent", "countTokens"],
+            "thinking": true
+        }],
+        "nextPageToken": "next"
+    }"#;
+    let (addr, request) = start_server(body).await;
+
+    let page = client(addr, "test").list_models(Some("abc")).await.unwrap();
+    assert_eq!(page.next_page_token.as_deref(), Some("next"));
+    let model = &page.models[0];
+    assert_eq!(model.name, "models/gemini-2.5-flash");
+    assert_eq!(model.input_token_limit, Some(1048576));
+    assert_eq!(model.thinking, Some(true));
+
+    let request = request.await.unwrap();
+    assert!(request_line(&request).starts_with("GET /v1beta/models?key=key&pageToken=abc "));
+}
+
+#[tokio::test]
+async fn get_model_strips_prefix() {
+    let (addr, request) = start_server(r#"{"name": "models/gemini-2.5-pro"}"#).await;
+
+    let model = client(addr, "test")
+        .get_model("models/gemini-2.5-pro")
+        .await
+        .unwrap();
+    assert_eq!(model.name, "models/gemini-2.5-pro");
+
+    let request = request.await.unwrap();
+    assert!(request_line(&request).starts_with("GET /v1beta/models/gemini-2.5-pro?key=key "));
+}
+
+#[tokio::test]
+async fn upload_file_sends_multipart_related() {
+    let body = r#"{"file": {
+        "name": "files/abc-123",
+        "mimeType": "text/plain",
+        "sizeBytes": "5",
+        "uri": "https://example.com/v1beta/files/abc-123",
+        "state": "ACTIVE"
+    }}"#;
+    let (addr, request) = start_server(body).await;
+
+    let file = client(addr, "test")
+        .upload_file(b"hello".to_vec(), "text/plain", Some("greeting.txt"))
+        .await
+        .unwrap();
+    assert_eq!(file.name, "files/abc-123");
+    assert_eq!(file.size_bytes.as_deref(), Some("5"));
+    assert_eq!(file.state, Some(FileState::Active));
+
+    let request = request.await.unwrap();
+    assert!(
+        request_line(&request)
+            .starts_with("POST /upload/v1beta/files?uploadType=multipart&key=key ")
+    );
+    assert!(
+        request
+            .to_ascii_lowercase()
+            .contains("content-type: multipart/related; boundary=")
+    );
+    assert!(request.contains(r#""displayName":"greeting.txt""#));
+    assert!(request.contains("Content-Type: text/plain\r\n\r\nhello\r\n"));
+}
+
+#[tokio::test]
+async fn list_files_uses_version_root() {
+    let body = r#"{"files": [{"name": "files/a", "state": "PROCESSING"}]}"#;
+    let (addr, request) = start_server(body).await;
+
+    let page = client(addr, "test").list_files(None).await.unwrap();
+    assert_eq!(page.files[0].state, Some(FileState::Processing));
+    assert_eq!(page.next_page_token, None);
+
+    let request = request.await.unwrap();
+    assert!(request_line(&request).starts_with("GET /v1beta/files?key=key "));
+}
+
+#[tokio::test]
+async fn delete_file_accepts_bare_id() {
+    let (addr, request) = start_server("{}").await;
+
+    client(addr, "test").delete_file("abc-123").await.unwrap();
+
+    let request = request.await.unwrap();
+    assert!(request_line(&request).starts_with("DELETE /v1beta/files/abc-123?key=key "));
+}
+
+#[test]
+fn embed_request_skips_unset_options() {
+    let content = Content::new(Role::User, vec![Part::new(PartData::Text("hi".into()))]);
+    let value = serde_json::to_value(EmbedContentRequest::new(content)).unwrap();
+    assert_eq!(
+        value,
+        json!({"content": {"role": "user", "parts": [{"text": "hi"}]}})
+    );
+}
@@ -1,7 +1,12 @@
+#![cfg(feature = "rest")]
+
 use gemini::v1beta::{
-    self, Content, Part, PartData, Role, request,
+    Content, Part, PartData, Role, media,
+    request::{self, GenerationConfig, ImageConfig, ResponseModality},
+    response::{BlockReason, Blocked, FinishReason, Output, Response},
     rest::{Client, Error},
 };
+use serde_json::json;
 use std::net::SocketAddr;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpListener;
@@ -70,3 +75,180 @@ async fn generate_content_invalid_json() {
         other => panic!("unexpected error: {:?}", other),
     }
 }
+
+const PROMPT_BLOCKED: &[u8] =
+    br#"{"promptFeedback": {"blockReason": "PROHIBITED_CONTENT", "safetyRatings": [
+    {"category": "HARM_CATEGORY_HARASSMENT", "probability": "NEGLIGIBLE"},
+    {"category": "HARM_CATEGORY_DANGEROUS_CONTENT", "probability": "HIGH", "blocked": true}
+]}}"#;
+
+#[tokio::test]
+async fn inline_data_is_limited_per_request() {
+    // Each part fits on its own, but not both together.
+    let bytes = vec![0u8; 12 * 1024 * 1024];
+    let part = Part::inline("application/octet-stream", &bytes).unwrap();
+    let req = request::Request::new(vec![Content::new(Role::User, vec![part.clone(), part])]);
+
+    let client = Client::new("key", "test").with_api_base("http://127.0.0.1:1/v1beta/models");
+    match client.generate_content(req.clone()).await {
+        Err(Error::Media(media::Error::TooLarge { size, limit })) => {
+            assert!(size > limit);
+            assert_eq!(limit, media::MAX_INLINE_DATA_BYTES);
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert!(matches!(
+        client.stream_content(req).await,
+        Err(Error::Media(media::Error::TooLarge { .. }))
+    ));
+}
+
+#[tokio::test]
+async fn prompt_blocks_are_ok_unless_strict() {
+    let (addr, handle) = start_server(PROMPT_BLOCKED, "200 OK").await;
+    let client = Client::new("key", "test").with_api_base(format!("http://{}/v1beta/models", addr));
+    let response = client
+        .generate_content(request::Request::new(vec![]))
+        .await
+        .unwrap();
+    handle.abort();
+    assert!(response.candidates.is_empty());
+    assert!(response.is_blocked());
+    assert_eq!(
+        response.prompt_feedback.unwrap().block_reason,
+        Some(BlockReason::ProhibitedContent)
+    );
+
+    let (addr, handle) = start_server(PROMPT_BLOCKED, "200 OK").await;
+    let client = Client::new("key", "test")
+        .with_api_base(format!("http://{}/v1beta/models", addr))
+        .with_strict(true);
+    let err = client
+        .generate_content(request::Request::new(vec![]))
+        .await
+        .unwrap_err();
+    handle.abort();
+    assert_eq!(err.to_string(), "prompt blocked: PROHIBITED_CONTENT");
+    let Error::Blocked(blocked) = err else {
+        panic!("unexpected error: {err:?}");
+    };
+    let offending = blocked.offending_ratings();
+    assert_eq!(offending.len(), 1);
+    assert!(offending[0].blocked);
+    assert!(matches!(
+        *blocked,
+        Blocked::Prompt {
+            reason: Some(BlockReason::ProhibitedContent),
+            ..
+        }
+    ));
+}
+
+#[tokio::test]
+async fn strict_mode_returns_partial_content_of_stopped_candidates() {
+    let body = br#"{"candidates": [{
+        "content": {"role": "model", "parts": [{"text": "Once upon"}]},
+        "finishReason": "RECITATION",
+        "safetyRatings": [{"category": "HARM_CATEGORY_HATE_SPEECH", "probability": "LOW"}]
+    }]}"#;
+    let (addr, handle) = start_server(body, "200 OK").await;
+    let client = Client::new("key", "test")
+        .with_api_base(format!("http://{}/v1beta/models", addr))
+        .with_strict(true);
+    let err = client
+        .generate_content(request::Request::new(vec![]))
+        .await
+        .unwrap_err();
+    handle.abort();
+    match err {
+        Error::Blocked(blocked) => match *blocked {
+            Blocked::Candidate {
+                reason,
+                safety_ratings,
+                partial,
+            } => {
+                assert_eq!(reason, Some(FinishReason::Recitation));
+                assert_eq!(safety_ratings.len(), 1);
+                assert_eq!(partial.unwrap().text().as_deref(), Some("Once upon"));
+            }
+            other => panic!("unexpected block: {other:?}"),
+        },
+        other => panic!("unexpected error: {other:?}"),
+    }
+
+    // Running out of tokens is not a refusal.
+    let body = br#"{"candidates": [{"content": {"role": "model", "parts": []}, "finishReason": "MAX_TOKENS"}]}"#;
+    let (addr, handle) = start_server(body, "200 OK").await;
+    let client = Client::new("key", "test")
+        .with_api_base(format!("http://{}/v1beta/models", addr))
+        .with_strict(true);
+    let response = client
+        .generate_content(request::Request::new(vec![]))
+        .await
+        .unwrap();
+    handle.abort();
+    assert!(!response.is_blocked());
+    assert_eq!(response.finish_reason(), Some(&FinishReason::MaxTokens));
+}
+
+#[test]
+fn newer_finish_reasons_parse() {
+    let response: Response = serde_json::from_value(json!({"candidates": [
+        {"finishReason": "SPII"},
+        {"finishReason": "MALFORMED_FUNCTION_CALL"}
+    ]}))
+    .unwrap();
+    assert!(response.candidates[0].is_blocked());
+    assert!(!response.candidates[1].is_blocked());
+    assert_eq!(
+        response.candidates[1]
+            .finish_reason
+            .as_ref()
+            .unwrap()
+            .as_str(),
+        "MALFORMED_FUNCTION_CALL"
+    );
+}
+
+#[test]
+fn generation_config_serializes_image_output() {
+    let config = GenerationConfig::new()
+        .with_response_modalities(vec![ResponseModality::Text, ResponseModality::Image])
+        .with_image_config(ImageConfig::new().with_aspect_ratio("16:9"));
+    let json = serde_json::to_value(&config).expect("serialize");
+    assert_eq!(json["responseModalities"], json!(["TEXT", "IMAGE"]));
+    assert_eq!(json["imageConfig"]["aspectRatio"], "16:9");
+    assert!(json["imageConfig"].get("imageSize").is_none());
+}
+
+#[test]
+fn response_interleaves_text_and_images() {
+    let data = json!({
+        "candidates": [{
+            "content": {
+                "role": "model",
+                "parts": [
+                    {"text": "Here is a cat:"},
+                    {"inlineData": {"mimeType": "image/png", "data": "iVBORw0KGgo="}},
+                    {"text": "and a dog:"},
+                    {"inlineData": {"mimeType": "image/jpeg", "data": "/9j/"}}
+                ]
+            }
+        }]
+    });
+    let resp: Response = serde_json::from_value(data).unwrap();
+
+    let outputs = resp.outputs();
+    assert_eq!(outputs.len(), 4);
+    assert!(matches!(outputs[0], Output::Text("Here is a cat:")));
+    assert!(matches!(outputs[1], Output::Image(image) if image.mime_type() == "image/png"));
+    assert_eq!(resp.images().len(), 2);
+
+    let dir = std::env::temp_dir().join("gemini-save-images-test");
+    std::fs::create_dir_all(&dir).unwrap();
+    let paths = resp.save_images(&dir, "pet").unwrap();
+    assert_eq!(paths[0], dir.join("pet-0.png"));
+    assert_eq!(paths[1], dir.join("pet-1.jpg"));
+    assert_eq!(std::fs::read(&paths[0]).unwrap(), b"\x89PNG\r\n\x1a\n");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,405 @@
+#![cfg(feature = "live")]
+
+use futures_util::{SinkExt, StreamExt};
+use gemini::v1beta::live::{
+    Client, ClientMessage, ConnectOptions, Error, RealtimeInput, ServerMessage, Setup,
+};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::{
+    Message,
+    protocol::{CloseFrame, frame::coding::CloseCode},
+};
+
+enum Reply {
+    /// Acknowledge the setup, then send the given frames.
+    SetupComplete(Vec<Message>),
+    Close(u16, &'static str),
+    Silence,
+}
+
+/// Accept one websocket, read the setup message and answer with `reply`.
+async fn start_server(reply: Reply) -> (String, tokio::task::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("ws://{}/ws", listener.local_addr().unwrap());
+    let handle = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        let setup = match ws.next().await.unwrap().unwrap() {
+            Message::Text(text) => text.to_string(),
+            other => panic!("unexpected message: {other:?}"),
+        };
+        match reply {
+            Reply::SetupComplete(messages) => {
+                ws.send(Message::binary(r#"{"setupComplete": {}}"#))
+                    .await
+                    .unwrap();
+                for message in messages {
+                    ws.send(message).await.unwrap();
+                }
+                // Keep the socket open until the client goes away.
+                while let Some(Ok(_)) = ws.next().await {}
+            }
+            Reply::Close(code, reason) => {
+                ws.close(Some(CloseFrame {
+                    code: CloseCode::from(code),
+                    reason: reason.into(),
+                }))
+                .await
+                .unwrap();
+                while let Some(Ok(_)) = ws.next().await {}
+            }
+            Reply::Silence => tokio::time::sleep(Duration::from_secs(5)).await,
+        }
+        setup
+    });
+    (endpoint, handle)
+}
+
+/// Answer the websocket upgrade with a plain HTTP response.
+async fn start_http_server(response: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("ws://{}/ws", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = [0u8; 2048];
+        let _ = stream.read(&mut buf).await;
+        stream.write_all(response.as_bytes()).await.unwrap();
+    });
+    endpoint
+}
+
+fn setup() -> Setup {
+    Setup::new("models/gemini-live-2.5-flash")
+}
+
+#[tokio::test]
+async fn connect_resolves_after_setup_complete() {
+    let (endpoint, server) = start_server(Reply::SetupComplete(Vec::new())).await;
+    let (client, mut stream) = Client::connect_with_endpoint("key", setup(), &endpoint)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        stream.next().await,
+        Some(Ok(ServerMessage::SetupComplete))
+    ));
+    client.disconnect(None).unwrap();
+    let sent = server.await.unwrap();
+    assert!(sent.contains(r#""model":"models/gemini-live-2.5-flash""#));
+}
+
+#[tokio::test]
+async fn unknown_and_undecodable_messages_reach_the_stream() {
+    let (endpoint, _server) = start_server(Reply::SetupComplete(vec![
+        Message::text(r#"{"usageMetadata": {"promptTokenCount": 7, "responseTokenCount": 3}}"#),
+        Message::binary(r#"{"somethingNew": {"value": 1}}"#),
+        Message::binary(r#"{"toolCall": {"functionCalls": 5}}"#),
+        Message::text(r#"{"serverContent": {"turnComplete": true}}"#),
+        Message::text(
+            r#"{"goAway": {"timeLeft": "10s"}, "usageMetadata": {"promptTokenCount": 2, "responseTokenCount": 1}}"#,
+        ),
+    ]))
+    .await;
+    let options = ConnectOptions::new()
+        .with_endpoint(&endpoint)
+        .with_reconnect_on_go_away(false);
+    let (client, stream) = Client::connect_with_options("key", setup(), options)
+        .await
+        .unwrap();
+    let messages: Vec<_> = stream.take(7).collect().await;
+
+    assert!(matches!(messages[0], Ok(ServerMessage::SetupComplete)));
+    assert!(matches!(
+        &messages[1],
+        Ok(ServerMessage::UsageMetadata(usage)) if usage.prompt_token_count == Some(7)
+    ));
+    assert!(matches!(
+        &messages[2],
+        Ok(ServerMessage::Unknown(value)) if value["somethingNew"]["value"] == 1
+    ));
+    assert!(matches!(messages[3], Err(Error::Json(_))));
+    assert!(matches!(
+        &messages[4],
+        Ok(ServerMessage::ServerContent { server_content, .. }) if server_content.turn_complete
+    ));
+    assert!(matches!(messages[5], Ok(ServerMessage::GoAway(_))));
+    assert!(matches!(
+        &messages[6],
+        Ok(ServerMessage::UsageMetadata(usage)) if usage.prompt_token_count == Some(2)
+    ));
+    assert_eq!(client.usage().total().output_tokens, 4);
+    client.disconnect(None).unwrap();
+}
+
+#[tokio::test]
+async fn rejected_setup_is_an_error() {
+    let (endpoint, _server) = start_server(Reply::Close(1007, "Unknown model")).await;
+    let err = Client::connect_with_endpoint("key", setup(), &endpoint)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::SetupRejected { code: 1007, reason } if reason == "Unknown model"
+    ));
+
+    let (endpoint, _server) = start_server(Reply::Close(
+        1008,
+        "API key not valid. Please pass a valid API key.",
+    ))
+    .await;
+    let err = Client::connect_with_endpoint("key", setup(), &endpoint)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Unauthorized(reason) if reason.starts_with("API key not valid")));
+}
+
+#[tokio::test]
+async fn missing_setup_complete_times_out() {
+    let (endpoint, _server) = start_server(Reply::Silence).await;
+    let options = ConnectOptions::new()
+        .with_endpoint(endpoint)
+        .with_setup_timeout(Duration::from_millis(200));
+    let err = Client::connect_with_options("key", setup(), options)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::SetupTimeout(timeout) if timeout == Duration::from_millis(200)));
+}
+
+#[tokio::test]
+async fn handshake_failures_are_typed() {
+    let endpoint =
+        start_http_server("HTTP/1.1 403 Forbidden\r\nContent-Length: 6\r\n\r\ndenied").await;
+    let err = Client::connect_with_endpoint("key", setup(), &endpoint)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Unauthorized(reason) if reason.contains("403")));
+
+    let endpoint = start_http_server("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n").await;
+    let err = Client::connect_with_endpoint("key", setup(), &endpoint)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Handshake(_)), "{err:?}");
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("ws://{}/ws", listener.local_addr().unwrap());
+    drop(listener);
+    let err = Client::connect_with_endpoint("key", setup(), &endpoint)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Connect(_)), "{err:?}");
+}
+
+#[tokio::test]
+async fn go_away_moves_the_session_to_a_resumed_connection() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("ws://{}/ws", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut first = tokio_tungstenite::accept_async(stream).await.unwrap();
+        first.next().await.unwrap().unwrap();
+        for frame in [
+            r#"{"setupComplete": {}}"#,
+            r#"{"sessionResumptionUpdate": {"newHandle": "handle-1", "resumable": true}}"#,
+            r#"{"goAway": {"timeLeft": "5s"}}"#,
+        ] {
+            first.send(Message::text(frame)).await.unwrap();
+        }
+
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut second = tokio_tungstenite::accept_async(stream).await.unwrap();
+        let setup = second.next().await.unwrap().unwrap().into_text().unwrap();
+        second
+            .send(Message::text(r#"{"setupComplete": {}}"#))
+            .await
+            .unwrap();
+        // The old connection is closed once the new one is in place.
+        while let Some(Ok(message)) = first.next().await {
+            assert!(message.is_close(), "{message:?}");
+        }
+        let call = second.next().await.unwrap().unwrap().into_text().unwrap();
+        (setup.to_string(), call.to_string())
+    });
+
+    let (client, mut stream) = Client::connect_with_endpoint("key", setup(), &endpoint)
+        .await
+        .unwrap();
+    let mut kinds = Vec::new();
+    while let Some(message) = stream.next().await {
+        match message.unwrap() {
+            ServerMessage::Reconnected { resumed } => {
+                assert!(resumed);
+                break;
+            }
+            ServerMessage::GoAway(go_away) => {
+                assert_eq!(go_away.time_left, Some(Duration::from_secs(5)));
+                kinds.push("goAway");
+            }
+            ServerMessage::SetupComplete => kinds.push("setupComplete"),
+            ServerMessage::SessionResumptionUpdate(_) => kinds.push("resumptionUpdate"),
+            other => panic!("unexpected message: {other:?}"),
+        }
+    }
+    assert_eq!(kinds, ["setupComplete", "resumptionUpdate", "goAway"]);
+
+    // The same client handle now talks to the new connection.
+    client
+        .call(ClientMessage::RealtimeInput(RealtimeInput::Text(
+            "hi".into(),
+        )))
+        .unwrap();
+    let (setup, call) = server.await.unwrap();
+    assert!(
+        setup.contains(r#""sessionResumption":{"handle":"handle-1"}"#),
+        "{setup}"
+    );
+    assert!(call.contains(r#""text":"hi""#), "{call}");
+    client.disconnect(None).unwrap();
+}
+
+#[tokio::test]
+async fn go_away_without_a_handle_ends_the_session() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("ws://{}/ws", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        ws.next().await.unwrap().unwrap();
+        for frame in [
+            r#"{"setupComplete": {}}"#,
+            r#"{"goAway": {"timeLeft": "1s"}}"#,
+        ] {
+            ws.send(Message::text(frame)).await.unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        ws.close(Some(CloseFrame {
+            code: CloseCode::Away,
+            reason: "going away".into(),
+        }))
+        .await
+        .unwrap();
+        // No replacement connection is opened.
+        let accepted = tokio::time::timeout(Duration::from_secs(1), listener.accept()).await;
+        assert!(accepted.is_err());
+    });
+
+    let (_client, stream) = Client::connect_with_endpoint("key", setup(), &endpoint)
+        .await
+        .unwrap();
+    let messages: Vec<_> = tokio::time::timeout(Duration::from_secs(10), stream.collect())
+        .await
+        .expect("the stream should end");
+    assert!(
+        matches!(
+            &messages[..],
+            [
+                Ok(ServerMessage::SetupComplete),
+                Ok(ServerMessage::GoAway(_)),
+                Err(Error::Disconnected { code: 1001, .. })
+            ]
+        ),
+        "{messages:?}"
+    );
+    server.await.unwrap();
+}
+
+/// Accept one connection, set up a resumable session and drop it abruptly.
+async fn accept_and_drop(listener: &TcpListener) -> String {
+    let (stream, _) = listener.accept().await.unwrap();
+    let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+    let setup = ws.next().await.unwrap().unwrap().into_text().unwrap();
+    for frame in [
+        r#"{"setupComplete": {}}"#,
+        r#"{"sessionResumptionUpdate": {"newHandle": "handle-1", "resumable": true}}"#,
+    ] {
+        ws.send(Message::text(frame)).await.unwrap();
+    }
+    // Let the client see the frames before the connection goes away.
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    setup.to_string()
+}
+
+#[tokio::test]
+async fn dropped_connections_are_resumed() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("ws://{}/ws", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        accept_and_drop(&listener).await;
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        let setup = ws.next().await.unwrap().unwrap().into_text().unwrap();
+        ws.send(Message::text(r#"{"setupComplete": {}}"#))
+            .await
+            .unwrap();
+        let call = ws.next().await.unwrap().unwrap().into_text().unwrap();
+        (setup.to_string(), call.to_string())
+    });
+
+    let (client, mut stream) = Client::connect_with_endpoint("key", setup(), &endpoint)
+        .await
+        .unwrap();
+    let mut kinds = Vec::new();
+    while let Some(message) = tokio::time::timeout(Duration::from_secs(10), stream.next())
+        .await
+        .expect("the session should be resumed")
+    {
+        match message.unwrap() {
+            ServerMessage::Reconnected { resumed } => {
+                assert!(resumed);
+                break;
+            }
+            ServerMessage::SetupComplete => kinds.push("setupComplete"),
+            ServerMessage::SessionResumptionUpdate(_) => kinds.push("resumptionUpdate"),
+            other => panic!("unexpected message: {other:?}"),
+        }
+    }
+    assert_eq!(kinds, ["setupComplete", "resumptionUpdate"]);
+
+    client
+        .call(ClientMessage::RealtimeInput(RealtimeInput::Text(
+            "hi".into(),
+        )))
+        .unwrap();
+    let (setup, call) = server.await.unwrap();
+    assert!(
+        setup.contains(r#""sessionResumption":{"handle":"handle-1"}"#),
+        "{setup}"
+    );
+    assert!(call.contains(r#""text":"hi""#), "{call}");
+    client.disconnect(None).unwrap();
+}
+
+#[tokio::test]
+async fn resuming_gives_up_after_the_attempts_run_out() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("ws://{}/ws", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        // The endpoint goes away with the connection.
+        accept_and_drop(&listener).await;
+    });
+
+    let options = ConnectOptions::new()
+        .with_endpoint(endpoint)
+        .with_reconnect_attempts(2u32);
+    let (_client, stream) = Client::connect_with_options("key", setup(), options)
+        .await
+        .unwrap();
+    let messages: Vec<_> = tokio::time::timeout(Duration::from_secs(10), stream.collect())
+        .await
+        .expect("the stream should end");
+    assert!(matches!(
+        messages[..2],
+        [
+            Ok(ServerMessage::SetupComplete),
+            Ok(ServerMessage::SessionResumptionUpdate(_))
+        ]
+    ));
+    assert!(
+        matches!(
+            &messages[2..],
+            [Err(Error::Disconnected { code: 1006, .. })]
+        ),
+        "{messages:?}"
+    );
+}
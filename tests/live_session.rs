@@ -0,0 +1,162 @@
+#![cfg(feature = "live")]
+
+use futures_util::{SinkExt, StreamExt};
+use gemini::v1beta::live::{
+    ConnectOptions, Event, FunctionResponse, FunctionResult, LiveSession, Setup,
+};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tokio_tungstenite::tungstenite::{
+    Message,
+    protocol::{CloseFrame, frame::coding::CloseCode},
+};
+
+/// Accept one websocket, acknowledge the setup and send `frames`. Then collect
+/// `calls` client messages and optionally close with `close`.
+async fn start_server(
+    frames: Vec<&'static str>,
+    calls: usize,
+    close: Option<(u16, &'static str)>,
+) -> (String, oneshot::Receiver<Vec<serde_json::Value>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("ws://{}/ws", listener.local_addr().unwrap());
+    let (tx, rx) = oneshot::channel();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        ws.next().await.unwrap().unwrap();
+        ws.send(Message::text(r#"{"setupComplete": {}}"#))
+            .await
+            .unwrap();
+        for frame in frames {
+            ws.send(Message::text(frame)).await.unwrap();
+        }
+        let mut received = Vec::new();
+        while received.len() < calls {
+            let text = ws.next().await.unwrap().unwrap().into_text().unwrap();
+            received.push(serde_json::from_str(&text).unwrap());
+        }
+        tx.send(received).unwrap();
+        if let Some((code, reason)) = close {
+            ws.close(Some(CloseFrame {
+                code: CloseCode::from(code),
+                reason: reason.into(),
+            }))
+            .await
+            .unwrap();
+        }
+        while let Some(Ok(_)) = ws.next().await {}
+    });
+    (endpoint, rx)
+}
+
+fn options(endpoint: String) -> ConnectOptions {
+    ConnectOptions::new()
+        .with_endpoint(endpoint)
+        .with_reconnect_on_go_away(false)
+}
+
+#[tokio::test]
+async fn server_messages_become_flat_events() {
+    let (endpoint, _server) = start_server(
+        vec![
+            r#"{"serverContent": {
+                "inputTranscription": {"text": "hello"},
+                "modelTurn": {"parts": [
+                    {"text": "thinking", "thought": true},
+                    {"text": "Hi"},
+                    {"inlineData": {"mimeType": "audio/pcm;rate=24000", "data": "AQACAA=="}}
+                ]},
+                "outputTranscription": {"text": "Hi"}
+            }}"#,
+            r#"{"serverContent": {"interrupted": true}}"#,
+            r#"{"toolCall": {"functionCalls": [{"id": "c1", "name": "time", "args": {}}]}}"#,
+            r#"{"toolCallCancellation": {"ids": ["c1"]}}"#,
+            r#"{"usageMetadata": {"promptTokenCount": 3, "responseTokenCount": 2}}"#,
+            r#"{"serverContent": {"generationComplete": true}}"#,
+            r#"{"serverContent": {"turnComplete": true}}"#,
+            r#"{"goAway": {"timeLeft": "1.5s"}}"#,
+        ],
+        0,
+        Some((1011, "internal error")),
+    )
+    .await;
+    let session =
+        LiveSession::connect_with_options("key", Setup::new("models/m"), options(endpoint))
+            .await
+            .unwrap();
+    let events: Vec<Event> = session.map(Result::unwrap).collect().await;
+
+    assert!(matches!(&events[0], Event::InputTranscript(text) if text == "hello"));
+    assert!(matches!(&events[1], Event::Thought(text) if text == "thinking"));
+    assert!(matches!(&events[2], Event::TextDelta(text) if text == "Hi"));
+    assert!(matches!(
+        &events[3],
+        Event::AudioChunk { samples, sample_rate: 24000 } if samples == &[1, 2]
+    ));
+    assert!(matches!(&events[4], Event::OutputTranscript(text) if text == "Hi"));
+    assert!(matches!(events[5], Event::Interrupted));
+    assert!(matches!(&events[6], Event::ToolCall(calls) if calls[0].name == "time"));
+    assert!(matches!(&events[7], Event::ToolCallCancelled(ids) if ids == &["c1"]));
+    assert!(matches!(events[8], Event::GenerationComplete));
+    assert!(matches!(
+        &events[9],
+        Event::TurnComplete { usage: Some(usage) } if usage.prompt_token_count == Some(3)
+    ));
+    assert!(matches!(
+        events[10],
+        Event::GoAway { time_left: Some(time_left) } if time_left == Duration::from_millis(1500)
+    ));
+    assert!(matches!(
+        &events[11],
+        Event::Closed { reason: Some(reason) } if reason == "internal error"
+    ));
+    assert_eq!(events.len(), 12);
+}
+
+#[tokio::test]
+async fn typed_sends_and_close() {
+    let (endpoint, server) = start_server(Vec::new(), 5, None).await;
+    let mut session =
+        LiveSession::connect_with_options("key", Setup::new("models/m"), options(endpoint))
+            .await
+            .unwrap();
+
+    session.send_text("hi").unwrap();
+    session.send_audio(&[1, -1], 16_000).unwrap();
+    session
+        .send_video_frame("image/jpeg", vec![0xff, 0xd8])
+        .unwrap();
+    session
+        .send_tool_responses(vec![FunctionResponse::new(
+            Some("c1".to_string()),
+            "time",
+            FunctionResult::new(serde_json::json!({"time": "noon"})),
+        )])
+        .unwrap();
+    session.end_audio_stream().unwrap();
+
+    let received = server.await.unwrap();
+    session.client().clone().disconnect(None).unwrap();
+    assert_eq!(received[0]["realtimeInput"]["text"], "hi");
+    assert_eq!(
+        received[1]["realtimeInput"]["audio"],
+        serde_json::json!({"mimeType": "audio/pcm;rate=16000", "data": "AQD//w=="})
+    );
+    assert_eq!(
+        received[2]["realtimeInput"]["video"]["mimeType"],
+        "image/jpeg"
+    );
+    assert_eq!(
+        received[3]["toolResponse"]["functionResponses"][0]["response"]["result"]["time"],
+        "noon"
+    );
+    assert_eq!(received[4]["realtimeInput"]["audioStreamEnd"], true);
+
+    assert!(matches!(
+        session.next().await,
+        Some(Ok(Event::Closed { reason: None }))
+    ));
+    assert!(session.next().await.is_none());
+}
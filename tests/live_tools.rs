@@ -0,0 +1,304 @@
+#![cfg(feature = "live")]
+
+use futures_util::{SinkExt, StreamExt};
+use gemini::v1beta::live::{
+    ConnectOptions, Event, FunctionBehavior, FunctionDeclaration, FunctionResponseScheduling,
+    LiveSession, Setup, ToolRegistry,
+};
+use serde_json::{Value, json};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// Acknowledge the setup, send `calls` and after a short pause a cancellation
+/// of `cancel`, then collect tool responses until the client has been quiet
+/// for a while. Each response is also reported as it arrives.
+async fn start_server(
+    calls: Value,
+    cancel: &'static [&'static str],
+) -> (
+    String,
+    tokio::task::JoinHandle<Vec<Value>>,
+    mpsc::UnboundedReceiver<Value>,
+) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("ws://{}/ws", listener.local_addr().unwrap());
+    let handle = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        ws.next().await.unwrap().unwrap();
+        for frame in [
+            json!({"setupComplete": {}}),
+            json!({"toolCall": {"functionCalls": calls}}),
+        ] {
+            ws.send(frame.to_string().into()).await.unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        ws.send(
+            json!({"toolCallCancellation": {"ids": cancel}})
+                .to_string()
+                .into(),
+        )
+        .await
+        .unwrap();
+
+        let mut responses = Vec::new();
+        while let Ok(Some(Ok(message))) =
+            tokio::time::timeout(Duration::from_millis(500), ws.next()).await
+        {
+            let value: Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+            for response in value["toolResponse"]["functionResponses"]
+                .as_array()
+                .unwrap()
+            {
+                let _ = tx.send(response.clone());
+                responses.push(response.clone());
+            }
+        }
+        responses
+    });
+    (endpoint, handle, rx)
+}
+
+fn registry() -> ToolRegistry {
+    ToolRegistry::new()
+        .with_timeout(Duration::from_millis(150))
+        .with_scheduling(FunctionResponseScheduling::Interrupt)
+        .with_function(
+            FunctionDeclaration::new("add").with_description("Add two numbers"),
+            |args: Value| async move {
+                let sum = args["a"].as_i64().unwrap() + args["b"].as_i64().unwrap();
+                Ok::<_, String>(json!({ "sum": sum }))
+            },
+        )
+        .with_function(
+            FunctionDeclaration::new("search").with_behavior(FunctionBehavior::NonBlocking),
+            |_| async {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                Ok::<_, String>(json!("stale"))
+            },
+        )
+        .with_function(
+            FunctionDeclaration::new("slow").with_behavior(FunctionBehavior::NonBlocking),
+            |_| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok::<_, String>(Value::Null)
+            },
+        )
+        .with_function(FunctionDeclaration::new("fail"), |_| async {
+            Err::<Value, _>("disk full")
+        })
+}
+
+#[tokio::test]
+async fn tool_calls_are_answered_and_cancelled() {
+    let (endpoint, server, _) = start_server(
+        json!([
+            {"id": "1", "name": "add", "args": {"a": 2, "b": 3}},
+            {"id": "2", "name": "search", "args": {"query": "rust"}},
+            {"id": "3", "name": "slow", "args": {}},
+            {"id": "4", "name": "fail", "args": {}},
+            {"id": "5", "name": "unknown", "args": {}}
+        ]),
+        &["2"],
+    )
+    .await;
+    let tools = registry();
+    let names: Vec<Value> = match tools.tool() {
+        gemini::v1beta::live::Tool::FunctionDeclarations(declarations) => declarations
+            .iter()
+            .map(|declaration| serde_json::to_value(declaration).unwrap()["name"].clone())
+            .collect(),
+        other => panic!("unexpected tool: {other:?}"),
+    };
+    assert_eq!(names, ["add", "fail", "search", "slow"]);
+
+    let session = LiveSession::connect_with_options(
+        "key",
+        Setup::new("models/m").with_tools(vec![tools.tool()]),
+        ConnectOptions::new().with_endpoint(endpoint),
+    )
+    .await
+    .unwrap()
+    .with_tools(tools);
+    let events = tokio::spawn(session.collect::<Vec<_>>());
+
+    let mut responses = server.await.unwrap();
+    responses.sort_by(|a, b| a["id"].as_str().cmp(&b["id"].as_str()));
+    assert_eq!(responses.len(), 3, "{responses:#?}");
+    assert_eq!(responses[0]["id"], "1");
+    assert_eq!(responses[0]["name"], "add");
+    assert_eq!(responses[0]["response"], json!({"result": {"sum": 5}}));
+    assert!(responses[0]["scheduling"].is_null());
+    assert_eq!(responses[1]["id"], "3");
+    assert_eq!(responses[1]["scheduling"], "INTERRUPT");
+    assert_eq!(
+        responses[1]["response"]["result"]["error"],
+        "timed out after 150ms"
+    );
+    assert_eq!(
+        responses[2]["response"]["result"],
+        json!({"error": "disk full"})
+    );
+    assert!(responses[2]["scheduling"].is_null());
+
+    let events = events.await.unwrap();
+    assert!(events.iter().any(|event| matches!(
+        event,
+        Ok(Event::ToolCall(calls)) if calls.len() == 5
+    )));
+}
+
+/// Wait until the server has received a response to call `id`.
+async fn received(responses: &mut mpsc::UnboundedReceiver<Value>, id: &str) -> Value {
+    loop {
+        let response = tokio::time::timeout(Duration::from_secs(5), responses.recv())
+            .await
+            .expect("no response in time")
+            .unwrap();
+        if response["id"] == id {
+            return response;
+        }
+    }
+}
+
+#[tokio::test]
+async fn streaming_tools_report_progress() {
+    let (endpoint, server, mut live) = start_server(
+        json!([
+            {"id": "lookup", "name": "lookup", "args": {}},
+            {"id": "burst", "name": "burst", "args": {}},
+            {"id": "watch", "name": "watch", "args": {}},
+            {"id": "broken", "name": "broken", "args": {}}
+        ]),
+        &["watch"],
+    )
+    .await;
+    let (progress, updates) = mpsc::channel::<Result<Value, String>>(8);
+    let updates = Mutex::new(Some(updates));
+    let non_blocking =
+        |name: &str| FunctionDeclaration::new(name).with_behavior(FunctionBehavior::NonBlocking);
+    let tools = ToolRegistry::new()
+        .with_streaming_function(non_blocking("lookup"), move |_| {
+            ReceiverStream::new(updates.lock().unwrap().take().unwrap())
+        })
+        .with_streaming_function(non_blocking("burst"), |_| {
+            tokio_stream::iter([Ok::<_, String>(json!(1)), Ok(json!(2))])
+        })
+        .with_streaming_function(non_blocking("watch"), |_| {
+            tokio_stream::iter([Ok::<_, String>(json!("first"))]).chain(tokio_stream::pending())
+        })
+        .with_streaming_function(non_blocking("broken"), |_| {
+            tokio_stream::iter([Ok(json!("partial")), Err("lost connection")])
+        });
+
+    let session = LiveSession::connect_with_options(
+        "key",
+        Setup::new("models/m"),
+        ConnectOptions::new().with_endpoint(endpoint),
+    )
+    .await
+    .unwrap()
+    .with_tools(tools);
+    let _events = tokio::spawn(session.collect::<Vec<_>>());
+
+    // Each update reaches the model before the handler yields the next one.
+    progress.send(Ok(json!({"done": 1}))).await.unwrap();
+    let first = received(&mut live, "lookup").await;
+    assert_eq!(first["response"]["result"], json!({"done": 1}));
+    assert_eq!(first["willContinue"], true);
+    progress.send(Ok(json!({"done": 2}))).await.unwrap();
+    let second = received(&mut live, "lookup").await;
+    assert_eq!(second["response"]["result"], json!({"done": 2}));
+    drop(progress);
+
+    let responses = server.await.unwrap();
+    let sent = |id: &str| -> Vec<(Value, Value)> {
+        responses
+            .iter()
+            .filter(|response| response["id"] == id)
+            .map(|response| {
+                assert_eq!(response["scheduling"], "WHEN_IDLE");
+                (
+                    response["response"]["result"].clone(),
+                    response["willContinue"].clone(),
+                )
+            })
+            .collect()
+    };
+
+    // Every result is sent once, and the end of the stream closes the call.
+    assert_eq!(
+        sent("lookup"),
+        [
+            (json!({"done": 1}), json!(true)),
+            (json!({"done": 2}), json!(true)),
+            (Value::Null, json!(false)),
+        ]
+    );
+    assert_eq!(
+        sent("burst"),
+        [
+            (json!(1), json!(true)),
+            (json!(2), json!(true)),
+            (Value::Null, json!(false)),
+        ]
+    );
+    // Cancelled after the first result.
+    assert_eq!(sent("watch"), [(json!("first"), json!(true))]);
+    assert_eq!(
+        sent("broken"),
+        [
+            (json!("partial"), json!(true)),
+            (json!({"error": "lost connection"}), json!(false)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn handlers_may_use_the_registry() {
+    let (endpoint, server, _) = start_server(
+        json!([
+            {"id": "1", "name": "slow", "args": {}},
+            {"id": "2", "name": "stop", "args": {}}
+        ]),
+        &[],
+    )
+    .await;
+    let registry = Arc::new(OnceLock::<ToolRegistry>::new());
+    let tools = ToolRegistry::new()
+        .with_function(FunctionDeclaration::new("slow"), |_| async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok::<_, String>(json!("done"))
+        })
+        .with_function(FunctionDeclaration::new("stop"), {
+            let registry = registry.clone();
+            move |_| {
+                // Runs synchronously while the call is dispatched.
+                registry.get().unwrap().cancel(&["1".to_string()]);
+                async { Ok::<_, String>(json!("stopped")) }
+            }
+        });
+    registry.set(tools.clone()).ok().unwrap();
+
+    let session = LiveSession::connect_with_options(
+        "key",
+        Setup::new("models/m"),
+        ConnectOptions::new().with_endpoint(endpoint),
+    )
+    .await
+    .unwrap()
+    .with_tools(tools);
+    let _events = tokio::spawn(session.collect::<Vec<_>>());
+
+    let responses = tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .expect("dispatch deadlocked")
+        .unwrap();
+    assert_eq!(responses.len(), 1, "{responses:#?}");
+    assert_eq!(responses[0]["id"], "2");
+    assert_eq!(responses[0]["response"]["result"], "stopped");
+}
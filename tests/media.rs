@@ -0,0 +1,172 @@
+use gemini::v1beta::{
+    Part, PartData, live,
+    media::{self, Error, MAX_INLINE_DATA_BYTES, PcmAudio},
+    request::{GenerationConfig, ResponseModality, SpeechConfig},
+    response::Response,
+};
+use serde_json::json;
+use std::time::Duration;
+
+const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+#[test]
+fn detects_mime_types_from_bytes_and_extensions() {
+    assert_eq!(media::detect_mime_type(PNG), Some("image/png"));
+    assert_eq!(
+        media::detect_mime_type(b"%PDF-1.7"),
+        Some("application/pdf")
+    );
+    assert_eq!(
+        media::detect_mime_type(b"RIFF\0\0\0\0WAVEfmt "),
+        Some("audio/wav")
+    );
+    assert_eq!(
+        media::detect_mime_type(b"\0\0\0\x18ftypmp42"),
+        Some("video/mp4")
+    );
+    assert_eq!(media::detect_mime_type(b"plain text"), None);
+    assert_eq!(media::mime_type_from_extension("notes.MD"), Some("text/md"));
+    assert_eq!(media::mime_type_from_extension("archive.zip"), None);
+}
+
+#[test]
+fn rest_part_from_path_detects_and_encodes() {
+    let path = std::env::temp_dir().join("gemini-media-test.bin");
+    std::fs::write(&path, PNG).unwrap();
+    let part = Part::from_path(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let PartData::InlineData(inline) = &part.data else {
+        panic!("unexpected part: {:?}", part.data);
+    };
+    assert_eq!(inline.mime_type(), "image/png");
+    assert_eq!(inline.decode().unwrap(), PNG);
+
+    let json = serde_json::to_value(&part).unwrap();
+    assert_eq!(json["inlineData"]["mimeType"], "image/png");
+}
+
+#[test]
+fn from_path_falls_back_to_extension() {
+    let path = std::env::temp_dir().join("gemini-media-test.csv");
+    std::fs::write(&path, "a,b\n1,2\n").unwrap();
+    let part = live::Part::from_path(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let live::PartData::InlineData(inline) = &part.data else {
+        panic!("unexpected part: {:?}", part.data);
+    };
+    assert_eq!(inline.mime_type(), "text/csv");
+    assert_eq!(inline.data(), b"a,b\n1,2\n");
+}
+
+#[test]
+fn typed_constructors_check_content() {
+    assert!(Part::image_bytes(PNG).is_ok());
+    assert!(matches!(
+        Part::image_bytes(b"%PDF-1.7"),
+        Err(Error::UnexpectedMimeType { found, .. }) if found == "application/pdf"
+    ));
+    assert!(live::Part::pdf(b"%PDF-1.7".to_vec()).is_ok());
+}
+
+#[test]
+fn audio_pcm_is_little_endian() {
+    let part = live::Part::audio_pcm(16_000, &[1, -2]).unwrap();
+    let live::PartData::InlineData(inline) = &part.data else {
+        panic!("unexpected part: {:?}", part.data);
+    };
+    assert_eq!(inline.mime_type(), "audio/pcm;rate=16000");
+    assert_eq!(inline.data(), &[0x01, 0x00, 0xfe, 0xff]);
+}
+
+#[test]
+fn rejects_oversized_inline_data() {
+    let bytes = vec![0u8; MAX_INLINE_DATA_BYTES];
+    match Part::inline("application/octet-stream", &bytes) {
+        Err(Error::TooLarge { size, limit }) => {
+            assert!(size > limit);
+            assert_eq!(limit, MAX_INLINE_DATA_BYTES);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn video_with_offsets_serializes_alongside_file_data() {
+    let part = Part::video_with_offsets(
+        "files/abc",
+        "video/mp4",
+        Duration::from_secs(10),
+        Duration::from_millis(12_500),
+    );
+    let json = serde_json::to_value(&part).unwrap();
+    assert_eq!(json["fileData"]["fileUri"], "files/abc");
+    assert_eq!(json["videoMetadata"]["startOffset"]["seconds"], 10);
+    assert_eq!(json["videoMetadata"]["endOffset"]["nanos"], 500_000_000);
+
+    let part: Part = serde_json::from_value(json).unwrap();
+    assert!(part.video_metadata.is_some());
+    assert!(matches!(part.data, PartData::FileData(_)));
+}
+
+#[test]
+fn speech_config_serializes_multi_speaker_voices() {
+    let config = GenerationConfig::new()
+        .with_response_modalities(vec![ResponseModality::Audio])
+        .with_speech_config(SpeechConfig::multi_speaker([
+            ("Joe", "Kore"),
+            ("Jane", "Puck"),
+        ]));
+    let json = serde_json::to_value(&config).unwrap();
+    let speakers = &json["speechConfig"]["multiSpeakerVoiceConfig"]["speakerVoiceConfigs"];
+    assert_eq!(speakers[1]["speaker"], "Jane");
+    assert_eq!(
+        speakers[1]["voiceConfig"]["prebuiltVoiceConfig"]["voiceName"],
+        "Puck"
+    );
+    assert!(json["speechConfig"].get("voiceConfig").is_none());
+
+    let json = serde_json::to_value(SpeechConfig::voice("Kore")).unwrap();
+    assert_eq!(
+        json["voiceConfig"]["prebuiltVoiceConfig"]["voiceName"],
+        "Kore"
+    );
+}
+
+#[test]
+fn response_audio_decodes_to_wav() {
+    let response: Response = serde_json::from_value(json!({
+        "candidates": [{
+            "content": {
+                "role": "model",
+                "parts": [
+                    {"inlineData": {"mimeType": "audio/pcm;rate=24000", "data": "AQD+/w=="}},
+                    {"inlineData": {"mimeType": "audio/pcm;rate=24000", "data": "AwA="}}
+                ]
+            }
+        }]
+    }))
+    .unwrap();
+
+    let audio = response.audio().unwrap().unwrap();
+    assert_eq!(audio, PcmAudio::new(24_000, 1, vec![1, -2, 3]));
+
+    let wav = audio.to_wav_bytes();
+    assert_eq!(wav.len(), 44 + 6);
+    assert_eq!(media::detect_mime_type(&wav), Some("audio/wav"));
+    assert_eq!(u32::from_le_bytes(wav[24..28].try_into().unwrap()), 24_000);
+    assert_eq!(u32::from_le_bytes(wav[40..44].try_into().unwrap()), 6);
+    assert_eq!(&wav[44..], &[0x01, 0x00, 0xfe, 0xff, 0x03, 0x00]);
+}
+
+#[test]
+fn pcm_sample_rate_parses_mime_parameters() {
+    assert_eq!(media::pcm_sample_rate("audio/pcm;rate=24000"), Some(24_000));
+    assert_eq!(media::pcm_sample_rate("audio/L16; rate=8000"), None);
+    assert_eq!(media::pcm_sample_rate("audio/pcm"), None);
+    assert!(matches!(
+        PcmAudio::from_inline("audio/wav", &[]),
+        Err(Error::NotPcm(_))
+    ));
+}
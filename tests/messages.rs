@@ -1,5 +1,5 @@
 use gemini::v1beta::live::{
-    ClientContent, Content, Part, PartData, Role, ServerContent, ServerMessage,
+    ClientContent, Content, Part, PartData, Role, ServerMessage, TurnCompleteReason,
 };
 
 #[test]
@@ -36,8 +36,8 @@ fn deserializes_server_content_v2() {
             server_content,
             usage_metadata,
         } => {
-            assert!(matches!(server_content, ServerContent::TurnComplete));
-            assert!(!matches!(server_content, ServerContent::GenerationComplete));
+            assert!(server_content.turn_complete);
+            assert!(!server_content.generation_complete);
             assert!(usage_metadata.is_some());
         }
         other => panic!("unexpected message: {:?}", other),
@@ -54,8 +54,8 @@ fn deserializes_server_content_v3() {
             server_content,
             usage_metadata,
         } => {
-            assert!(matches!(server_content, ServerContent::GenerationComplete));
-            assert!(!matches!(server_content, ServerContent::TurnComplete));
+            assert!(server_content.generation_complete);
+            assert!(!server_content.turn_complete);
             assert!(usage_metadata.is_none());
         }
         other => panic!("unexpected message: {:?}", other),
@@ -69,21 +69,48 @@ fn deserializes_server_content_with_inline() {
     let msg: ServerMessage = serde_json::from_slice(data).unwrap();
     match msg {
         ServerMessage::ServerContent { server_content, .. } => {
-            assert!(matches!(server_content, ServerContent::ModelTurn(..)));
-
-            match server_content {
-                ServerContent::ModelTurn(turn) => match &turn.parts[0].data {
-                    PartData::InlineData(inline_data) => {
-                        assert_eq!(inline_data.mime_type(), "audio/pcm;rate=24000");
-                    }
-                    other => panic!("unexpected part: {:?}", other),
-                },
-                _ => panic!(),
+            let turn = server_content.model_turn.expect("model turn");
+            match &turn.parts[0].data {
+                PartData::InlineData(inline_data) => {
+                    assert_eq!(inline_data.mime_type(), "audio/pcm;rate=24000");
+                }
+                other => panic!("unexpected part: {:?}", other),
             }
         }
         other => panic!("unexpected message: {:?}", other),
     }
 }
+
+#[test]
+fn deserializes_simultaneous_server_content_fields() {
+    let data = br#"{"serverContent": {
+        "modelTurn": {"parts": [{"text": "Bye"}]},
+        "outputTranscription": {"text": "Bye"},
+        "interrupted": true,
+        "turnComplete": true,
+        "turnCompleteReason": "NEED_MORE_INPUT",
+        "waitingForInput": true,
+        "urlContextMetadata": {"urlMetadata": []}
+    }}"#;
+
+    let msg: ServerMessage = serde_json::from_slice(data).unwrap();
+    let ServerMessage::ServerContent { server_content, .. } = msg else {
+        panic!("unexpected message: {msg:?}");
+    };
+    assert_eq!(server_content.text().as_deref(), Some("Bye"));
+    assert_eq!(server_content.output_transcription.unwrap().text, "Bye");
+    assert!(server_content.interrupted);
+    assert!(server_content.turn_complete);
+    assert!(!server_content.generation_complete);
+    assert!(server_content.waiting_for_input);
+    assert_eq!(
+        server_content.turn_complete_reason,
+        Some(TurnCompleteReason::NeedMoreInput)
+    );
+    assert!(server_content.url_context_metadata.is_some());
+    assert!(server_content.input_transcription.is_none());
+}
+
 #[test]
 fn deserializes_tool_call() {
     let data = br#"{
@@ -104,6 +131,36 @@ fn deserializes_tool_call() {
     }
 }
 
+#[test]
+fn frames_keep_usage_sent_along_with_other_messages() {
+    let data = br#"{
+        "toolCall": {"functionCalls": [{"id": "1", "name": "foo", "args": {}}]},
+        "usageMetadata": {"promptTokenCount": 12, "responseTokenCount": 4}
+    }"#;
+
+    let messages = ServerMessage::from_frame(data).unwrap();
+    assert_eq!(messages.len(), 2);
+    assert!(
+        matches!(&messages[0], ServerMessage::ToolCall(call) if call.function_calls[0].name == "foo")
+    );
+    assert!(matches!(
+        &messages[1],
+        ServerMessage::UsageMetadata(usage)
+            if usage.prompt_token_count == Some(12) && usage.response_token_count == Some(4)
+    ));
+
+    let data =
+        br#"{"serverContent": {"turnComplete": true}, "usageMetadata": {"promptTokenCount": 1}}"#;
+    let messages = ServerMessage::from_frame(data).unwrap();
+    assert!(matches!(
+        &messages[..],
+        [ServerMessage::ServerContent {
+            usage_metadata: Some(_),
+            ..
+        }]
+    ));
+}
+
 #[test]
 fn deserializes_tool_call_cancellation() {
     let data = br#"{
@@ -128,12 +185,25 @@ fn deserializes_go_away() {
     let msg: ServerMessage = serde_json::from_slice(data).unwrap();
     match msg {
         ServerMessage::GoAway(go) => {
-            assert_eq!(go.time_left.as_deref(), Some("10s"));
+            assert_eq!(go.time_left, Some(std::time::Duration::from_secs(10)));
         }
         other => panic!("unexpected message: {:?}", other),
     }
 }
 
+#[test]
+fn go_away_time_left_accepts_fractions() {
+    let msg: ServerMessage =
+        serde_json::from_slice(br#"{"goAway": {"timeLeft": "0.250s"}}"#).unwrap();
+    assert!(matches!(
+        msg,
+        ServerMessage::GoAway(go) if go.time_left == Some(std::time::Duration::from_millis(250))
+    ));
+    assert!(
+        serde_json::from_slice::<ServerMessage>(br#"{"goAway": {"timeLeft": "soon"}}"#).is_err()
+    );
+}
+
 #[test]
 fn deserializes_session_resumption_update() {
     let data = br#"{
@@ -157,17 +227,13 @@ fn deserializes_executable_code() {
     let msg: ServerMessage = serde_json::from_slice(data).unwrap();
     match msg {
         ServerMessage::ServerContent { server_content, .. } => {
-            assert!(matches!(server_content, ServerContent::ModelTurn(..)));
-
-            match server_content {
-                ServerContent::ModelTurn(turn) => match &turn.parts[0].data {
-                    PartData::ExecutableCode(executable) => {
-                        assert_eq!(executable.language, "PYTHON");
-                        assert_eq!(executable.code, "print(default_api.time())\n");
-                    }
-                    other => panic!("unexpected part: {:?}", other),
-                },
-                _ => panic!(),
+            let turn = server_content.model_turn.expect("model turn");
+            match &turn.parts[0].data {
+                PartData::ExecutableCode(executable) => {
+                    assert_eq!(executable.language, "PYTHON");
+                    assert_eq!(executable.code, "print(default_api.time())\n");
+                }
+                other => panic!("unexpected part: {:?}", other),
             }
         }
         other => panic!("unexpected message: {:?}", other),
@@ -0,0 +1,397 @@
+#![cfg(feature = "openai")]
+
+use gemini::v1beta::{openai, rest::Client};
+use serde_json::{Value, json};
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+type Responses = Arc<Mutex<VecDeque<(&'static str, String)>>>;
+
+/// Fake Gemini API answering requests with `responses` in order, as
+/// `(content type, body)`. Returns the raw requests it received.
+async fn start_gemini(
+    responses: Vec<(&'static str, String)>,
+) -> (SocketAddr, UnboundedReceiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let responses: Responses = Arc::new(Mutex::new(responses.into()));
+    let (tx, rx) = unbounded_channel();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            tokio::spawn(serve_connection(stream, responses.clone(), tx.clone()));
+        }
+    });
+    (addr, rx)
+}
+
+async fn serve_connection(
+    mut stream: TcpStream,
+    responses: Responses,
+    tx: UnboundedSender<String>,
+) {
+    let mut buf = Vec::new();
+    loop {
+        let Some(request) = read_request(&mut stream, &mut buf).await else {
+            return;
+        };
+        let _ = tx.send(request);
+        let Some((content_type, body)) = responses.lock().unwrap().pop_front() else {
+            return;
+        };
+        let head = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\r\n",
+            body.len()
+        );
+        stream.write_all(head.as_bytes()).await.unwrap();
+        // Like the real API, send one server-sent event at a time.
+        for event in body.split_inclusive("\n\n") {
+            stream.write_all(event.as_bytes()).await.unwrap();
+            stream.flush().await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+    }
+}
+
+async fn read_request(stream: &mut TcpStream, buf: &mut Vec<u8>) -> Option<String> {
+    let mut chunk = [0u8; 4096];
+    loop {
+        if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            let head = String::from_utf8_lossy(&buf[..end]).into_owned();
+            let length = head
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length")
+                        .then(|| value.trim().parse::<usize>().ok())?
+                })
+                .unwrap_or(0);
+            if buf.len() >= end + 4 + length {
+                let request: Vec<u8> = buf.drain(..end + 4 + length).collect();
+                return Some(String::from_utf8_lossy(&request).into_owned());
+            }
+        }
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+}
+
+/// Serve the proxy in front of the fake Gemini API at `gemini`.
+async fn start_proxy(gemini: SocketAddr) -> String {
+    let client =
+        Client::new("key", "unused").with_api_base(format!("http://{gemini}/v1beta/models"));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, openai::router(client)).await.unwrap() });
+    format!("http://{addr}")
+}
+
+fn request_line(request: &str) -> &str {
+    request.lines().next().unwrap()
+}
+
+fn json_body(request: &str) -> Value {
+    serde_json::from_str(request.split_once("\r\n\r\n").unwrap().1).unwrap()
+}
+
+fn json(body: Value) -> (&'static str, String) {
+    ("application/json", body.to_string())
+}
+
+#[tokio::test]
+async fn chat_completion_translates_tools_and_history() {
+    let (gemini, mut requests) = start_gemini(vec![json(json!({
+        "candidates": [{
+            "content": {"role": "model", "parts": [
+                {"functionCall": {"name": "get_weather", "args": {"city": "Oslo"}}}
+            ]},
+            "finishReason": "STOP"
+        }],
+        "usageMetadata": {
+            "promptTokenCount": 20,
+            "candidatesTokenCount": 5,
+            "thoughtsTokenCount": 3,
+            "totalTokenCount": 28
+        }
+    }))])
+    .await;
+    let proxy = start_proxy(gemini).await;
+
+    let request = json!({
+        "model": "gemini-2.5-flash",
+        "messages": [
+            {"role": "system", "content": "Be brief."},
+            {"role": "user", "content": [
+                {"type": "text", "text": "What is this?"},
+                {"type": "image_url", "image_url": {"url": "data:image/png;base64,iVBORw0KGgo="}}
+            ]},
+            {"role": "assistant", "content": null, "tool_calls": [
+                {"id": "call_1", "type": "function", "function": {"name": "lookup", "arguments": "{\"q\":\"x\"}"}}
+            ]},
+            {"role": "tool", "tool_call_id": "call_1", "content": "{\"answer\": 42}"},
+            {"role": "user", "content": "And the weather?"}
+        ],
+        "tools": [{"type": "function", "function": {
+            "name": "get_weather",
+            "description": "Weather of a city",
+            "parameters": {
+                "type": "object",
+                "properties": {"city": {"type": "string"}},
+                "additionalProperties": false
+            }
+        }}],
+        "tool_choice": "required",
+        "max_tokens": 100,
+        "stop": "END"
+    });
+    let response: Value = reqwest::Client::new()
+        .post(format!("{proxy}/v1/chat/completions"))
+        .json(&request)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let upstream = requests.recv().await.unwrap();
+    assert!(
+        request_line(&upstream)
+            .starts_with("POST /v1beta/models/gemini-2.5-flash:generateContent?key=key ")
+    );
+    let body = json_body(&upstream);
+    assert_eq!(body["systemInstruction"]["parts"][0]["text"], "Be brief.");
+    let contents = body["contents"].as_array().unwrap();
+    assert_eq!(contents.len(), 4);
+    assert_eq!(
+        contents[0]["parts"][1]["inlineData"]["mimeType"],
+        "image/png"
+    );
+    assert_eq!(contents[1]["role"], "model");
+    assert_eq!(contents[1]["parts"][0]["functionCall"]["id"], "call_1");
+    assert_eq!(contents[1]["parts"][0]["functionCall"]["args"]["q"], "x");
+    assert_eq!(contents[2]["role"], "user");
+    let function_response = &contents[2]["parts"][0]["functionResponse"];
+    assert_eq!(function_response["name"], "lookup");
+    assert_eq!(function_response["response"]["result"]["answer"], 42);
+    let declaration = &body["tools"][0]["functionDeclarations"][0];
+    assert_eq!(declaration["name"], "get_weather");
+    assert!(
+        declaration["parameters"]
+            .get("additionalProperties")
+            .is_none()
+    );
+    assert_eq!(body["toolConfig"]["functionCallingConfig"]["mode"], "ANY");
+    assert_eq!(body["generationConfig"]["maxOutputTokens"], 100);
+    assert_eq!(body["generationConfig"]["stopSequences"], json!(["END"]));
+
+    assert_eq!(response["object"], "chat.completion");
+    assert_eq!(response["model"], "gemini-2.5-flash");
+    let choice = &response["choices"][0];
+    assert_eq!(choice["finish_reason"], "tool_calls");
+    assert_eq!(choice["message"]["role"], "assistant");
+    assert_eq!(choice["message"]["content"], Value::Null);
+    let call = &choice["message"]["tool_calls"][0];
+    assert!(call["id"].as_str().unwrap().starts_with("gemini_call_"));
+    assert_eq!(call["function"]["name"], "get_weather");
+    let arguments: Value =
+        serde_json::from_str(call["function"]["arguments"].as_str().unwrap()).unwrap();
+    assert_eq!(arguments, json!({"city": "Oslo"}));
+    assert_eq!(
+        response["usage"],
+        json!({
+            "prompt_tokens": 20,
+            "completion_tokens": 8,
+            "total_tokens": 28,
+            "completion_tokens_details": {"reasoning_tokens": 3}
+        })
+    );
+}
+
+#[tokio::test]
+async fn chat_completion_json_mode() {
+    let (gemini, mut requests) = start_gemini(vec![json(json!({
+        "candidates": [{
+            "content": {"role": "model", "parts": [{"text": "{\"ok\":true}"}]},
+            "finishReason": "MAX_TOKENS"
+        }]
+    }))])
+    .await;
+    let proxy = start_proxy(gemini).await;
+
+    let response: Value = reqwest::Client::new()
+        .post(format!("{proxy}/v1/chat/completions"))
+        .json(&json!({
+            "model": "models/gemini-2.5-flash",
+            "messages": [{"role": "user", "content": "Answer in JSON"}],
+            "response_format": {"type": "json_schema", "json_schema": {
+                "name": "answer",
+                "schema": {"$schema": "x", "type": "object", "properties": {"ok": {"type": "boolean"}}}
+            }}
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let body = json_body(&requests.recv().await.unwrap());
+    let config = &body["generationConfig"];
+    assert_eq!(config["responseMimeType"], "application/json");
+    assert_eq!(
+        config["responseSchema"],
+        json!({"type": "object", "properties": {"ok": {"type": "boolean"}}})
+    );
+    assert_eq!(
+        response["choices"][0]["message"]["content"],
+        "{\"ok\":true}"
+    );
+    assert_eq!(response["choices"][0]["finish_reason"], "length");
+}
+
+#[tokio::test]
+async fn chat_completion_streams_sse_chunks() {
+    let sse = [
+        json!({"candidates": [{"content": {"role": "model", "parts": [{"text": "Hel"}]}}]}),
+        json!({
+            "candidates": [{"content": {"role": "model", "parts": [{"text": "lo"}]}, "finishReason": "STOP"}],
+            "usageMetadata": {"promptTokenCount": 3, "candidatesTokenCount": 2, "totalTokenCount": 5}
+        }),
+    ]
+    .iter()
+    .map(|chunk| format!("data: {chunk}\n\n"))
+    .collect::<String>();
+    let (gemini, mut requests) = start_gemini(vec![("text/event-stream", sse)]).await;
+    let proxy = start_proxy(gemini).await;
+
+    let response = reqwest::Client::new()
+        .post(format!("{proxy}/v1/chat/completions"))
+        .json(&json!({
+            "model": "gemini-2.5-flash",
+            "messages": [{"role": "user", "content": "hi"}],
+            "stream": true,
+            "stream_options": {"include_usage": true}
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+    let text = response.text().await.unwrap();
+
+    let upstream = requests.recv().await.unwrap();
+    assert!(request_line(&upstream).contains(":streamGenerateContent?alt=sse&key=key "));
+
+    let events: Vec<&str> = text
+        .lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .collect();
+    assert_eq!(events.last(), Some(&"[DONE]"));
+    let chunks: Vec<Value> = events[..events.len() - 1]
+        .iter()
+        .map(|event| serde_json::from_str(event).unwrap())
+        .collect();
+    assert_eq!(chunks.len(), 3);
+    assert_eq!(chunks[0]["object"], "chat.completion.chunk");
+    assert_eq!(chunks[0]["choices"][0]["delta"]["role"], "assistant");
+    assert_eq!(chunks[0]["choices"][0]["delta"]["content"], "Hel");
+    assert!(chunks[1]["choices"][0]["delta"].get("role").is_none());
+    assert_eq!(chunks[1]["choices"][0]["delta"]["content"], "lo");
+    assert_eq!(chunks[1]["choices"][0]["finish_reason"], "stop");
+    assert_eq!(chunks[0]["id"], chunks[2]["id"]);
+    assert_eq!(chunks[2]["choices"], json!([]));
+    assert_eq!(chunks[2]["usage"]["total_tokens"], 5);
+}
+
+#[tokio::test]
+async fn embeddings_embed_each_input() {
+    let (gemini, mut requests) = start_gemini(vec![
+        json(json!({"embedding": {"values": [1.0, 0.0]}})),
+        json(json!({"embedding": {"values": [0.0, 1.0]}})),
+    ])
+    .await;
+    let proxy = start_proxy(gemini).await;
+
+    let response: Value = reqwest::Client::new()
+        .post(format!("{proxy}/v1/embeddings"))
+        .json(&json!({
+            "model": "gemini-embedding-001",
+            "input": ["a", "b"],
+            "dimensions": 2
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    for text in ["a", "b"] {
+        let upstream = requests.recv().await.unwrap();
+        assert!(request_line(&upstream).contains("/gemini-embedding-001:embedContent"));
+        let body = json_body(&upstream);
+        assert_eq!(body["content"]["parts"][0]["text"], text);
+        assert_eq!(body["outputDimensionality"], 2);
+    }
+    assert_eq!(response["object"], "list");
+    assert_eq!(response["data"][1]["index"], 1);
+    assert_eq!(response["data"][1]["embedding"], json!([0.0, 1.0]));
+}
+
+#[tokio::test]
+async fn models_are_listed_without_prefix() {
+    let (gemini, _requests) = start_gemini(vec![json(json!({
+        "models": [{"name": "models/gemini-2.5-flash"}, {"name": "models/gemini-2.5-pro"}]
+    }))])
+    .await;
+    let proxy = start_proxy(gemini).await;
+
+    let response: Value = reqwest::get(format!("{proxy}/v1/models"))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let ids: Vec<&str> = response["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|model| model["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, ["gemini-2.5-flash", "gemini-2.5-pro"]);
+    assert_eq!(response["data"][0]["object"], "model");
+}
+
+#[tokio::test]
+async fn invalid_requests_get_openai_errors() {
+    let (gemini, _requests) = start_gemini(Vec::new()).await;
+    let proxy = start_proxy(gemini).await;
+
+    let response = reqwest::Client::new()
+        .post(format!("{proxy}/v1/chat/completions"))
+        .json(&json!({
+            "model": "gemini-2.5-flash",
+            "messages": [{"role": "tool", "tool_call_id": "missing", "content": "1"}]
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 400);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["error"]["type"], "invalid_request_error");
+    assert!(
+        body["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("missing")
+    );
+}
@@ -0,0 +1,311 @@
+use gemini::v1beta::{
+    Content, ExecutableCode, FunctionCall, FunctionResponse, FunctionResult, InlineData, Part,
+    PartData, Role,
+    openai::{
+        Message, MessageContent, Tool,
+        convert::{self, Error, SYNTHETIC_ID_PREFIX},
+    },
+    request::{FunctionDeclaration, SystemInstructionContent, SystemInstructionPart},
+};
+use serde_json::{Value, json};
+
+fn text(role: Role, text: &str) -> Content {
+    Content::new(role, vec![Part::new(PartData::Text(text.into()))])
+}
+
+fn call(id: Option<&str>, name: &str, args: Value) -> Part {
+    Part::new(PartData::FunctionCall(FunctionCall::new(
+        id.map(str::to_string),
+        name,
+        args,
+    )))
+}
+
+fn response(id: Option<&str>, name: &str, result: Value) -> Part {
+    Part::new(PartData::FunctionResponse(FunctionResponse::new(
+        id.map(str::to_string),
+        name,
+        FunctionResult::new(result),
+    )))
+}
+
+fn messages_json(messages: &[Message]) -> Value {
+    serde_json::to_value(messages).unwrap()
+}
+
+#[test]
+fn calls_without_ids_pair_with_responses_by_name_and_order() {
+    let contents = vec![
+        text(Role::User, "Weather in Oslo and Bergen?"),
+        Content::new(
+            Role::Model,
+            vec![
+                call(None, "weather", json!({"city": "Oslo"})),
+                call(None, "weather", json!({"city": "Bergen"})),
+            ],
+        ),
+        Content::new(
+            Role::User,
+            vec![
+                response(None, "weather", json!("rain")),
+                response(None, "weather", json!({"temp": 12})),
+            ],
+        ),
+    ];
+
+    let messages = convert::to_messages(None, &contents).unwrap();
+    let value = messages_json(&messages);
+    let calls = value[1]["tool_calls"].as_array().unwrap();
+    let first = calls[0]["id"].as_str().unwrap();
+    let second = calls[1]["id"].as_str().unwrap();
+    assert!(first.starts_with(SYNTHETIC_ID_PREFIX));
+    assert_ne!(first, second);
+    assert_eq!(calls[1]["function"]["arguments"], r#"{"city":"Bergen"}"#);
+    assert_eq!(value[1]["content"], Value::Null);
+    assert_eq!(
+        value[2],
+        json!({"role": "tool", "tool_call_id": first, "content": "rain"})
+    );
+    assert_eq!(
+        value[3],
+        json!({"role": "tool", "tool_call_id": second, "content": "{\"temp\":12}"})
+    );
+
+    // Back in Gemini form, the made-up ids are gone again.
+    let conversation = convert::to_contents(messages).unwrap();
+    let back = serde_json::to_value(&conversation.contents).unwrap();
+    assert_eq!(back, serde_json::to_value(&contents).unwrap());
+}
+
+#[test]
+fn gemini_ids_become_tool_call_ids() {
+    let contents = vec![
+        Content::new(Role::Model, vec![call(Some("abc"), "lookup", json!({}))]),
+        Content::new(Role::User, vec![response(Some("abc"), "lookup", json!(1))]),
+    ];
+
+    let messages = convert::to_messages(None, &contents).unwrap();
+    let value = messages_json(&messages);
+    assert_eq!(value[0]["tool_calls"][0]["id"], "abc");
+    assert_eq!(value[1]["tool_call_id"], "abc");
+
+    let conversation = convert::to_contents(messages).unwrap();
+    let back = serde_json::to_value(&conversation.contents).unwrap();
+    assert_eq!(back[0]["parts"][0]["functionCall"]["id"], "abc");
+    assert_eq!(back[1]["parts"][0]["functionResponse"]["id"], "abc");
+    assert_eq!(
+        back[1]["parts"][0]["functionResponse"]["response"]["result"],
+        1
+    );
+}
+
+#[test]
+fn thought_signatures_travel_in_extra_content() {
+    let contents = vec![Content::new(
+        Role::Model,
+        vec![call(None, "lookup", json!({})).with_thought_signature("c2ln")],
+    )];
+
+    let messages = convert::to_messages(None, &contents).unwrap();
+    let value = messages_json(&messages);
+    assert_eq!(
+        value[0]["tool_calls"][0]["extra_content"],
+        json!({"google": {"thought_signature": "c2ln"}})
+    );
+
+    let conversation = convert::to_contents(messages).unwrap();
+    let part = &conversation.contents[0].parts[0];
+    assert_eq!(part.thought_signature.as_deref(), Some("c2ln"));
+}
+
+#[test]
+fn openai_history_converts_to_contents() {
+    let messages: Vec<Message> = serde_json::from_value(json!([
+        {"role": "system", "content": "Be brief."},
+        {"role": "developer", "content": "Use metric units."},
+        {"role": "user", "content": [
+            {"type": "text", "text": "What is this?"},
+            {"type": "image_url", "image_url": {"url": "data:image/png;base64,iVBORw0KGgo="}},
+            {"type": "image_url", "image_url": {"url": "https://example.com/cat.webp?size=2"}}
+        ]},
+        {"role": "assistant", "content": "Checking.", "tool_calls": [
+            {"id": "call_1", "type": "function", "function": {"name": "a", "arguments": ""}},
+            {"id": "call_2", "type": "function", "function": {"name": "b", "arguments": "{\"x\":1}"}}
+        ]},
+        {"role": "tool", "tool_call_id": "call_2", "content": "{\"ok\": true}"},
+        {"role": "tool", "tool_call_id": "call_1", "content": "done"},
+        {"role": "assistant", "content": "A cat."}
+    ]))
+    .unwrap();
+
+    let conversation = convert::to_contents(messages).unwrap();
+    assert_eq!(
+        conversation.system_instruction.unwrap().text(),
+        "Be brief.\n\nUse metric units."
+    );
+    let value = serde_json::to_value(&conversation.contents).unwrap();
+    assert_eq!(value.as_array().unwrap().len(), 4);
+    assert_eq!(
+        value[0]["parts"][1]["inlineData"],
+        json!({"mimeType": "image/png", "data": "iVBORw0KGgo="})
+    );
+    assert_eq!(
+        value[0]["parts"][2]["fileData"],
+        json!({"mimeType": "image/webp", "fileUri": "https://example.com/cat.webp?size=2"})
+    );
+    assert_eq!(value[1]["role"], "model");
+    assert_eq!(value[1]["parts"][0]["text"], "Checking.");
+    assert_eq!(value[1]["parts"][1]["functionCall"]["args"], json!({}));
+    let responses = value[2]["parts"].as_array().unwrap();
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0]["functionResponse"]["name"], "b");
+    assert_eq!(responses[0]["functionResponse"]["id"], "call_2");
+    assert_eq!(
+        responses[0]["functionResponse"]["response"]["result"]["ok"],
+        true
+    );
+    assert_eq!(
+        responses[1]["functionResponse"]["response"]["result"],
+        "done"
+    );
+    assert_eq!(value[3]["parts"][0]["text"], "A cat.");
+}
+
+#[test]
+fn gemini_contents_convert_to_messages() {
+    let system =
+        SystemInstructionContent::new(vec![SystemInstructionPart::new("Be brief.".into())]);
+    let mut thought = Part::new(PartData::Text("Hmm.".into()));
+    thought.thought = Some(true);
+    let contents = vec![
+        Content::new(
+            Role::User,
+            vec![
+                Part::new(PartData::Text("Describe".into())),
+                Part::new(PartData::InlineData(InlineData::new("image/jpeg", "/9j/"))),
+            ],
+        ),
+        Content::new(
+            Role::Model,
+            vec![thought, Part::new(PartData::Text("A dog.".into()))],
+        ),
+        text(Role::User, "Thanks"),
+    ];
+
+    let messages = convert::to_messages(Some(&system), &contents).unwrap();
+    assert_eq!(
+        messages_json(&messages),
+        json!([
+            {"role": "system", "content": "Be brief."},
+            {"role": "user", "content": [
+                {"type": "text", "text": "Describe"},
+                {"type": "image_url", "image_url": {"url": "data:image/jpeg;base64,/9j/"}}
+            ]},
+            {"role": "assistant", "content": "A dog."},
+            {"role": "user", "content": "Thanks"}
+        ])
+    );
+}
+
+#[test]
+fn conversion_errors() {
+    let unknown: Vec<Message> = serde_json::from_value(json!([
+        {"role": "tool", "tool_call_id": "nope", "content": "1"}
+    ]))
+    .unwrap();
+    assert!(matches!(
+        convert::to_contents(unknown),
+        Err(Error::UnknownToolCall(id)) if id == "nope"
+    ));
+
+    let bad_arguments: Vec<Message> = serde_json::from_value(json!([
+        {"role": "assistant", "content": null, "tool_calls": [
+            {"id": "c", "type": "function", "function": {"name": "a", "arguments": "[1]"}}
+        ]}
+    ]))
+    .unwrap();
+    assert!(matches!(
+        convert::to_contents(bad_arguments),
+        Err(Error::InvalidArguments { .. })
+    ));
+
+    let unmatched = vec![Content::new(
+        Role::User,
+        vec![response(None, "weather", json!(1))],
+    )];
+    assert!(matches!(
+        convert::to_messages(None, &unmatched),
+        Err(Error::UnmatchedFunctionResponse(name)) if name == "weather"
+    ));
+
+    let code = vec![Content::new(
+        Role::Model,
+        vec![Part::new(PartData::ExecutableCode(ExecutableCode::new(
+            "PYTHON", "print(1)",
+        )))],
+    )];
+    assert!(matches!(
+        convert::to_messages(None, &code),
+        Err(Error::Unsupported("executable code"))
+    ));
+}
+
+#[test]
+fn tools_convert_both_ways() {
+    let tool: Tool = serde_json::from_value(json!({
+        "type": "function",
+        "function": {
+            "name": "weather",
+            "description": "Weather of a city",
+            "strict": true,
+            "parameters": {
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "properties": {
+                    "city": {"type": "string"},
+                    "days": {"type": "array", "items": {"type": "object", "additionalProperties": false}}
+                },
+                "additionalProperties": false
+            }
+        }
+    }))
+    .unwrap();
+
+    let declaration = FunctionDeclaration::from(tool);
+    assert_eq!(declaration.name(), "weather");
+    assert_eq!(
+        serde_json::to_value(&declaration).unwrap(),
+        json!({
+            "name": "weather",
+            "description": "Weather of a city",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "city": {"type": "string"},
+                    "days": {"type": "array", "items": {"type": "object"}}
+                }
+            }
+        })
+    );
+
+    let tool = Tool::from(&FunctionDeclaration::new("ping", ""));
+    assert_eq!(
+        serde_json::to_value(&tool).unwrap(),
+        json!({"type": "function", "function": {"name": "ping"}})
+    );
+}
+
+#[test]
+fn assistant_text_only_message_content() {
+    let messages = convert::to_messages(None, &[text(Role::Model, "Hi")]).unwrap();
+    match &messages[0] {
+        Message::Assistant {
+            content: Some(MessageContent::Text(text)),
+            tool_calls,
+        } => {
+            assert_eq!(text, "Hi");
+            assert!(tool_calls.is_empty());
+        }
+        other => panic!("unexpected message: {other:?}"),
+    }
+}
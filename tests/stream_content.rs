@@ -1,4 +1,10 @@
-use gemini::v1beta::{self, Content, Part, PartData, Role, request, rest::Client};
+#![cfg(feature = "rest")]
+
+use gemini::v1beta::{
+    Content, Part, PartData, Role, request,
+    response::Blocked,
+    rest::{Client, Error},
+};
 use std::net::SocketAddr;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpListener;
@@ -50,6 +56,36 @@ async fn stream_content_parses_sse() {
     assert!(items[1].usage_metadata.is_some());
 }
 
+#[tokio::test]
+async fn strict_stream_errors_on_the_blocked_chunk() {
+    let (addr, handle) = start_server(vec![
+        b"data: {\"candidates\": [{\"content\": {\"role\": \"model\", \"parts\": [{\"text\": \"Sure\"}]}}]}\n\n",
+        b"data: {\"candidates\": [{\"finishReason\": \"SAFETY\"}]}\n\n",
+    ])
+    .await;
+    let client = Client::new("key", "test")
+        .with_api_base(format!("http://{}/v1beta/models", addr))
+        .with_strict(true);
+    let mut stream = client
+        .stream_content(request::Request::new(vec![]))
+        .await
+        .expect("stream");
+    let first = stream.next().await.expect("item").unwrap();
+    let second = stream.next().await.expect("item");
+    handle.abort();
+    assert_eq!(first.text().as_deref(), Some("Sure"));
+    let Err(Error::Blocked(blocked)) = second else {
+        panic!("expected a blocked error, got {second:?}");
+    };
+    let Blocked::Candidate { partial, .. } = *blocked else {
+        panic!("expected a stopped candidate, got {blocked:?}");
+    };
+    assert_eq!(
+        partial.and_then(|partial| partial.text()).as_deref(),
+        Some("Sure")
+    );
+}
+
 #[tokio::test]
 async fn stream_content_invalid_json() {
     let (addr, handle) = start_server(vec![b"data: invalid\n\n"]).await;
@@ -60,3 +96,28 @@ async fn stream_content_invalid_json() {
     handle.abort();
     assert!(res.is_err());
 }
+
+#[tokio::test]
+async fn stream_content_split_separates_thoughts() {
+    let (addr, handle) = start_server(vec![
+        b"data: {\"candidates\": [{\"content\": {\"role\": \"model\", \"parts\": [{\"text\": \"Considering\", \"thought\": true}]}}]}\n\n",
+        b"data: {\"candidates\": [{\"content\": {\"role\": \"model\", \"parts\": [{\"text\": \"42\", \"thoughtSignature\": \"c2ln\"}]}}]}\n\n",
+    ])
+    .await;
+    let client = Client::new("key", "test").with_api_base(format!("http://{}/v1beta/models", addr));
+    let mut stream = client
+        .stream_content_split(request::Request::new(vec![]))
+        .await
+        .expect("stream");
+
+    let first = stream.next().await.unwrap().unwrap();
+    assert_eq!(first.thought_text().as_deref(), Some("Considering"));
+    assert_eq!(first.answer.text(), None);
+
+    let second = stream.next().await.unwrap().unwrap();
+    handle.abort();
+    assert!(second.thoughts.is_empty());
+    assert_eq!(second.answer.text().as_deref(), Some("42"));
+    let part = &second.answer.candidates[0].content.as_ref().unwrap().parts[0];
+    assert_eq!(part.thought_signature.as_deref(), Some("c2ln"));
+}
@@ -0,0 +1,279 @@
+#![cfg(feature = "rest")]
+
+use gemini::v1beta::{
+    Content, Part, PartData, Role,
+    request::{GenerationConfig, Request},
+    rest::Client,
+};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio_stream::StreamExt;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+
+type Fields = HashMap<String, String>;
+
+#[derive(Default)]
+struct Captured {
+    spans: HashMap<u64, Fields>,
+    events: Vec<Fields>,
+}
+
+/// Records the fields of every span and event.
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Captured>>);
+
+struct Visitor<'a>(&'a mut Fields);
+
+impl Visit for Visitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(
+            field.name().to_string(),
+            format!("{value:?}").trim_matches('"').to_string(),
+        );
+    }
+}
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for Capture {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, _: Context<'_, S>) {
+        let mut fields = Fields::new();
+        attrs.record(&mut Visitor(&mut fields));
+        self.0.lock().unwrap().spans.insert(id.into_u64(), fields);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, _: Context<'_, S>) {
+        let mut captured = self.0.lock().unwrap();
+        let fields = captured.spans.entry(id.into_u64()).or_default();
+        values.record(&mut Visitor(fields));
+    }
+
+    fn on_event(&self, event: &tracing::Event<'_>, _: Context<'_, S>) {
+        let mut fields = Fields::new();
+        event.record(&mut Visitor(&mut fields));
+        self.0.lock().unwrap().events.push(fields);
+    }
+}
+
+impl Capture {
+    fn gen_ai_span(&self) -> Fields {
+        let captured = self.0.lock().unwrap();
+        captured
+            .spans
+            .values()
+            .find(|fields| fields.contains_key("gen_ai.operation.name"))
+            .cloned()
+            .expect("gen_ai span")
+    }
+
+    fn metric(&self, name: &str) -> Vec<Fields> {
+        let captured = self.0.lock().unwrap();
+        let key = format!("histogram.{name}");
+        captured
+            .events
+            .iter()
+            .filter(|fields| fields.contains_key(&key))
+            .cloned()
+            .collect()
+    }
+}
+
+async fn start_server(content_type: &'static str, body: &'static str) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = [0u8; 4096];
+        let _ = stream.read(&mut buf).await;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+    });
+    addr
+}
+
+fn request() -> Request {
+    Request::new(vec![Content::new(
+        Role::User,
+        vec![Part::new(PartData::Text("secret prompt".into()))],
+    )])
+    .with_generation_config(
+        GenerationConfig::new()
+            .with_temperature(0.5)
+            .with_max_output_tokens(64),
+    )
+}
+
+const RESPONSE: &str = r#"{"candidates": [{"content": {"role": "model", "parts": [{"text": "secret answer"}]}, "finishReason": "STOP"}], "usageMetadata": {"promptTokenCount": 3, "candidatesTokenCount": 2, "totalTokenCount": 5}}"#;
+
+#[tokio::test]
+async fn generate_content_records_gen_ai_span_and_metrics() {
+    let capture = Capture::default();
+    let _guard =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+    let addr = start_server("application/json", RESPONSE).await;
+    let client = Client::new("key", "models/test")
+        .with_api_base(format!("http://{}/v1beta/models", addr))
+        .with_capture_content(false);
+
+    client.generate_content(request()).await.unwrap();
+
+    let span = capture.gen_ai_span();
+    assert_eq!(span["otel.name"], "generate_content test");
+    assert_eq!(span["gen_ai.system"], "gemini");
+    assert_eq!(span["gen_ai.request.model"], "test");
+    assert_eq!(span["gen_ai.request.temperature"], "0.5");
+    assert_eq!(span["gen_ai.request.max_tokens"], "64");
+    assert_eq!(span["gen_ai.response.finish_reasons"], r#"[\"stop\"]"#);
+    assert_eq!(span["gen_ai.usage.input_tokens"], "3");
+    assert_eq!(span["gen_ai.usage.output_tokens"], "2");
+    assert!(!span.contains_key("gen_ai.input.messages"));
+    assert!(!span.contains_key("gen_ai.output.messages"));
+
+    let usage = capture.metric("gen_ai.client.token.usage");
+    assert_eq!(usage.len(), 2);
+    assert_eq!(usage[0]["gen_ai.token.type"], "input");
+    assert_eq!(capture.metric("gen_ai.client.operation.duration").len(), 1);
+}
+
+#[tokio::test]
+async fn stream_content_records_time_to_first_token_and_captured_content() {
+    let capture = Capture::default();
+    let _guard =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+    let body: &'static str = Box::leak(format!("data: {RESPONSE}\n\n").into_boxed_str());
+    let addr = start_server("text/event-stream", body).await;
+    let client = Client::new("key", "test")
+        .with_api_base(format!("http://{}/v1beta/models", addr))
+        .with_capture_content(true);
+
+    let mut stream = client.stream_content(request()).await.unwrap();
+    while let Some(chunk) = stream.next().await {
+        chunk.unwrap();
+    }
+
+    let span = capture.gen_ai_span();
+    assert!(span["gen_ai.input.messages"].contains("secret prompt"));
+    assert!(span["gen_ai.output.messages"].contains("secret answer"));
+    assert_eq!(span["gen_ai.usage.output_tokens"], "2");
+    assert_eq!(capture.metric("gen_ai.server.time_to_first_token").len(), 1);
+    assert_eq!(capture.metric("gen_ai.client.operation.duration").len(), 1);
+}
+
+const BLOCKED: &str = r#"{"promptFeedback": {"blockReason": "SAFETY"}}"#;
+
+#[tokio::test]
+async fn blocked_responses_record_an_error_type_in_strict_mode() {
+    let capture = Capture::default();
+    let _guard =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+    let addr = start_server("application/json", BLOCKED).await;
+    let client = Client::new("key", "test")
+        .with_api_base(format!("http://{}/v1beta/models", addr))
+        .with_strict(true);
+
+    client.generate_content(request()).await.unwrap_err();
+
+    assert_eq!(capture.gen_ai_span()["error.type"], "blocked");
+    let duration = capture.metric("gen_ai.client.operation.duration");
+    assert_eq!(duration[0]["error.type"], "blocked");
+}
+
+#[tokio::test]
+async fn blocked_streams_record_an_error_type_in_strict_mode() {
+    let capture = Capture::default();
+    let _guard =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+    let body: &'static str = Box::leak(format!("data: {BLOCKED}\n\n").into_boxed_str());
+    let addr = start_server("text/event-stream", body).await;
+    let client = Client::new("key", "test")
+        .with_api_base(format!("http://{}/v1beta/models", addr))
+        .with_strict(true);
+
+    let chunks: Vec<_> = client
+        .stream_content(request())
+        .await
+        .unwrap()
+        .collect()
+        .await;
+    assert!(chunks[0].is_err());
+
+    assert_eq!(capture.gen_ai_span()["error.type"], "blocked");
+}
+
+#[cfg(feature = "live")]
+#[tokio::test]
+async fn live_sessions_record_captured_content() {
+    use futures_util::SinkExt;
+    use gemini::v1beta::live::{self, ClientContent, ClientMessage, ConnectOptions, Setup};
+    use tokio_tungstenite::tungstenite::Message;
+
+    let capture = Capture::default();
+    let _guard =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("ws://{}/ws", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        ws.next().await.unwrap().unwrap();
+        ws.send(Message::text(r#"{"setupComplete": {}}"#))
+            .await
+            .unwrap();
+        ws.next().await.unwrap().unwrap();
+        for frame in [
+            r#"{"serverContent": {"modelTurn": {"role": "model", "parts": [{"text": "secret "}]}}}"#,
+            r#"{"serverContent": {"modelTurn": {"role": "model", "parts": [{"text": "answer"}]}, "turnComplete": true}}"#,
+        ] {
+            ws.send(Message::text(frame)).await.unwrap();
+        }
+        while let Some(Ok(_)) = ws.next().await {}
+    });
+
+    let options = ConnectOptions::new()
+        .with_endpoint(endpoint)
+        .with_capture_content(true);
+    let (client, mut messages) =
+        live::Client::connect_with_options("key", Setup::new("models/test"), options)
+            .await
+            .unwrap();
+    let turn = live::Content::new(
+        live::Role::User,
+        vec![live::Part::new(live::PartData::Text(
+            "secret prompt".into(),
+        ))],
+    );
+    client
+        .call(ClientMessage::ClientContent(
+            ClientContent::new(vec![turn]).is_turn_completed(true),
+        ))
+        .unwrap();
+    while let Some(message) = messages.next().await {
+        if let live::ServerMessage::ServerContent { server_content, .. } = message.unwrap()
+            && server_content.turn_complete
+        {
+            break;
+        }
+    }
+    client.disconnect(None).unwrap();
+    while messages.next().await.is_some() {}
+
+    let span = capture.gen_ai_span();
+    assert_eq!(span["otel.name"], "chat test");
+    assert!(span["gen_ai.input.messages"].contains("secret prompt"));
+    // The streamed chunks of a turn make up one message.
+    let output: serde_json::Value =
+        serde_json::from_str(&span["gen_ai.output.messages"].replace("\\\"", "\"")).unwrap();
+    assert_eq!(
+        output,
+        serde_json::json!([{"role": "model", "parts": [
+            {"type": "text", "content": "secret "},
+            {"type": "text", "content": "answer"}
+        ]}])
+    );
+}
@@ -0,0 +1,274 @@
+#![cfg(feature = "twilio")]
+
+use base64::{Engine as _, engine::general_purpose};
+use futures_util::{SinkExt, StreamExt};
+use gemini::v1beta::audio::G711;
+use gemini::v1beta::live::{ConnectOptions, Setup};
+use gemini::v1beta::twilio::{Bridge, Error, InboundMessage, OutboundMessage};
+use serde_json::{Value, json};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+type Twilio = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Audio chunk of 100 ms at 24 kHz, as the model sends it.
+fn model_audio() -> String {
+    let samples: Vec<u8> = (0..2400i16)
+        .flat_map(|n| ((n % 100 - 50) * 200).to_le_bytes())
+        .collect();
+    let data = general_purpose::STANDARD.encode(samples);
+    json!({"serverContent": {"modelTurn": {"parts": [
+        {"inlineData": {"mimeType": "audio/pcm;rate=24000", "data": data}}
+    ]}}})
+    .to_string()
+}
+
+/// Fake Live API: after each batch of frames, wait for one audio input.
+/// Reports the client messages once the bridge hangs up.
+async fn start_live_server(batches: Vec<Vec<String>>) -> (String, oneshot::Receiver<Vec<Value>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("ws://{}/ws", listener.local_addr().unwrap());
+    let (tx, rx) = oneshot::channel();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        ws.next().await.unwrap().unwrap();
+        ws.send(Message::text(r#"{"setupComplete": {}}"#))
+            .await
+            .unwrap();
+        let mut received = Vec::new();
+        for batch in batches {
+            let text = ws.next().await.unwrap().unwrap().into_text().unwrap();
+            received.push(serde_json::from_str(&text).unwrap());
+            for frame in batch {
+                ws.send(Message::text(frame)).await.unwrap();
+            }
+        }
+        while let Some(Ok(Message::Text(text))) = ws.next().await {
+            received.push(serde_json::from_str(&text).unwrap());
+        }
+        tx.send(received).unwrap();
+    });
+    (endpoint, rx)
+}
+
+/// Run a bridge on a local port and connect to it as Twilio would.
+async fn start_bridge(endpoint: String) -> (Twilio, JoinHandle<Result<(), Error>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}/media", listener.local_addr().unwrap());
+    let options = ConnectOptions::new()
+        .with_endpoint(endpoint)
+        .with_reconnect_on_go_away(false);
+    let bridge = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        Bridge::new("key", Setup::new("models/gemini-live-2.5-flash"))
+            .with_options(options)
+            .accept(stream)
+            .await
+    });
+    let (twilio, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+    (twilio, bridge)
+}
+
+fn start(encoding: &str) -> Message {
+    Message::text(
+        json!({
+            "event": "start",
+            "sequenceNumber": "1",
+            "streamSid": "MZ1",
+            "start": {
+                "accountSid": "AC1",
+                "streamSid": "MZ1",
+                "callSid": "CA1",
+                "tracks": ["inbound"],
+                "customParameters": {"caller": "+15550100"},
+                "mediaFormat": {"encoding": encoding, "sampleRate": 8000, "channels": 1}
+            }
+        })
+        .to_string(),
+    )
+}
+
+/// 20 ms of a 400 Hz tone from the caller.
+fn caller_media() -> Message {
+    let tone: Vec<i16> = (0..160)
+        .map(|n| ((n as f64 * 0.1 * std::f64::consts::PI).sin() * 8000.0) as i16)
+        .collect();
+    let payload = general_purpose::STANDARD.encode(G711::MuLaw.encode(&tone));
+    Message::text(
+        json!({
+            "event": "media",
+            "streamSid": "MZ1",
+            "media": {"track": "inbound", "chunk": "1", "timestamp": "0", "payload": payload}
+        })
+        .to_string(),
+    )
+}
+
+fn event(name: &str, body: Value) -> Message {
+    let mut message = json!({"event": name, "streamSid": "MZ1"});
+    message
+        .as_object_mut()
+        .unwrap()
+        .extend(body.as_object().unwrap().clone());
+    Message::text(message.to_string())
+}
+
+async fn next_event(twilio: &mut Twilio) -> Value {
+    let text = twilio.next().await.unwrap().unwrap().into_text().unwrap();
+    serde_json::from_str(&text).unwrap()
+}
+
+#[tokio::test]
+async fn audio_is_relayed_with_marks_and_barge_in() {
+    let (endpoint, live) = start_live_server(vec![
+        vec![
+            model_audio(),
+            r#"{"serverContent": {"interrupted": true}}"#.into(),
+        ],
+        vec![
+            r#"{"serverContent": {"interrupted": true}}"#.into(),
+            model_audio(),
+            r#"{"serverContent": {"turnComplete": true}}"#.into(),
+        ],
+    ])
+    .await;
+    let (mut twilio, bridge) = start_bridge(endpoint).await;
+
+    twilio
+        .send(event(
+            "connected",
+            json!({"protocol": "Call", "version": "1.0.0"}),
+        ))
+        .await
+        .unwrap();
+    twilio.send(start("audio/x-mulaw")).await.unwrap();
+    twilio.send(caller_media()).await.unwrap();
+
+    // The model's audio is played, then cleared when the caller barges in.
+    let media = next_event(&mut twilio).await;
+    assert_eq!(media["event"], "media");
+    assert_eq!(media["streamSid"], "MZ1");
+    let payload = general_purpose::STANDARD
+        .decode(media["media"]["payload"].as_str().unwrap())
+        .unwrap();
+    assert!(
+        payload.len() > 700 && payload.len() <= 800,
+        "{}",
+        payload.len()
+    );
+    assert_eq!(
+        next_event(&mut twilio).await,
+        json!({"event": "mark", "streamSid": "MZ1", "mark": {"name": "1"}})
+    );
+    assert_eq!(
+        next_event(&mut twilio).await,
+        json!({"event": "clear", "streamSid": "MZ1"})
+    );
+
+    // Once everything has played, an interruption has nothing to clear.
+    twilio
+        .send(event("mark", json!({"mark": {"name": "1"}})))
+        .await
+        .unwrap();
+    twilio.send(caller_media()).await.unwrap();
+    let kinds: Vec<_> = [
+        next_event(&mut twilio).await,
+        next_event(&mut twilio).await,
+        next_event(&mut twilio).await,
+        next_event(&mut twilio).await,
+    ]
+    .iter()
+    .map(|event| match event["event"].as_str().unwrap() {
+        "mark" => format!("mark {}", event["mark"]["name"].as_str().unwrap()),
+        other => other.to_string(),
+    })
+    .collect();
+    // The rest of the turn is flushed on turnComplete.
+    assert_eq!(kinds, ["media", "mark 2", "media", "mark 3"]);
+
+    twilio
+        .send(event(
+            "stop",
+            json!({"stop": {"accountSid": "AC1", "callSid": "CA1"}}),
+        ))
+        .await
+        .unwrap();
+    bridge.await.unwrap().unwrap();
+
+    let received = live.await.unwrap();
+    assert_eq!(received.len(), 2);
+    for message in &received {
+        let audio = &message["realtimeInput"]["audio"];
+        assert_eq!(audio["mimeType"], "audio/pcm;rate=16000");
+        let bytes = general_purpose::STANDARD
+            .decode(audio["data"].as_str().unwrap())
+            .unwrap();
+        // 20 ms at 16 kHz, less the resampler's delay on the first chunk.
+        assert!(bytes.len() > 400 && bytes.len() <= 640, "{}", bytes.len());
+    }
+}
+
+#[tokio::test]
+async fn streams_that_never_start_properly_are_errors() {
+    let (mut twilio, bridge) = start_bridge("ws://127.0.0.1:1/ws".into()).await;
+    twilio
+        .send(event(
+            "connected",
+            json!({"protocol": "Call", "version": "1.0.0"}),
+        ))
+        .await
+        .unwrap();
+    twilio.close(None).await.unwrap();
+    assert!(matches!(bridge.await.unwrap(), Err(Error::NotStarted)));
+
+    let (mut twilio, bridge) = start_bridge("ws://127.0.0.1:1/ws".into()).await;
+    twilio.send(start("audio/l16")).await.unwrap();
+    assert!(matches!(
+        bridge.await.unwrap(),
+        Err(Error::UnsupportedFormat { encoding, sample_rate: 8000, channels: 1 }) if encoding == "audio/l16"
+    ));
+}
+
+#[test]
+fn messages_match_the_twilio_wire_format() {
+    let message: InboundMessage = serde_json::from_value(json!({
+        "event": "start",
+        "sequenceNumber": "1",
+        "streamSid": "MZ1",
+        "start": {
+            "accountSid": "AC1",
+            "streamSid": "MZ1",
+            "callSid": "CA1",
+            "tracks": ["inbound"],
+            "customParameters": {"caller": "+15550100"},
+            "mediaFormat": {"encoding": "audio/x-mulaw", "sampleRate": 8000, "channels": 1}
+        }
+    }))
+    .unwrap();
+    let InboundMessage::Start { start, .. } = message else {
+        panic!("{message:?}");
+    };
+    assert_eq!(start.call_sid, "CA1");
+    assert_eq!(start.custom_parameters["caller"], "+15550100");
+
+    let message: InboundMessage = serde_json::from_value(json!({
+        "event": "dtmf",
+        "streamSid": "MZ1",
+        "sequenceNumber": "5",
+        "dtmf": {"track": "inbound_track", "digit": "1"}
+    }))
+    .unwrap();
+    assert!(matches!(message, InboundMessage::Dtmf { dtmf, .. } if dtmf.digit == "1"));
+    let message: InboundMessage =
+        serde_json::from_value(json!({"event": "somethingNew", "streamSid": "MZ1"})).unwrap();
+    assert!(matches!(message, InboundMessage::Unknown));
+
+    assert_eq!(
+        serde_json::to_value(OutboundMessage::media("MZ1", &[0xFF, 0x7F])).unwrap(),
+        json!({"event": "media", "streamSid": "MZ1", "media": {"payload": "/38="}})
+    );
+}
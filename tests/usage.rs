@@ -0,0 +1,95 @@
+use gemini::v1beta::{
+    live,
+    response::{Modality, UsageMetadata},
+    usage::{Price, PriceTable, Usage, UsageTracker},
+};
+use serde_json::json;
+
+fn rest_usage() -> UsageMetadata {
+    serde_json::from_value(json!({
+        "promptTokenCount": 1000,
+        "cachedContentTokenCount": 400,
+        "candidatesTokenCount": 200,
+        "thoughtsTokenCount": 50,
+        "toolUsePromptTokenCount": 100,
+        "totalTokenCount": 1350,
+        "promptTokensDetails": [
+            {"modality": "TEXT", "tokenCount": 600},
+            {"modality": "AUDIO", "tokenCount": 400}
+        ],
+        "cacheTokensDetails": [{"modality": "TEXT", "tokenCount": 400}],
+        "candidatesTokensDetails": [{"modality": "TEXT", "tokenCount": 200}]
+    }))
+    .unwrap()
+}
+
+#[test]
+fn usage_breaks_down_rest_metadata() {
+    let usage = Usage::from(&rest_usage());
+    assert_eq!(usage.input_tokens, 1000);
+    assert_eq!(usage.uncached_input_tokens(), 600);
+    assert_eq!(usage.thinking_tokens, 50);
+    assert_eq!(usage.tool_use_tokens, 100);
+    assert_eq!(usage.input_by_modality[&Modality::Audio], 400);
+    assert_eq!(usage.cached_by_modality[&Modality::Text], 400);
+}
+
+#[test]
+fn price_table_costs_per_model() {
+    let prices = PriceTable::new()
+        .with_price(
+            "gemini-2.5-flash",
+            Price::new(0.30, 2.50)
+                .with_cached_input(0.075)
+                .with_audio_input(1.00),
+        )
+        .with_price("gemini-2.5-flash-lite", Price::new(0.10, 0.40));
+    assert_eq!(
+        prices.price("models/gemini-2.5-flash-preview-05-20"),
+        prices.price("gemini-2.5-flash")
+    );
+    assert_eq!(
+        prices.price("gemini-2.5-flash-lite-001").unwrap().input,
+        0.10
+    );
+    assert!(prices.price("gemini-2.5-pro").is_none());
+
+    let tracker = UsageTracker::new();
+    tracker.record("models/gemini-2.5-flash", &rest_usage());
+    tracker.record("gemini-2.5-pro", &rest_usage());
+
+    let snapshot = tracker.snapshot();
+    assert_eq!(snapshot.by_model["gemini-2.5-flash"].responses, 1);
+    assert_eq!(snapshot.total().input_tokens, 2000);
+
+    // 400 cached at 0.075, 400 uncached audio at 1.00, 200 uncached text and
+    // 100 tool-use tokens at 0.30, 250 output and thinking tokens at 2.50.
+    let expected = (400.0 * 0.075 + 400.0 * 1.00 + 300.0 * 0.30 + 250.0 * 2.50) / 1e6;
+    let costs = snapshot.cost_by_model(&prices);
+    assert!((costs["gemini-2.5-flash"].unwrap() - expected).abs() < 1e-12);
+    assert_eq!(costs["gemini-2.5-pro"], None);
+    assert!((snapshot.cost(&prices) - expected).abs() < 1e-12);
+}
+
+#[test]
+fn tracker_records_live_usage_and_merges_snapshots() {
+    let usage: live::UsageMetadata = serde_json::from_value(json!({
+        "promptTokenCount": 10,
+        "responseTokenCount": 20,
+        "totalTokenCount": 30,
+        "responseTokensDetails": [{"modality": "AUDIO", "tokenCount": 20}]
+    }))
+    .unwrap();
+    let session = UsageTracker::new();
+    session.record("models/gemini-live-2.5-flash", &usage);
+    session.record("models/gemini-live-2.5-flash", &usage);
+
+    let feature = UsageTracker::new();
+    feature.add(&session.snapshot());
+    feature.add(&session.take());
+
+    let total = feature.snapshot().total();
+    assert_eq!(total.responses, 4);
+    assert_eq!(total.output_by_modality[&Modality::Audio], 80);
+    assert_eq!(session.snapshot().total(), Usage::default());
+}